@@ -0,0 +1,15 @@
+#![no_main]
+
+use credential_exchange_types::{B64Url, B32};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Ok(value) = B64Url::try_from(data) {
+        let encoded = String::from(&value);
+        assert_eq!(B64Url::try_from(encoded.as_str()).ok(), Some(value));
+    }
+    if let Ok(value) = B32::try_from(data) {
+        let encoded = String::from(value.clone());
+        assert_eq!(B32::try_from(encoded.as_str()).ok(), Some(value));
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use credential_exchange_types::format::{DateParsing, EditableField};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mut field) = serde_json::from_slice::<EditableField>(data) else {
+        return;
+    };
+
+    let strict = field.date(DateParsing::Strict);
+    let lenient = field.date(DateParsing::Lenient);
+    if let Ok(date) = strict {
+        assert_eq!(lenient.ok(), Some(date));
+    }
+    if field.normalize_date().is_ok() {
+        assert!(field.date(DateParsing::Strict).is_ok());
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use credential_exchange_types::protocol::ExportRequest;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request) = serde_json::from_slice::<ExportRequest>(data) {
+        serde_json::to_vec(&request).expect("a parsed request must serialize");
+    }
+});
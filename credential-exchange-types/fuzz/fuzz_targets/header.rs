@@ -0,0 +1,17 @@
+#![no_main]
+
+use credential_exchange_types::format::Header;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(header) = serde_json::from_slice::<Header>(data) else {
+        return;
+    };
+
+    // Parsing normalizes some values, e.g. timestamps lose their fraction of a second, so the
+    // serialized form is only expected to be stable from the first serialization onwards.
+    let json = serde_json::to_vec(&header).expect("a parsed header must serialize");
+    let reparsed: Header = serde_json::from_slice(&json).expect("a serialized header must parse");
+    let rejson = serde_json::to_vec(&reparsed).expect("a parsed header must serialize");
+    assert_eq!(json, rejson);
+});
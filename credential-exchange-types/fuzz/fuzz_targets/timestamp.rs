@@ -0,0 +1,17 @@
+#![no_main]
+
+use credential_exchange_types::timestamp;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut deserializer = serde_json::Deserializer::from_slice(data);
+    let Ok(datetime) = timestamp::deserialize(&mut deserializer) else {
+        return;
+    };
+
+    // Timestamps are serialized in seconds, dropping any fraction or milliseconds.
+    let json = timestamp::serialize(&datetime, serde_json::value::Serializer)
+        .expect("a timestamp must serialize");
+    let reparsed = timestamp::deserialize(json).expect("a serialized timestamp must parse");
+    assert_eq!(datetime.timestamp(), reparsed.timestamp());
+});
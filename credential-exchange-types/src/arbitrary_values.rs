@@ -0,0 +1,36 @@
+//! Generators for the `arbitrary` feature, used for members whose serialized form does not accept
+//! every value of their type.
+
+use arbitrary::{Result, Unstructured};
+use chrono::{DateTime, Utc};
+
+use crate::timestamp::MILLISECONDS_THRESHOLD;
+
+/// The latest timestamp, in seconds, which is parsed back as seconds rather than milliseconds.
+const MAX_TIMESTAMP: u64 = MILLISECONDS_THRESHOLD - 1;
+
+fn seconds(u: &mut Unstructured) -> Result<u64> {
+    u.int_in_range(0..=MAX_TIMESTAMP)
+}
+
+pub(crate) fn timestamp(u: &mut Unstructured) -> Result<DateTime<Utc>> {
+    let seconds = seconds(u)? as i64;
+    Ok(DateTime::from_timestamp(seconds, 0).unwrap_or_default())
+}
+
+pub(crate) fn optional_timestamp(u: &mut Unstructured) -> Result<Option<DateTime<Utc>>> {
+    if !u.arbitrary::<bool>()? {
+        return Ok(None);
+    }
+    timestamp(u).map(Some)
+}
+
+/// A single label domain name, e.g. `abc.example`, which is a valid host and relying party
+/// identifier.
+pub(crate) fn domain(u: &mut Unstructured) -> Result<String> {
+    let length = u.int_in_range(1..=63)?;
+    let label = (0..length)
+        .map(|_| Ok(char::from(u.int_in_range(b'a'..=b'z')?)))
+        .collect::<Result<String>>()?;
+    Ok(format!("{label}.example"))
+}
@@ -1,10 +1,36 @@
-use data_encoding::{Specification, BASE32_NOPAD, BASE64URL, BASE64URL_NOPAD};
+use std::sync::OnceLock;
+
+use data_encoding::{DecodeKind, Encoding, BASE32_NOPAD, BASE64URL_NOPAD};
+use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
-#[serde(try_from = "&str", into = "String")]
+#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(try_from = "String", into = "String")]
 pub struct B64Url(Vec<u8>);
 
+impl B64Url {
+    /// The maximum size in bytes of an opaque identifier, such as
+    /// [Item::id][crate::format::Item::id].
+    pub const MAX_ID_LENGTH: usize = 64;
+    /// The size in bytes of the identifiers created by [B64Url::generate_id].
+    pub const DEFAULT_ID_LENGTH: usize = 16;
+
+    /// Create a value of `len` cryptographically random bytes. The length is capped at
+    /// [B64Url::MAX_ID_LENGTH] so that the result is always usable as an identifier.
+    pub fn random(len: usize) -> Self {
+        let mut bytes = vec![0; len.min(Self::MAX_ID_LENGTH)];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// Generate a new opaque identifier of [B64Url::DEFAULT_ID_LENGTH] random bytes, suitable for
+    /// the `id` member of any entity in the exchange.
+    pub fn generate_id() -> Self {
+        Self::random(Self::DEFAULT_ID_LENGTH)
+    }
+}
+
 impl From<Vec<u8>> for B64Url {
     fn from(src: Vec<u8>) -> Self {
         Self(src)
@@ -45,39 +71,43 @@ impl std::fmt::Display for B64Url {
     }
 }
 
-/// An error returned when a string is not base64 decodable.
-#[derive(Debug)]
-pub struct NotB64UrlEncoded;
+impl TryFrom<String> for B64Url {
+    type Error = InvalidEncoding;
 
-impl std::fmt::Display for NotB64UrlEncoded {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("Data isn't base64url encoded")
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
     }
 }
 
 impl TryFrom<&str> for B64Url {
-    type Error = NotB64UrlEncoded;
+    type Error = InvalidEncoding;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let specs = BASE64URL.specification();
-        let padding = specs.padding.unwrap();
-        let specs = Specification {
-            check_trailing_bits: false,
-            padding: None,
-            ..specs
-        };
-        let encoding = specs.encoding().unwrap();
-        let sane_string = value.trim_end_matches(padding);
-        encoding
-            .decode(sane_string.as_bytes())
+        let value = value.trim_end_matches('=');
+        base64url()
+            .decode(value.as_bytes())
             .map(Self)
-            .map_err(|_| NotB64UrlEncoded)
+            .map_err(InvalidEncoding::from)
     }
 }
 
+/// [BASE64URL_NOPAD] without the check of the trailing bits, as some exporters emit values whose
+/// unused bits are not zero. The padding is removed before decoding.
+fn base64url() -> &'static Encoding {
+    static ENCODING: OnceLock<Encoding> = OnceLock::new();
+    ENCODING.get_or_init(|| {
+        let mut specification = BASE64URL_NOPAD.specification();
+        specification.check_trailing_bits = false;
+        specification
+            .encoding()
+            .expect("the specification of base64url is valid")
+    })
+}
+
 /// Newtype to encode and decode a vector of bytes to and from Base32.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
-#[serde(try_from = "&str", into = "String")]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(try_from = "String", into = "String")]
 pub struct B32(Vec<u8>);
 
 impl From<Vec<u8>> for B32 {
@@ -109,26 +139,109 @@ impl From<B32> for String {
     }
 }
 
-/// The string was not base32 encoded
-#[derive(Debug)]
-pub struct NotBase32Encoded;
+impl TryFrom<String> for B32 {
+    type Error = InvalidEncoding;
 
-impl std::fmt::Display for NotBase32Encoded {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("Data isn't base32 encoded")
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
     }
 }
 
 impl TryFrom<&str> for B32 {
-    type Error = NotBase32Encoded;
+    type Error = InvalidEncoding;
 
+    /// Decode `value` leniently: letters are accepted in any case, and the characters which are
+    /// not Base32 symbols, such as spaces, dashes and padding, are ignored.
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let symbols = BASE32_NOPAD.specification().symbols;
-        let mut sane_string: String = value.to_ascii_uppercase();
-        sane_string.retain(|c| symbols.contains(c));
-        BASE32_NOPAD
-            .decode(sane_string.as_bytes())
-            .map(Self)
-            .map_err(|_| NotBase32Encoded)
+        let is_symbol = |byte: u8| matches!(byte, b'A'..=b'Z' | b'2'..=b'7');
+        if value.bytes().all(is_symbol) {
+            return BASE32_NOPAD
+                .decode(value.as_bytes())
+                .map(Self)
+                .map_err(InvalidEncoding::from);
+        }
+
+        let symbols: Vec<(usize, u8)> = value
+            .bytes()
+            .enumerate()
+            .map(|(position, byte)| (position, byte.to_ascii_uppercase()))
+            .filter(|(_, byte)| is_symbol(*byte))
+            .collect();
+        let sane: Vec<u8> = symbols.iter().map(|(_, byte)| *byte).collect();
+        BASE32_NOPAD.decode(&sane).map(Self).map_err(|error| {
+            // Report the position in `value` rather than in the filtered symbols.
+            let position = symbols
+                .get(error.position)
+                .map_or(value.len(), |(position, _)| *position);
+            InvalidEncoding::from(data_encoding::DecodeError { position, ..error })
+        })
+    }
+}
+
+/// An error returned when a string is not a valid [B64Url] or [B32] encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidEncoding {
+    /// The character at `position` is not a symbol of the encoding.
+    Symbol { position: usize },
+    /// The string is truncated, its last block starts at `position`.
+    Length { position: usize },
+    /// The unused bits of the symbol at `position`, which is the last one, are not zero.
+    TrailingBits { position: usize },
+    /// The padding starting at `position` is not valid.
+    Padding { position: usize },
+}
+
+impl InvalidEncoding {
+    /// The position in bytes of the error in the decoded string.
+    pub fn position(&self) -> usize {
+        match self {
+            InvalidEncoding::Symbol { position }
+            | InvalidEncoding::Length { position }
+            | InvalidEncoding::TrailingBits { position }
+            | InvalidEncoding::Padding { position } => *position,
+        }
+    }
+}
+
+impl From<data_encoding::DecodeError> for InvalidEncoding {
+    fn from(error: data_encoding::DecodeError) -> Self {
+        let position = error.position;
+        match error.kind {
+            DecodeKind::Symbol => InvalidEncoding::Symbol { position },
+            DecodeKind::Length => InvalidEncoding::Length { position },
+            DecodeKind::Trailing => InvalidEncoding::TrailingBits { position },
+            DecodeKind::Padding => InvalidEncoding::Padding { position },
+        }
+    }
+}
+
+impl std::fmt::Display for InvalidEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidEncoding::Symbol { position } => {
+                write!(
+                    f,
+                    "Data isn't validly encoded, invalid symbol at position {position}"
+                )
+            }
+            InvalidEncoding::Length { position } => {
+                write!(
+                    f,
+                    "Data isn't validly encoded, invalid length at position {position}"
+                )
+            }
+            InvalidEncoding::TrailingBits { position } => {
+                write!(
+                    f,
+                    "Data isn't validly encoded, non-zero trailing bits at position {position}"
+                )
+            }
+            InvalidEncoding::Padding { position } => {
+                write!(
+                    f,
+                    "Data isn't validly encoded, invalid padding at position {position}"
+                )
+            }
+        }
     }
 }
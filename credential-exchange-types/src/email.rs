@@ -0,0 +1,200 @@
+//! The [Email] type of the email addresses stored in accounts and fields.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::format::EditableField;
+
+/// The longest local part of an address allowed by
+/// [RFC5321](https://www.rfc-editor.org/rfc/rfc5321#section-4.5.3.1.1).
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+
+/// An email address, such as `jane@example.com`.
+///
+/// Addresses are validated leniently: they must contain a non empty local part and a domain
+/// separated by an `@`, without whitespace or control characters, but the full
+/// [RFC5322](https://www.rfc-editor.org/rfc/rfc5322#section-3.4.1) grammar is not enforced as
+/// exporters are not consistent about it. The domain is case insensitive: it is lowercased when
+/// [parsed][Email::parse] and ignored when comparing, while the local part is kept as written.
+///
+/// Deserialization accepts any string and keeps it unchanged, so that an account with a missing or
+/// malformed address, e.g. `""`, is still imported, and every address is exported again as it was
+/// written. Use [is_valid][Email::is_valid] to check them.
+#[derive(Clone, Debug, Serialize)]
+#[serde(into = "String")]
+pub struct Email(String);
+
+impl Email {
+    /// Parse an email address, ignoring surrounding whitespace.
+    pub fn parse(value: &str) -> Result<Self, InvalidEmail> {
+        let (local, domain) = split_address(value.trim()).ok_or(InvalidEmail)?;
+        Ok(Self(format!("{local}@{}", domain.to_lowercase())))
+    }
+
+    /// Create an [Email] without validating nor normalizing it, for values which must be kept even
+    /// though they are malformed.
+    pub fn lenient(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+
+    /// Whether the address is valid, which is always the case for [parsed][Email::parse] values
+    /// but not for [deserialized][Deserialize] or [lenient][Email::lenient] ones. The case of the
+    /// domain doesn't matter.
+    pub fn is_valid(&self) -> bool {
+        split_address(&self.0).is_some()
+    }
+
+    /// The address as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The part of the address before the `@`, or the whole value for invalid addresses without
+    /// an `@`.
+    pub fn local_part(&self) -> &str {
+        self.split().0
+    }
+
+    /// The domain of the address, after the `@`, or an empty string for invalid addresses without
+    /// an `@`. It is lowercase for [parsed][Email::parse] addresses.
+    pub fn domain(&self) -> &str {
+        self.split().1
+    }
+
+    fn split(&self) -> (&str, &str) {
+        self.0.rsplit_once('@').unwrap_or((&self.0, ""))
+    }
+
+    /// The local part and the lowercased domain, as compared by the [PartialEq], [Hash] and [Ord]
+    /// implementations.
+    fn normalized(&self) -> (&str, String) {
+        let (local, domain) = self.split();
+        (local, domain.to_lowercase())
+    }
+}
+
+impl PartialEq for Email {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for Email {}
+
+impl std::hash::Hash for Email {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl PartialOrd for Email {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Email {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.normalized().cmp(&other.normalized())
+    }
+}
+
+impl std::fmt::Display for Email {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Email {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Email> for String {
+    fn from(src: Email) -> Self {
+        src.0
+    }
+}
+
+impl TryFrom<&str> for Email {
+    type Error = InvalidEmail;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = InvalidEmail;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl std::str::FromStr for Email {
+    type Err = InvalidEmail;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Email {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Email {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(format!(
+            "user@{}",
+            crate::arbitrary_values::domain(u)?
+        )))
+    }
+}
+
+/// An error returned when a string is not a valid email address.
+#[derive(Debug)]
+pub struct InvalidEmail;
+
+impl std::fmt::Display for InvalidEmail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Data isn't a valid email address")
+    }
+}
+
+impl EditableField {
+    /// Parse the [value][EditableField::value] of this field as an [Email]. This is meant for
+    /// fields of the [email][crate::format::FieldType::Email] type.
+    pub fn email(&self) -> Result<Email, InvalidEmail> {
+        Email::parse(&self.value)
+    }
+
+    /// Parse the [value][EditableField::value] of this field as an [Email] and rewrite it in its
+    /// normalized form. The value is left untouched if it is not a valid address.
+    pub fn normalize_email(&mut self) -> Result<(), InvalidEmail> {
+        self.value = self.email()?.into();
+        Ok(())
+    }
+}
+
+/// Split a valid address into its local part and its domain.
+fn split_address(value: &str) -> Option<(&str, &str)> {
+    let (local, domain) = value.rsplit_once('@')?;
+    let valid_text = |text: &str| {
+        !text.is_empty()
+            && !text
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || c == '@')
+    };
+    let valid_local = valid_text(local) && local.len() <= MAX_LOCAL_PART_LENGTH
+        || local.starts_with('"') && local.ends_with('"') && local.len() > 2;
+    let valid_domain = match domain.strip_prefix('[') {
+        Some(literal) => literal.strip_suffix(']').is_some_and(valid_text),
+        None => domain.split('.').all(valid_text),
+    };
+    (valid_local && valid_domain && value.trim() == value).then_some((local, domain))
+}
@@ -1,37 +1,228 @@
-use serde::{Deserialize, Serialize};
+//! # Credential Exchange Format
+//!
+//! Contains the data model of an exchanged document, starting at its [Header].
+//!
+//! ## Fidelity
+//!
+//! Parsing a valid document and serializing it again yields semantically identical JSON, which
+//! allows this crate to be used as a pass-through between providers:
+//!
+//! - Members which are unknown to this version of the format are captured in the `extra` member of
+//!   the entity or credential they belong to, see [Header::extra].
+//! - Credentials of an unknown type are kept as [Credential::Unknown], and extensions which are not
+//!   recognized as [Extension::Unknown]. Extensions parsed into the external type `E` are only
+//!   preserved as far as `E` itself preserves them.
+//! - Unknown values of enumerations, such as [ItemType] or [FieldType], are kept in their `Unknown`
+//!   variant.
+//!
+//! Semantically identical means that whitespace is not preserved, that known members are written in
+//! their declared order followed by the unknown members sorted by name, that optional members set
+//! to `null` are omitted, and that binary values are written in their canonical encoding, e.g.
+//! base64url without padding. With the `preserve_order` feature, the unknown members and the
+//! members of [unknown extensions][Extension::Unknown] are written in their original order
+//! instead, so that unknown extensions are written back exactly as they were parsed, apart from
+//! whitespace.
 
+use chrono::{DateTime, Utc};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+#[cfg(feature = "assertion")]
+pub use self::assertion::{Assertion, UnsupportedPasskeyKey};
+#[cfg(feature = "protocol")]
+pub use self::consent::ConsentSummary;
+#[cfg(feature = "non-standard-extensions")]
+pub use self::extensions::{
+    AppearanceExtension, AttachmentExtension, ItemState, ItemStateExtension, OrganizationExtension,
+    PasskeyProvenanceExtension, UsageExtension, UsageExtensionBuilder,
+};
+#[cfg(feature = "non-standard-credentials")]
+pub use self::identity::{BirthCertificateCredential, InsurancePolicyCredential};
+use self::redact::{debug_masked, MaskedMap};
 pub use self::{
+    attachment::Documents,
+    builder::{AccountBuilder, BuildError, HeaderBuilder, ItemBuilder},
+    card::CardNetwork,
+    credential_type::CredentialType,
+    date::{parse_date, DateParsing, InvalidDate},
+    delta::{AccountDelta, Delta, ItemHashes},
+    diff::AccountDiff,
     document::{FileCredential, NoteCredential},
+    duplicates::{find_duplicates, DuplicateGroup, DuplicateReason},
+    expiry::{ExpiringCredential, ExpiryReport},
+    extension_set::ExtensionSet,
+    extensions::{
+        EquivalentDomainsExtension, SharedExtension, SharedExtensionBuilder, SharingAccessor,
+        SharingAccessorPermission, SharingAccessorType,
+    },
+    graph::{ItemKey, ReferenceGraph},
+    hash::ContentHash,
     identity::{
         AddressCredential, CreditCardCredential, DriversLicenseCredential,
         IdentityDocumentCredential, PassportCredential, PersonNameCredential,
     },
     login::{
-        BasicAuthCredential, OTPHashAlgorithm, PasskeyCredential, SshKeyCredential, TotpCredential,
+        BasicAuthCredential, OTPHashAlgorithm, PasskeyCredential, SshKeyCredential,
+        SshKeyCredentialBuilder, TotpCredential,
     },
+    merge::{IdCollision, MergePolicy},
+    merge3::{merge3, Conflict, ThreeWayMerge},
+    otpauth::OtpauthUriError,
+    parse_error::ParseError,
     passkey::{Fido2Extensions, Fido2HmacSecret, Fido2LargeBlob, Fido2SupplementalKeys},
+    preview::{
+        preview, ImportOutcome, ImportPreview, ImporterCapabilities, ItemPreview,
+        UnsupportedContent,
+    },
+    redact::RedactionPolicy,
+    registry::{ExtensionRegistry, ResolvedExtension},
+    remap::IdRemapping,
+    report::InteropReport,
+    sanitize::{sanitize_display, UnsafeDisplayString},
+    search::Query,
+    shared::{SharedAccount, SharedHeader},
+    sorting::{compare_titles, ItemOrder},
+    stats::Stats,
+    stream::{HeaderStream, HeaderWriter, StreamEvent},
+    validate::{ValidationIssue, ValidationIssueKind},
 };
-use crate::b64url::B64Url;
+use crate::{b64url::B64Url, trace, uri::Origin, Email, RpId, Uri};
 
+#[cfg(feature = "assertion")]
+mod assertion;
+mod attachment;
+mod billing;
+mod builder;
+mod card;
+#[cfg(feature = "protocol")]
+mod consent;
+mod credential_type;
+mod date;
+mod delta;
+mod diff;
 mod document;
+mod duplicates;
+mod expiry;
+mod extension_set;
+mod extensions;
+#[cfg(test)]
+mod fixtures;
+mod graph;
+mod hash;
 mod identity;
 mod login;
+mod merge;
+mod merge3;
+mod ordering;
+mod otpauth;
+mod parse_error;
 mod passkey;
+mod preview;
+#[cfg(feature = "qr")]
+mod qr;
+mod redact;
+mod registry;
+mod remap;
+mod report;
+mod sanitize;
+mod search;
+mod shared;
+mod sorting;
+mod split;
+mod stats;
+mod stream;
+mod summary;
+#[cfg(feature = "totp")]
+mod totp;
+mod validate;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase", bound(deserialize = "E: Deserialize<'de>"))]
 pub struct Header<E = ()> {
     /// The version of the format definition, The current version is 0.
     pub version: u8,
     /// The name of the exporting app as a [relying party identifier](https://www.w3.org/TR/webauthn-3/#relying-party-identifier).
-    pub exporter: String,
-    /// The UNIX timestamp during at which the export document was completed.
-    pub timestamp: u64,
+    pub exporter: RpId,
+    /// The time, serialized as a UNIX timestamp in seconds, at which the export document was
+    /// completed. See [timestamp][crate::timestamp] for the accepted representations.
+    #[serde(with = "crate::timestamp")]
+    #[cfg_attr(feature = "arbitrary", arbitrary(with = crate::arbitrary_values::timestamp))]
+    pub timestamp: DateTime<Utc>,
     /// The list of [Account]s being exported.
     pub accounts: Vec<Account<E>>,
+    /// This OPTIONAL member contains extensions applying to the whole exchange rather than to a
+    /// specific [Account], such as the reason of the export or the device it was created on.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub extensions: Option<Vec<Extension<E>>>, // default []
+    /// The members which are not known to this version of the format, e.g. members added by a
+    /// later version of the specification or vendor specific members. They are captured so that
+    /// re-serializing a parsed document preserves them instead of silently dropping them. The
+    /// same applies to the `extra` member of the other entities and credentials.
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl<E> Header<E> {
+    /// The [timestamp][Header::timestamp] as a number of seconds since the UNIX epoch, as it was
+    /// represented before being typed as a [DateTime]. Timestamps before the epoch give 0.
+    pub fn timestamp_seconds(&self) -> u64 {
+        u64::try_from(self.timestamp.timestamp()).unwrap_or_default()
+    }
+
+    /// Resolve a [LinkedItem] to the [Item] it refers to. The `account_id` is the
+    /// [Account::id] of the account in which the [LinkedItem] was encountered, and is used when
+    /// the link does not specify an [account][LinkedItem::account] of its own.
+    ///
+    /// Returns [None] if the referenced account or item is not part of this exchange.
+    pub fn resolve(&self, account_id: &B64Url, linked: &LinkedItem) -> Option<&Item<E>> {
+        let account_id = linked.account.as_ref().unwrap_or(account_id);
+        self.accounts
+            .iter()
+            .find(|account| &account.id == account_id)?
+            .resolve(linked)
+    }
+
+    /// Iterate over every [Credential] of every [Item] in all the [Accounts][Account] of this
+    /// exchange.
+    pub fn credentials(&self) -> impl Iterator<Item = &Credential> {
+        self.accounts.iter().flat_map(Account::credentials)
+    }
+
+    /// Iterate over all the credentials of type `T` in this exchange, e.g.
+    /// `header.credentials_of::<TotpCredential>()`.
+    pub fn credentials_of<T: CredentialKind>(&self) -> impl Iterator<Item = &T> {
+        self.credentials().filter_map(T::from_credential)
+    }
+}
+
+impl<E: Serialize> Header<E> {
+    /// Serialize the exchange as JSON indented with two spaces, one member per line and without
+    /// a trailing newline.
+    ///
+    /// Members are written in the order in which they are declared in this crate, followed by
+    /// the [unknown members][Header::extra] sorted by name, or in the order in which they were
+    /// parsed or inserted with the `preserve_order` feature. Combined with
+    /// [Header::normalize_order], the output is reproducible for the same logical content, making
+    /// it suitable for display and archival.
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        trace::debug_span!("serialize", accounts = self.accounts.len(), pretty = true);
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Serialize the exchange as JSON without any whitespace, with the same member order as
+    /// [Header::to_json_pretty].
+    pub fn to_json_compact(&self) -> serde_json::Result<String> {
+        trace::debug_span!("serialize", accounts = self.accounts.len(), pretty = false);
+        serde_json::to_string(self)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase", bound(deserialize = "E: Deserialize<'de>"))]
 pub struct Account<E = ()> {
     /// A unique identifier for the [Account] which is machine generated and an opaque byte
@@ -41,12 +232,12 @@ pub struct Account<E = ()> {
     /// empty string.
     pub user_name: String,
     /// The email used to register the account in the previous provider.
-    pub email: String,
+    pub email: Email,
     /// This OPTIONAL field holds the user’s full name.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub full_name: Option<String>,
     /// This OPTIONAL field defines if the user has set an icon as the account’s avatar.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub icon: Option<String>,
     /// All the collections this account owns. If the user has collections that were shared with
     /// them by another account, it MUST NOT be present in this list.
@@ -55,11 +246,134 @@ pub struct Account<E = ()> {
     /// them by another account, it MUST NOT be present in this list.
     pub items: Vec<Item<E>>,
     /// This OPTIONAL field contains all the extensions to the [Account]’s attributes.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub extensions: Option<Vec<Extension<E>>>, // default []
+    /// The unknown members of the [Account], see [Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl<E> Account<E> {
+    /// Resolve a [LinkedItem] to one of the [Items][Item] owned by this [Account].
+    ///
+    /// Returns [None] if the link refers to another account or if the item is not present. Use
+    /// [Header::resolve] to follow links across the accounts of an exchange.
+    pub fn resolve(&self, linked: &LinkedItem) -> Option<&Item<E>> {
+        if linked
+            .account
+            .as_ref()
+            .is_some_and(|account| account != &self.id)
+        {
+            return None;
+        }
+        self.items.iter().find(|item| item.id == linked.item)
+    }
+
+    /// Iterate over every [Collection] of this [Account], including all nested
+    /// [sub-collections][Collection::sub_collections], in depth-first order. See [CollectionIter]
+    /// for details on the yielded values.
+    pub fn iter_all_collections(&self) -> CollectionIter<'_, E> {
+        CollectionIter::new(Vec::new(), &self.collections)
+    }
+
+    /// Iterate over every [Credential] of every [Item] owned by this [Account].
+    pub fn credentials(&self) -> impl Iterator<Item = &Credential> {
+        self.items.iter().flat_map(|item| item.credentials.iter())
+    }
+
+    /// Iterate over all the credentials of type `T` owned by this [Account], e.g.
+    /// `account.credentials_of::<TotpCredential>()`.
+    pub fn credentials_of<T: CredentialKind>(&self) -> impl Iterator<Item = &T> {
+        self.credentials().filter_map(T::from_credential)
+    }
+
+    /// Iterate over all the [PasskeyCredentials][PasskeyCredential] owned by this [Account].
+    pub fn passkeys(&self) -> impl Iterator<Item = &PasskeyCredential> {
+        self.credentials_of()
+    }
+
+    /// Iterate over all the [BasicAuthCredentials][BasicAuthCredential] owned by this [Account].
+    pub fn basic_auths(&self) -> impl Iterator<Item = &BasicAuthCredential> {
+        self.credentials_of()
+    }
+
+    /// Call `f` with every [LinkedItem] of this [Account], whether it is part of a [Collection],
+    /// at any depth, or of an [ItemReferenceCredential].
+    pub(crate) fn for_each_link_mut(&mut self, mut f: impl FnMut(&mut LinkedItem)) {
+        visit_collections_mut(&mut self.collections, |collections| {
+            for collection in collections {
+                collection.items.iter_mut().for_each(&mut f);
+            }
+        });
+        for credential in self.items.iter_mut().flat_map(|item| &mut item.credentials) {
+            if let Credential::ItemReference(reference) = credential {
+                f(&mut reference.reference);
+            }
+        }
+    }
+
+    /// Find the [Items][Item] owned by this [Account] which are associated with the provided URL.
+    ///
+    /// Only the scheme, host and port are compared: the host is compared case insensitively, a
+    /// missing scheme is treated as `https` and default ports are ignored. The schemes must match,
+    /// e.g. the items of `https://example.com` aren't returned for `http://example.com`. Returns no
+    /// items if the URL cannot be parsed.
+    ///
+    /// The [EquivalentDomainsExtensions][EquivalentDomainsExtension] of the exchange are not
+    /// taken into account, as they could associate the credentials of a website with any other
+    /// one. See [Account::items_for_url_with_equivalent_domains].
+    pub fn items_for_url(&self, url: &str) -> impl Iterator<Item = &Item<E>> {
+        self.items_for_url_with_equivalent_domains(url, [])
+    }
+
+    /// Find the [Items][Item] owned by this [Account] which are associated with the provided URL,
+    /// as [Account::items_for_url], or whose URLs have a host which is part of the same set of
+    /// `equivalent` domains as the URL, regardless of their scheme and port.
+    ///
+    /// The `equivalent` domains must come from a trusted source, e.g. the importer itself or a
+    /// user confirmation.
+    #[cfg_attr(
+        feature = "non-standard-extensions",
+        doc = "The ones of the exchange are returned by [Account::equivalent_domains]."
+    )]
+    pub fn items_for_url_with_equivalent_domains<'a>(
+        &'a self,
+        url: &str,
+        equivalent: impl IntoIterator<Item = &'a EquivalentDomainsExtension>,
+    ) -> impl Iterator<Item = &'a Item<E>> {
+        let origin = Origin::parse(url);
+        let equivalent: Vec<_> = match &origin {
+            Some(origin) => equivalent
+                .into_iter()
+                .filter(|domains| domains.contains(origin))
+                .collect(),
+            None => Vec::new(),
+        };
+        self.items.iter().filter(move |item| {
+            origin
+                .as_ref()
+                .is_some_and(|origin| item_matches(item, origin, &equivalent))
+        })
+    }
+
+    /// Iterate over the [EquivalentDomainsExtensions][EquivalentDomainsExtension] of this
+    /// [Account] and of its [Items][Item], as provided by the exporter.
+    #[cfg(feature = "non-standard-extensions")]
+    pub fn equivalent_domains(&self) -> impl Iterator<Item = &EquivalentDomainsExtension> {
+        std::iter::once(&self.extensions)
+            .chain(self.items.iter().map(|item| &item.extensions))
+            .flatten()
+            .flatten()
+            .filter_map(|extension| match extension {
+                Extension::EquivalentDomains(domains) => Some(domains.as_ref()),
+                _ => None,
+            })
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase", bound(deserialize = "E: Deserialize<'de>"))]
 pub struct Collection<E = ()> {
     /// A unique identifier for the [Collection] which is machine generated and an opaque byte
@@ -68,41 +382,173 @@ pub struct Collection<E = ()> {
     /// The display name of the [Collection].
     pub title: String,
     /// This OPTIONAL field is a subtitle or a description of the [Collection].
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub subtitle: Option<String>,
     /// This OPTIONAL field is a relative path from this file to the icon file acting as this
     /// [Collection]’s avatar.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub icon: Option<String>,
     /// Enumerates all the [LinkedItem] in this [Collection]. A [LinkedItem] contains the necessary
     /// data to indicate which [Items][Item] are part of this [Collection].
     pub items: Vec<LinkedItem>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     /// Enumerates any sub-collections if the provider supports recursive organization.
+    ///
+    /// The nesting depth of parsed documents is bounded by the recursion limit of the
+    /// deserializer, e.g. 128 nested JSON values for `serde_json`, which keeps the recursive
+    /// drop, clone and comparison of collections from overflowing the stack.
     pub sub_collections: Option<Vec<Collection<E>>>, // default []
     /// This enumeration contains all the extensions to the [Collection]’s attributes.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub extensions: Option<Vec<Extension<E>>>, // default []
+    /// The unknown members of the [Collection], see [Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl<E: Clone> Account<E> {
+    /// Clone the metadata of this account, without its items and collections.
+    pub(crate) fn clone_metadata(&self) -> Self {
+        Account {
+            id: self.id.clone(),
+            user_name: self.user_name.clone(),
+            email: self.email.clone(),
+            full_name: self.full_name.clone(),
+            icon: self.icon.clone(),
+            collections: Vec::new(),
+            items: Vec::new(),
+            extensions: self.extensions.clone(),
+            extra: self.extra.clone(),
+        }
+    }
+}
+
+impl<E> Collection<E> {
+    /// Iterate over all the [sub-collections][Collection::sub_collections] of this [Collection],
+    /// recursively, in depth-first order. This [Collection] itself is not yielded, but is the
+    /// first element of every path. See [CollectionIter] for details on the yielded values.
+    pub fn iter_descendants(&self) -> CollectionIter<'_, E> {
+        CollectionIter::new(
+            vec![self],
+            self.sub_collections.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
+/// A depth-first iterator over a tree of [Collections][Collection], created by
+/// [Collection::iter_descendants] and [Account::iter_all_collections].
+///
+/// Each element is a tuple of the path leading to a collection, i.e. its ancestors ordered from
+/// the outermost to its direct parent, and the collection itself. The tree is walked using an
+/// explicit stack rather than recursion, so arbitrarily deep nesting cannot overflow the call
+/// stack.
+pub struct CollectionIter<'a, E = ()> {
+    stack: Vec<(Vec<&'a Collection<E>>, &'a Collection<E>)>,
+}
+
+impl<'a, E> CollectionIter<'a, E> {
+    fn new(path: Vec<&'a Collection<E>>, collections: &'a [Collection<E>]) -> Self {
+        let mut iter = Self { stack: Vec::new() };
+        iter.push(path, collections);
+        iter
+    }
+
+    fn push(&mut self, path: Vec<&'a Collection<E>>, collections: &'a [Collection<E>]) {
+        // Pushed in reverse so that siblings are yielded in their original order.
+        self.stack.extend(
+            collections
+                .iter()
+                .rev()
+                .map(|collection| (path.clone(), collection)),
+        );
+    }
+}
+
+/// Whether one of the URLs of `item` matches `origin`, or has a host which is part of one of the
+/// `equivalent` sets of domains, as described in [Account::items_for_url_with_equivalent_domains].
+pub(crate) fn item_matches<E>(
+    item: &Item<E>,
+    origin: &Origin,
+    equivalent: &[&EquivalentDomainsExtension],
+) -> bool {
+    item.urls()
+        .filter_map(|url| Origin::parse(url.as_str()))
+        .any(|candidate| {
+            candidate.matches(origin)
+                || equivalent
+                    .iter()
+                    .any(|domains| domains.contains(&candidate))
+        })
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Call `f` with `collections` and then with the [sub-collections][Collection::sub_collections] of
+/// every [Collection] in the tree, allowing them to be modified or filtered. The tree is walked
+/// without recursion, and children are visited after `f` has been applied to their parent list.
+pub(crate) fn visit_collections_mut<E>(
+    collections: &mut Vec<Collection<E>>,
+    mut f: impl FnMut(&mut Vec<Collection<E>>),
+) {
+    let mut stack = vec![collections];
+    while let Some(collections) = stack.pop() {
+        f(collections);
+        stack.extend(
+            collections
+                .iter_mut()
+                .filter_map(|collection| collection.sub_collections.as_mut()),
+        );
+    }
+}
+
+impl<'a, E> Iterator for CollectionIter<'a, E> {
+    type Item = (Vec<&'a Collection<E>>, &'a Collection<E>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, collection) = self.stack.pop()?;
+        if let Some(children) = collection.sub_collections.as_deref() {
+            let mut child_path = path.clone();
+            child_path.push(collection);
+            self.push(child_path, children);
+        }
+        Some((path, collection))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase", bound(deserialize = "E: Deserialize<'de>"))]
 pub struct Item<E = ()> {
     /// A unique identifier for the [Item] which is machine generated and an opaque byte sequence
     /// with a maximum size of 64 bytes. It SHOULD NOT be displayed to the user.
     pub id: B64Url,
-    /// The OPTIONAL member contains the UNIX timestamp in seconds at which this item was
-    /// originally created. If this member is not set, but the importing provider requires this
-    /// member in their proprietary data model, the importer SHOULD use the current timestamp
-    /// at the time the provider encounters this [Item].
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub creation_at: Option<u64>,
-    /// This OPTIONAL member contains the UNIX timestamp in seconds of the last modification
-    /// brought to this [Item]. If this member is not set, but the importing provider requires
-    /// this member in their proprietary data model, the importer SHOULD use the current
-    /// timestamp at the time the provider encounters this [Item].
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub modified_at: Option<u64>,
+    /// The OPTIONAL member contains the time, serialized as a UNIX timestamp in seconds, at which
+    /// this item was originally created. If this member is not set, but the importing provider
+    /// requires this member in their proprietary data model, the importer SHOULD use the
+    /// current timestamp at the time the provider encounters this [Item].
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serialization::is_omitted",
+        with = "crate::timestamp::option"
+    )]
+    #[cfg_attr(
+        feature = "arbitrary",
+        arbitrary(with = crate::arbitrary_values::optional_timestamp)
+    )]
+    pub creation_at: Option<DateTime<Utc>>,
+    /// This OPTIONAL member contains the time, serialized as a UNIX timestamp in seconds, of the
+    /// last modification brought to this [Item]. If this member is not set, but the importing
+    /// provider requires this member in their proprietary data model, the importer SHOULD use
+    /// the current timestamp at the time the provider encounters this [Item].
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serialization::is_omitted",
+        with = "crate::timestamp::option"
+    )]
+    #[cfg_attr(
+        feature = "arbitrary",
+        arbitrary(with = crate::arbitrary_values::optional_timestamp)
+    )]
+    pub modified_at: Option<DateTime<Utc>>,
     /// This member contains a hint to the objects in the credentials array. It SHOULD be a member
     /// of [ItemType].
     #[serde(rename = "type")]
@@ -110,25 +556,41 @@ pub struct Item<E = ()> {
     /// This member’s value is the user-defined name or title of the item.
     pub title: String,
     /// This OPTIONAL member is a subtitle or description for the [Item].
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub subtitle: Option<String>,
     /// This OPTIONAL member denotes whether the user has marked the [Item] as a favorite to easily
     /// present in the UI.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub favorite: Option<bool>,
     /// This member contains a set of [Credentials][Item::credentials] that SHOULD be associated to
     /// the type.
     pub credentials: Vec<Credential>,
     /// This OPTIONAL member contains user-defined tags that they may use to organize the item.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub tags: Option<Vec<String>>, // default []
     /// This member contains all the extensions the exporter MAY have to define the [Item] type
     /// that is being exported to be as complete of an export as possible.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub extensions: Option<Vec<Extension<E>>>, // default []
+    /// The unknown members of the [Item], see [Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl<E> Item<E> {
+    /// Iterate over all the URLs this [Item] is associated with through its
+    /// [BasicAuthCredentials][BasicAuthCredential].
+    pub fn urls(&self) -> impl Iterator<Item = &Uri> {
+        self.credentials
+            .iter()
+            .filter_map(BasicAuthCredential::from_credential)
+            .flat_map(|credential| credential.urls.iter())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "kebab-case")]
 pub enum ItemType {
     /// An [Item] that SHOULD contain any of the following [Credential] types:
@@ -148,12 +610,22 @@ pub enum ItemType {
     /// - [DriversLicenseCredential]
     /// - [IdentityDocumentCredential]
     /// - [PassportCredential]
+    #[cfg_attr(
+        feature = "non-standard-credentials",
+        doc = "- [BirthCertificateCredential]"
+    )]
+    #[cfg_attr(
+        feature = "non-standard-credentials",
+        doc = "- [InsurancePolicyCredential]"
+    )]
     Identity,
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
     #[serde(untagged)]
     Unknown(String),
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct LinkedItem {
     /// The [Item’s id][Item::id] that this [LinkedItem] refers to. Note that this [Item] might not
     /// be sent as part of the current exchange.
@@ -161,20 +633,78 @@ pub struct LinkedItem {
     /// This OPTIONAL member indicates the [Account’s id][Account::id] the referenced [Item]
     /// belongs to. If not present, the [Item] belongs to the current [Account] being
     /// exchanged.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub account: Option<B64Url>,
+    /// The unknown members of the [LinkedItem], see [Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(tag = "name", rename_all = "kebab-case")]
 pub enum Extension<E = ()> {
+    #[cfg(feature = "non-standard-extensions")]
+    Appearance(Box<AppearanceExtension>),
+    #[cfg(feature = "non-standard-extensions")]
+    Attachment(Box<AttachmentExtension>),
+    #[cfg(feature = "non-standard-extensions")]
+    EquivalentDomains(Box<EquivalentDomainsExtension>),
+    #[cfg(feature = "non-standard-extensions")]
+    Organization(Box<OrganizationExtension>),
+    #[cfg(feature = "non-standard-extensions")]
+    PasskeyProvenance(Box<PasskeyProvenanceExtension>),
+    Shared(Box<SharedExtension>),
+    #[cfg(feature = "non-standard-extensions")]
+    ItemState(Box<ItemStateExtension>),
+    #[cfg(feature = "non-standard-extensions")]
+    Usage(Box<UsageExtension>),
     #[serde(untagged)]
     External(E),
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
     #[serde(untagged)]
     Unknown(serde_json::Value),
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl<E> Extension<E> {
+    /// The `name` member of the extension, or [None] for [external][Extension::External]
+    /// extensions and unknown extensions without a name.
+    pub fn name(&self) -> Option<&str> {
+        Some(match self {
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Appearance(_) => "appearance",
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Attachment(_) => "attachment",
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::EquivalentDomains(_) => "equivalent-domains",
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::ItemState(_) => "item-state",
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Organization(_) => "organization",
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::PasskeyProvenance(_) => "passkey-provenance",
+            Extension::Shared(_) => "shared",
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Usage(_) => "usage",
+            Extension::External(_) => return None,
+            Extension::Unknown(value) => value.get("name")?.as_str()?,
+        })
+    }
+
+    /// All the members of an [unknown extension][Extension::Unknown], including its `name`, sorted
+    /// by name, or in the order in which they were parsed with the `preserve_order` feature.
+    /// Returns [None] for the other variants.
+    pub fn payload(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        match self {
+            Extension::Unknown(value) => value.as_object(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum Credential {
     BasicAuth(Box<BasicAuthCredential>),
@@ -190,17 +720,138 @@ pub enum Credential {
     IdentityDocument(Box<IdentityDocumentCredential>),
     Passport(Box<PassportCredential>),
     PersonName(Box<PersonNameCredential>),
+    #[cfg(feature = "non-standard-credentials")]
+    BirthCertificate(Box<BirthCertificateCredential>),
+    #[cfg(feature = "non-standard-credentials")]
+    InsurancePolicy(Box<InsurancePolicyCredential>),
+    /// A credential of a type which is not known to this version of the format. The `type`
+    /// member and all the other members are kept, so the credential is serialized again as-is.
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
     #[serde(untagged)]
     Unknown {
+        #[serde(rename = "type")]
         ty: String,
         #[serde(flatten)]
         content: serde_json::Map<String, serde_json::Value>,
     },
 }
 
+impl std::fmt::Debug for Credential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Credential::BasicAuth(credential) => {
+                f.debug_tuple("BasicAuth").field(credential).finish()
+            }
+            Credential::Passkey(credential) => f.debug_tuple("Passkey").field(credential).finish(),
+            Credential::CreditCard(credential) => {
+                f.debug_tuple("CreditCard").field(credential).finish()
+            }
+            Credential::Note(credential) => f.debug_tuple("Note").field(credential).finish(),
+            Credential::Totp(credential) => f.debug_tuple("Totp").field(credential).finish(),
+            Credential::DriversLicense(credential) => {
+                f.debug_tuple("DriversLicense").field(credential).finish()
+            }
+            Credential::Address(credential) => f.debug_tuple("Address").field(credential).finish(),
+            Credential::ItemReference(credential) => {
+                f.debug_tuple("ItemReference").field(credential).finish()
+            }
+            Credential::File(credential) => f.debug_tuple("File").field(credential).finish(),
+            Credential::SshKey(credential) => f.debug_tuple("SshKey").field(credential).finish(),
+            Credential::IdentityDocument(credential) => {
+                f.debug_tuple("IdentityDocument").field(credential).finish()
+            }
+            Credential::Passport(credential) => {
+                f.debug_tuple("Passport").field(credential).finish()
+            }
+            Credential::PersonName(credential) => {
+                f.debug_tuple("PersonName").field(credential).finish()
+            }
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::BirthCertificate(credential) => {
+                f.debug_tuple("BirthCertificate").field(credential).finish()
+            }
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(credential) => {
+                f.debug_tuple("InsurancePolicy").field(credential).finish()
+            }
+            Credential::Unknown { ty, content } => f
+                .debug_struct("Unknown")
+                .field("ty", ty)
+                .field("content", &MaskedMap(content))
+                .finish(),
+        }
+    }
+}
+
+/// Implemented by every concrete credential type held by a [Credential] variant, so that they can
+/// be extracted generically, e.g. with [Account::credentials_of].
+///
+/// These types can also be converted into a [Credential] with [From], and extracted from one with
+/// [TryFrom], which gives back the [Credential] if it holds a different type.
+pub trait CredentialKind: 'static {
+    /// Returns the inner credential if `credential` holds a value of this type.
+    fn from_credential(credential: &Credential) -> Option<&Self>;
+}
+
+macro_rules! impl_credential_kind {
+    ($($(#[$meta:meta])* $variant:ident => $ty:ty),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            impl CredentialKind for $ty {
+                fn from_credential(credential: &Credential) -> Option<&Self> {
+                    match credential {
+                        Credential::$variant(inner) => Some(inner),
+                        _ => None,
+                    }
+                }
+            }
+
+            $(#[$meta])*
+            impl From<$ty> for Credential {
+                fn from(credential: $ty) -> Self {
+                    Credential::$variant(Box::new(credential))
+                }
+            }
+
+            $(#[$meta])*
+            impl TryFrom<Credential> for $ty {
+                type Error = Credential;
+
+                fn try_from(credential: Credential) -> Result<Self, Self::Error> {
+                    match credential {
+                        Credential::$variant(inner) => Ok(*inner),
+                        credential => Err(credential),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_credential_kind! {
+    BasicAuth => BasicAuthCredential,
+    Passkey => PasskeyCredential,
+    CreditCard => CreditCardCredential,
+    Note => NoteCredential,
+    Totp => TotpCredential,
+    DriversLicense => DriversLicenseCredential,
+    Address => AddressCredential,
+    ItemReference => ItemReferenceCredential,
+    File => FileCredential,
+    SshKey => SshKeyCredential,
+    IdentityDocument => IdentityDocumentCredential,
+    Passport => PassportCredential,
+    PersonName => PersonNameCredential,
+    #[cfg(feature = "non-standard-credentials")]
+    BirthCertificate => BirthCertificateCredential,
+    #[cfg(feature = "non-standard-credentials")]
+    InsurancePolicy => InsurancePolicyCredential,
+}
+
 /// An [ItemReferenceCredential] is a pointer to another [Item], denoting that the two items MAY be
 /// logically linked together.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct ItemReferenceCredential {
     /// A [LinkedItem] which references another [Item].
@@ -209,9 +860,18 @@ pub struct ItemReferenceCredential {
     /// [Account]. However, the other item MAY NOT be in the exchange if it is owned by a different
     /// account and shared with the currenly exchanged account.
     pub reference: LinkedItem,
+    /// The unknown members of the [ItemReferenceCredential], see [Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+debug_masked!(ItemReferenceCredential { reference });
+
+/// The [Debug] output of an [EditableField] masks its value when it is a
+/// [concealed string][FieldType::ConcealedString].
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct EditableField {
     /// A unique identifier for the [EditableField] which is machine generated and an opaque byte
@@ -228,14 +888,63 @@ pub struct EditableField {
     /// SHOULD ignore any unknown values and default to [string][FieldType::String].
     pub field_type: FieldType,
     /// This member contains the [fieldType][EditableField::field_type] defined by the user.
+    ///
+    /// Some exporters emit native JSON booleans for [boolean][FieldType::Boolean] fields, these
+    /// are accepted and converted to the `"true"` or `"false"` string mandated by the
+    /// specification.
+    #[serde(deserialize_with = "deserialize_field_value")]
     pub value: String,
     /// This member contains a user facing value describing the value stored. This value MAY be
     /// user defined.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub label: Option<String>,
+    /// This OPTIONAL member contains all the extensions to the [EditableField]’s attributes. As
+    /// fields are not parameterized over an external extension type, these are only ever parsed
+    /// as [Extension::Unknown].
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub extensions: Option<Vec<Extension>>, // default []
+    /// The unknown members of the [EditableField], see [Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+impl std::fmt::Debug for EditableField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        redact::debug_field(self, f, self.field_type == FieldType::ConcealedString)
+    }
+}
+
+/// Deserialize the [value][EditableField::value] of an [EditableField], accepting native booleans
+/// in addition to strings.
+fn deserialize_field_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    struct FieldValueVisitor;
+
+    impl Visitor<'_> for FieldValueVisitor {
+        type Value = String;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a string or a boolean")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(value.to_owned())
+        }
+
+        fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+            Ok(value.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(FieldValueVisitor)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "kebab-case")]
 pub enum FieldType {
     /// A UTF-8 encoded string value which is unconcealed and does not have a specified format.
@@ -254,6 +963,119 @@ pub enum FieldType {
     /// A string value representing a calendar date which follows the format specified in
     /// [RFC3339](https://www.rfc-editor.org/rfc/rfc3339).
     Date,
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
     #[serde(untagged)]
     Unknown(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Parse `document` and serialize it back.
+    fn round_trip(document: &serde_json::Value) -> serde_json::Value {
+        let header: Header = serde_json::from_value(document.clone()).expect("the header is valid");
+        serde_json::to_value(&header).expect("the header serializes")
+    }
+
+    #[test]
+    fn samples_round_trip() {
+        for (name, sample) in crate::samples::all() {
+            let document: serde_json::Value = serde_json::from_str(sample).unwrap();
+            assert_eq!(round_trip(&document), document, "{name}");
+        }
+    }
+
+    #[test]
+    fn unknown_members_round_trip() {
+        let document = json!({
+            "version": 0,
+            "exporter": "Exporter.Example.COM.",
+            "timestamp": 1705228800,
+            "futureHeaderMember": { "nested": [1, 2, 3] },
+            "extensions": [{ "name": "future-extension", "value": [true, null] }],
+            "accounts": [{
+                "id": "AQ",
+                "userName": "jane.doe",
+                "email": "Jane.Doe@Example.com",
+                "futureAccountMember": "account",
+                "collections": [{
+                    "id": "Ag",
+                    "title": "Work",
+                    "items": [{ "item": "Aw", "futureLinkMember": 1 }],
+                    "futureCollectionMember": false,
+                }],
+                "items": [{
+                    "id": "Aw",
+                    "type": "future-item-type",
+                    "title": "Example",
+                    "futureItemMember": { "a": "b" },
+                    "credentials": [
+                        {
+                            "type": "basic-auth",
+                            "urls": ["https://Example.com/", "example.com/login"],
+                            "username": {
+                                "id": "BA",
+                                "fieldType": "future-field-type",
+                                "value": "jane.doe",
+                                "futureFieldMember": 2,
+                            },
+                            "futureCredentialMember": [],
+                        },
+                        { "type": "future-credential", "secret": { "value": "hunter2" } },
+                    ],
+                    "extensions": [{ "name": "future-item-extension" }],
+                }],
+            }],
+        });
+
+        let header: Header = serde_json::from_value(document.clone()).unwrap();
+        assert_eq!(
+            header.exporter,
+            RpId::parse("exporter.example.com").unwrap()
+        );
+        let item = &header.accounts[0].items[0];
+        assert!(matches!(item.ty, ItemType::Unknown(_)));
+        assert!(matches!(item.credentials[0], Credential::BasicAuth(_)));
+        assert!(matches!(item.credentials[1], Credential::Unknown { .. }));
+        assert!(matches!(
+            header.extensions.as_deref(),
+            Some([Extension::Unknown(_)])
+        ));
+        assert_eq!(round_trip(&document), document);
+    }
+
+    #[cfg(not(feature = "non-standard-extensions"))]
+    #[test]
+    fn non_standard_extensions_are_unknown() {
+        let extensions = [
+            json!({ "name": "item-state", "state": "archived" }),
+            json!({ "name": "usage", "lastUsedAt": 1705228800, "useCount": 3 }),
+            json!({ "name": "appearance", "color": "#1e90ff", "sortOrder": 2 }),
+            json!({ "name": "attachment", "file": "AQ", "mimeType": "application/pdf" }),
+            json!({ "name": "equivalent-domains", "domains": ["amazon.com", "amazon.de"] }),
+            json!({ "name": "passkey-provenance", "credentialId": "AQ", "backupState": true }),
+            json!({ "name": "organization", "organizationId": "AQ", "organizationName": "Acme" }),
+        ];
+        for extension in extensions {
+            let parsed: Extension = serde_json::from_value(extension.clone()).unwrap();
+            assert!(matches!(parsed, Extension::Unknown(_)), "{extension}");
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), extension);
+        }
+    }
+
+    #[cfg(feature = "testdata")]
+    #[test]
+    fn generated_documents_round_trip() {
+        for seed in 0..8 {
+            let header = crate::testdata::TestData::default()
+                .seed(seed)
+                .generate()
+                .header;
+            let json = serde_json::to_string(&header).unwrap();
+            assert_eq!(serde_json::from_str::<Header>(&json).unwrap(), header);
+        }
+    }
+}
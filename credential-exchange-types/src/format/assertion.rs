@@ -0,0 +1,78 @@
+//! # Passkey Assertions
+//!
+//! Produces [WebAuthn assertions](https://www.w3.org/TR/webauthn-3/#authenticatorgetassertion)
+//! with imported [PasskeyCredentials][PasskeyCredential], so that importing providers can check
+//! that a passkey works before surfacing it to the user. Only the `ES256` and `EdDSA` (Ed25519)
+//! algorithms are supported.
+
+use p256::{ecdsa::signature::Signer, pkcs8::DecodePrivateKey};
+use sha2::{Digest, Sha256};
+
+use super::PasskeyCredential;
+
+/// The [user present](https://www.w3.org/TR/webauthn-3/#authdata-flags-up) flag.
+const FLAG_UP: u8 = 0x01;
+/// The [user verified](https://www.w3.org/TR/webauthn-3/#authdata-flags-uv) flag.
+const FLAG_UV: u8 = 0x04;
+
+/// A WebAuthn assertion, as returned by [PasskeyCredential::assert].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assertion {
+    /// The [authenticator data](https://www.w3.org/TR/webauthn-3/#authenticator-data): the
+    /// SHA-256 hash of the [RP ID][PasskeyCredential::rp_id], the flags and a zero signature
+    /// counter, as imported passkeys MUST NOT increment their counter.
+    pub authenticator_data: Vec<u8>,
+    /// The [assertion signature](https://www.w3.org/TR/webauthn-3/#assertion-signature) over the
+    /// authenticator data and the hash of the client data. `ES256` signatures are ASN.1 DER
+    /// encoded, `EdDSA` signatures are raw.
+    pub signature: Vec<u8>,
+}
+
+impl PasskeyCredential {
+    /// Sign an assertion for `client_data_json`, the serialized
+    /// [client data](https://www.w3.org/TR/webauthn-3/#client-data) of the ceremony, with the
+    /// [private key][PasskeyCredential::key] of this passkey. The user present flag is always set,
+    /// the user verified flag is set if `user_verified` is true.
+    pub fn assert(
+        &self,
+        client_data_json: &[u8],
+        user_verified: bool,
+    ) -> Result<Assertion, UnsupportedPasskeyKey> {
+        let mut authenticator_data = Sha256::digest(self.rp_id.normalized()).to_vec();
+        authenticator_data.push(if user_verified {
+            FLAG_UP | FLAG_UV
+        } else {
+            FLAG_UP
+        });
+        authenticator_data.extend_from_slice(&0u32.to_be_bytes());
+
+        let mut message = authenticator_data.clone();
+        message.extend_from_slice(&Sha256::digest(client_data_json));
+
+        let key = self.key.as_ref();
+        let signature = if let Ok(key) = p256::ecdsa::SigningKey::from_pkcs8_der(key) {
+            let signature: p256::ecdsa::Signature = key.sign(&message);
+            signature.to_der().as_bytes().to_vec()
+        } else if let Ok(key) = ed25519_dalek::SigningKey::from_pkcs8_der(key) {
+            key.sign(&message).to_bytes().to_vec()
+        } else {
+            return Err(UnsupportedPasskeyKey);
+        };
+
+        Ok(Assertion {
+            authenticator_data,
+            signature,
+        })
+    }
+}
+
+/// An error returned when the [key][PasskeyCredential::key] of a passkey is not a PKCS#8 encoded
+/// `ES256` or `EdDSA` private key.
+#[derive(Debug)]
+pub struct UnsupportedPasskeyKey;
+
+impl std::fmt::Display for UnsupportedPasskeyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Data isn't a supported PKCS#8 passkey private key")
+    }
+}
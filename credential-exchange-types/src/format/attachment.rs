@@ -0,0 +1,72 @@
+//! # Attachments
+//!
+//! Attaches files to [Items][Item]. The metadata of a file is held by a [FileCredential] of the
+//! item, while its content is stored separately, in the `documents` folder of an export archive,
+//! under the [id][FileCredential::id] of the credential.
+
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+
+use super::{Credential, FileCredential, Item};
+use crate::B64Url;
+
+/// The content of the files of an exchange, by [FileCredential::id].
+pub type Documents = BTreeMap<B64Url, Vec<u8>>;
+
+impl FileCredential {
+    /// Describe a file named `name` holding `content`, with a new random id.
+    pub fn from_content(name: impl Into<String>, content: &[u8]) -> Self {
+        FileCredential {
+            id: B64Url::generate_id(),
+            name: name.into(),
+            decrypted_size: content.len() as u64,
+            integration_hash: B64Url::from(Sha256::digest(content).to_vec()),
+            extra: Default::default(),
+        }
+    }
+
+    /// Whether `content` has the [size][FileCredential::decrypted_size] and the
+    /// [hash][FileCredential::integration_hash] of this file.
+    pub fn verify(&self, content: &[u8]) -> bool {
+        self.decrypted_size == content.len() as u64
+            && self.integration_hash.as_ref() == &Sha256::digest(content)[..]
+    }
+}
+
+impl<E> Item<E> {
+    /// Attach a file named `name` holding `content` to this item: a [FileCredential] is added to
+    /// the item, and the content is added to `documents` under its id. Returns the
+    /// [id][FileCredential::id] of the new credential.
+    pub fn attach_file(
+        &mut self,
+        name: impl Into<String>,
+        content: Vec<u8>,
+        documents: &mut Documents,
+    ) -> B64Url {
+        let file = FileCredential::from_content(name, &content);
+        let id = file.id.clone();
+        documents.insert(id.clone(), content);
+        self.credentials.push(Credential::File(Box::new(file)));
+        id
+    }
+
+    /// The files attached to this item with their content, as found in `documents`. Files which
+    /// are missing from `documents`, or whose content does not [verify][FileCredential::verify],
+    /// are skipped.
+    pub fn attached_files<'a>(
+        &'a self,
+        documents: &'a Documents,
+    ) -> impl Iterator<Item = (&'a FileCredential, &'a [u8])> {
+        self.credentials
+            .iter()
+            .filter_map(|credential| match credential {
+                Credential::File(file) => Some(file.as_ref()),
+                _ => None,
+            })
+            .filter_map(|file| {
+                let content = documents.get(&file.id)?;
+                file.verify(content).then_some((file, content.as_slice()))
+            })
+    }
+}
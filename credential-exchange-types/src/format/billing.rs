@@ -0,0 +1,71 @@
+//! # Billing Addresses
+//!
+//! Links a [CreditCardCredential][super::CreditCardCredential] to its billing address. The card
+//! [Item] holds an [ItemReferenceCredential] pointing to the [Item] holding the
+//! [AddressCredential], so that the address can be shared with other items.
+
+use super::{Account, AddressCredential, Credential, Item, ItemReferenceCredential, LinkedItem};
+use crate::B64Url;
+
+impl<E> Account<E> {
+    /// Link the item `card`, which holds a credit card, to the item `address`, which holds an
+    /// address, by adding an [ItemReferenceCredential] to `card`. Nothing is added if the link
+    /// already exists, or if both credentials are held by the same item.
+    ///
+    /// Returns `false` if either item is missing from this [Account] or does not hold the expected
+    /// credential.
+    pub fn link_billing_address(&mut self, card: &B64Url, address: &B64Url) -> bool {
+        let holds = |id: &B64Url, f: fn(&Credential) -> bool| {
+            self.items
+                .iter()
+                .any(|item| &item.id == id && item.credentials.iter().any(f))
+        };
+        if !holds(card, |credential| {
+            matches!(credential, Credential::CreditCard(_))
+        }) || !holds(address, |credential| {
+            matches!(credential, Credential::Address(_))
+        }) {
+            return false;
+        }
+
+        let Some(item) = self.items.iter_mut().find(|item| &item.id == card) else {
+            return false;
+        };
+        let linked = item.credentials.iter().any(|credential| {
+            matches!(credential, Credential::ItemReference(reference)
+                if reference.reference.item == *address && reference.reference.account.is_none())
+        });
+        if !linked && card != address {
+            item.credentials.push(Credential::ItemReference(Box::new(
+                ItemReferenceCredential {
+                    reference: LinkedItem {
+                        item: address.clone(),
+                        account: None,
+                        extra: Default::default(),
+                    },
+                    extra: Default::default(),
+                },
+            )));
+        }
+        true
+    }
+
+    /// The billing address of the credit card held by `card`: the first [AddressCredential] of the
+    /// items of this [Account] referenced by `card`, or else the first one held by `card` itself.
+    pub fn billing_address<'a>(&'a self, card: &'a Item<E>) -> Option<&'a AddressCredential> {
+        let referenced = card
+            .credentials
+            .iter()
+            .filter_map(|credential| match credential {
+                Credential::ItemReference(reference) => self.resolve(&reference.reference),
+                _ => None,
+            })
+            .flat_map(|item| &item.credentials);
+        referenced
+            .chain(&card.credentials)
+            .find_map(|credential| match credential {
+                Credential::Address(address) => Some(address.as_ref()),
+                _ => None,
+            })
+    }
+}
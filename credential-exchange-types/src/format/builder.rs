@@ -0,0 +1,330 @@
+//! # Builders
+//!
+//! Fluent construction of [Headers][Header], [Accounts][Account] and [Items][Item] for exporters,
+//! which would otherwise fill every member and nested vector by hand. The builders generate the
+//! identifiers and timestamps which aren't provided, and check the required members when building.
+
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+
+use super::{Account, Collection, Credential, Extension, Header, Item, ItemType};
+use crate::{B64Url, Email, RpId};
+
+/// An error returned when a required member is missing from a builder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// No [exporter][HeaderBuilder::exporter] was provided.
+    MissingExporter,
+    /// No [email][AccountBuilder::email] was provided.
+    MissingEmail,
+    /// No [type][ItemBuilder::ty] was provided.
+    MissingType,
+    /// No [title][ItemBuilder::title] was provided, or it is empty.
+    MissingTitle,
+    /// A provided identifier is longer than [B64Url::MAX_ID_LENGTH] bytes.
+    IdTooLong,
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BuildError::MissingExporter => "Exporter is missing",
+            BuildError::MissingEmail => "Email is missing",
+            BuildError::MissingType => "Item type is missing",
+            BuildError::MissingTitle => "Title is missing",
+            BuildError::IdTooLong => "Identifier is longer than 64 bytes",
+        })
+    }
+}
+
+impl<E> Header<E> {
+    /// Start building a [Header] without any account.
+    pub fn builder() -> HeaderBuilder<E> {
+        HeaderBuilder {
+            exporter: None,
+            timestamp: None,
+            accounts: Vec::new(),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+/// Builder for [Header], created by [Header::builder].
+#[derive(Clone, Debug)]
+pub struct HeaderBuilder<E = ()> {
+    exporter: Option<RpId>,
+    timestamp: Option<DateTime<Utc>>,
+    accounts: Vec<Account<E>>,
+    extensions: Vec<Extension<E>>,
+}
+
+impl<E> HeaderBuilder<E> {
+    /// Set the relying party identifier of the exporting app. This is required.
+    pub fn exporter(mut self, exporter: RpId) -> Self {
+        self.exporter = Some(exporter);
+        self
+    }
+
+    /// Set the time at which the export was completed. Defaults to the time of
+    /// [build][HeaderBuilder::build].
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Add an [Account] to the exchange.
+    pub fn account(mut self, account: Account<E>) -> Self {
+        self.accounts.push(account);
+        self
+    }
+
+    /// Add an extension applying to the whole exchange.
+    pub fn extension(mut self, extension: Extension<E>) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Create the [Header], for version 0 of the format.
+    pub fn build(self) -> Result<Header<E>, BuildError> {
+        Ok(Header {
+            version: 0,
+            exporter: self.exporter.ok_or(BuildError::MissingExporter)?,
+            timestamp: self.timestamp.unwrap_or_else(now),
+            accounts: self.accounts,
+            extensions: non_empty(self.extensions),
+            extra: Default::default(),
+        })
+    }
+}
+
+impl<E> Account<E> {
+    /// Start building an [Account] without any collection or item.
+    pub fn builder() -> AccountBuilder<E> {
+        AccountBuilder {
+            id: None,
+            user_name: String::new(),
+            email: None,
+            full_name: None,
+            icon: None,
+            collections: Vec::new(),
+            items: Vec::new(),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+/// Builder for [Account], created by [Account::builder].
+#[derive(Clone, Debug)]
+pub struct AccountBuilder<E = ()> {
+    id: Option<B64Url>,
+    user_name: String,
+    email: Option<Email>,
+    full_name: Option<String>,
+    icon: Option<String>,
+    collections: Vec<Collection<E>>,
+    items: Vec<Item<E>>,
+    extensions: Vec<Extension<E>>,
+}
+
+impl<E> AccountBuilder<E> {
+    /// Set the identifier of the account. Defaults to a [generated][B64Url::generate_id] one.
+    pub fn id(mut self, id: B64Url) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the pseudonym of the account. Defaults to an empty string.
+    pub fn user_name(mut self, user_name: impl Into<String>) -> Self {
+        self.user_name = user_name.into();
+        self
+    }
+
+    /// Set the email used to register the account. This is required.
+    pub fn email(mut self, email: Email) -> Self {
+        self.email = Some(email);
+        self
+    }
+
+    /// Set the full name of the user.
+    pub fn full_name(mut self, full_name: impl Into<String>) -> Self {
+        self.full_name = Some(full_name.into());
+        self
+    }
+
+    /// Set the icon of the account.
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Add a top-level [Collection] to the account.
+    pub fn collection(mut self, collection: Collection<E>) -> Self {
+        self.collections.push(collection);
+        self
+    }
+
+    /// Add an [Item] to the account.
+    pub fn item(mut self, item: Item<E>) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Add an extension to the account.
+    pub fn extension(mut self, extension: Extension<E>) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Create the [Account].
+    pub fn build(self) -> Result<Account<E>, BuildError> {
+        Ok(Account {
+            id: checked_id(self.id)?,
+            user_name: self.user_name,
+            email: self.email.ok_or(BuildError::MissingEmail)?,
+            full_name: self.full_name,
+            icon: self.icon,
+            collections: self.collections,
+            items: self.items,
+            extensions: non_empty(self.extensions),
+            extra: Default::default(),
+        })
+    }
+}
+
+impl<E> Item<E> {
+    /// Start building an [Item] without any credential.
+    pub fn builder() -> ItemBuilder<E> {
+        ItemBuilder {
+            id: None,
+            creation_at: None,
+            modified_at: None,
+            ty: None,
+            title: String::new(),
+            subtitle: None,
+            favorite: None,
+            credentials: Vec::new(),
+            tags: Vec::new(),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+/// Builder for [Item], created by [Item::builder].
+#[derive(Clone, Debug)]
+pub struct ItemBuilder<E = ()> {
+    id: Option<B64Url>,
+    creation_at: Option<DateTime<Utc>>,
+    modified_at: Option<DateTime<Utc>>,
+    ty: Option<ItemType>,
+    title: String,
+    subtitle: Option<String>,
+    favorite: Option<bool>,
+    credentials: Vec<Credential>,
+    tags: Vec<String>,
+    extensions: Vec<Extension<E>>,
+}
+
+impl<E> ItemBuilder<E> {
+    /// Set the identifier of the item. Defaults to a [generated][B64Url::generate_id] one.
+    pub fn id(mut self, id: B64Url) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the time at which the item was created. Defaults to the time of
+    /// [build][ItemBuilder::build].
+    pub fn creation_at(mut self, creation_at: DateTime<Utc>) -> Self {
+        self.creation_at = Some(creation_at);
+        self
+    }
+
+    /// Set the time at which the item was last modified. Defaults to the
+    /// [creation time][ItemBuilder::creation_at].
+    pub fn modified_at(mut self, modified_at: DateTime<Utc>) -> Self {
+        self.modified_at = Some(modified_at);
+        self
+    }
+
+    /// Set the type of the item. This is required.
+    pub fn ty(mut self, ty: ItemType) -> Self {
+        self.ty = Some(ty);
+        self
+    }
+
+    /// Set the title of the item. This is required and must not be empty.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Set the subtitle of the item.
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// Set whether the user marked the item as a favorite.
+    pub fn favorite(mut self, favorite: bool) -> Self {
+        self.favorite = Some(favorite);
+        self
+    }
+
+    /// Add a credential to the item, e.g. a [BasicAuthCredential][super::BasicAuthCredential].
+    pub fn credential(mut self, credential: impl Into<Credential>) -> Self {
+        self.credentials.push(credential.into());
+        self
+    }
+
+    /// Add a tag to the item.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Add an extension to the item.
+    pub fn extension(mut self, extension: Extension<E>) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Create the [Item].
+    pub fn build(self) -> Result<Item<E>, BuildError> {
+        let id = checked_id(self.id)?;
+        let ty = self.ty.ok_or(BuildError::MissingType)?;
+        if self.title.is_empty() {
+            return Err(BuildError::MissingTitle);
+        }
+        let creation_at = self.creation_at.unwrap_or_else(now);
+        Ok(Item {
+            id,
+            creation_at: Some(creation_at),
+            modified_at: Some(self.modified_at.unwrap_or(creation_at)),
+            ty,
+            title: self.title,
+            subtitle: self.subtitle,
+            favorite: self.favorite,
+            credentials: self.credentials,
+            tags: non_empty(self.tags),
+            extensions: non_empty(self.extensions),
+            extra: Default::default(),
+        })
+    }
+}
+
+/// The provided identifier if it fits the specification, or a generated one.
+fn checked_id(id: Option<B64Url>) -> Result<B64Url, BuildError> {
+    match id {
+        Some(id) if id.as_ref().len() > B64Url::MAX_ID_LENGTH => Err(BuildError::IdTooLong),
+        Some(id) => Ok(id),
+        None => Ok(B64Url::generate_id()),
+    }
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    (!values.is_empty()).then_some(values)
+}
+
+fn now() -> DateTime<Utc> {
+    SystemTime::now().into()
+}
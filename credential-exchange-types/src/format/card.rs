@@ -0,0 +1,187 @@
+//! # Card Display
+//!
+//! Formats the members of a [CreditCardCredential] for display, so that user interfaces never
+//! need to handle the full card number themselves.
+
+use super::CreditCardCredential;
+
+/// The character replacing the hidden digits of a masked card number.
+const MASK: char = '•';
+
+/// The number of trailing digits of a card number which are shown, as is customary.
+const SHOWN_DIGITS: usize = 4;
+
+/// The shortest card number whose last digits are shown, shorter values may not be card numbers
+/// and would be revealed almost entirely.
+const MIN_DIGITS: usize = 12;
+
+/// The number of trailing digits shown out of the `len` digits of a card number: the last four
+/// of numbers long enough to be card numbers, and none otherwise.
+pub(super) fn shown_digits(len: usize) -> usize {
+    if len >= MIN_DIGITS {
+        SHOWN_DIGITS
+    } else {
+        0
+    }
+}
+
+/// The payment network of a card, identified from the first digits of its number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CardNetwork {
+    AmericanExpress,
+    DinersClub,
+    Discover,
+    Jcb,
+    Mastercard,
+    UnionPay,
+    Visa,
+}
+
+impl CardNetwork {
+    /// The name of the network as printed on cards.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CardNetwork::AmericanExpress => "American Express",
+            CardNetwork::DinersClub => "Diners Club",
+            CardNetwork::Discover => "Discover",
+            CardNetwork::Jcb => "JCB",
+            CardNetwork::Mastercard => "Mastercard",
+            CardNetwork::UnionPay => "UnionPay",
+            CardNetwork::Visa => "Visa",
+        }
+    }
+
+    /// Identify the network from the digits of a card number.
+    fn identify(digits: &[u8]) -> Option<Self> {
+        let prefix = |len: usize| {
+            digits.get(..len).map(|prefix| {
+                prefix
+                    .iter()
+                    .fold(0u32, |value, digit| value * 10 + *digit as u32)
+            })
+        };
+        Some(match (prefix(1)?, prefix(2)?, prefix(3), prefix(4)) {
+            (4, ..) => CardNetwork::Visa,
+            (_, 34 | 37, ..) => CardNetwork::AmericanExpress,
+            (_, 36 | 38 | 39, ..) | (_, _, Some(300..=305), _) => CardNetwork::DinersClub,
+            (_, 51..=55, ..) | (.., Some(2221..=2720)) => CardNetwork::Mastercard,
+            (_, 65, ..) | (_, _, Some(644..=649), _) | (.., Some(6011)) => CardNetwork::Discover,
+            (.., Some(3528..=3589)) => CardNetwork::Jcb,
+            (_, 62, ..) => CardNetwork::UnionPay,
+            _ => return None,
+        })
+    }
+
+    /// The sizes of the groups in which the digits of a number of `len` digits are printed.
+    fn groups(network: Option<Self>, len: usize) -> Vec<usize> {
+        match (network, len) {
+            (Some(CardNetwork::AmericanExpress), 15) => vec![4, 6, 5],
+            (Some(CardNetwork::DinersClub), 14) => vec![4, 6, 4],
+            _ => (0..len)
+                .step_by(4)
+                .map(|start| (len - start).min(4))
+                .collect(),
+        }
+    }
+}
+
+impl CreditCardCredential {
+    /// The payment network of the card, if its number is recognized.
+    pub fn network(&self) -> Option<CardNetwork> {
+        CardNetwork::identify(&self.digits())
+    }
+
+    /// The card number grouped as printed on the card by its network, e.g.
+    /// `4111 1111 1111 1111` or `3782 822463 10005`. Characters other than digits are dropped.
+    pub fn grouped_number(&self) -> String {
+        let digits: String = self
+            .digits()
+            .iter()
+            .map(|digit| (b'0' + digit) as char)
+            .collect();
+        self.group(&digits)
+    }
+
+    /// The card number grouped like [CreditCardCredential::grouped_number], with every digit
+    /// but the last four replaced by `•`, e.g. `•••• •••• •••• 1234`. Numbers which are too short
+    /// to be card numbers are masked entirely.
+    pub fn masked_number(&self) -> String {
+        let digits = self.digits();
+        let shown = shown_digits(digits.len());
+        let masked: String = digits
+            .iter()
+            .enumerate()
+            .map(|(index, digit)| {
+                if index + shown >= digits.len() {
+                    (b'0' + digit) as char
+                } else {
+                    MASK
+                }
+            })
+            .collect();
+        self.group(&masked)
+    }
+
+    /// The [expiry date][CreditCardCredential::expiry_date] in the `MM/YY` format printed on
+    /// cards, if it is a valid `YYYY-MM` value.
+    pub fn formatted_expiry_date(&self) -> Option<String> {
+        format_month(self.expiry_date.as_deref()?)
+    }
+
+    /// The [valid from][CreditCardCredential::valid_from] date in the `MM/YY` format printed on
+    /// cards, if it is a valid `YYYY-MM` value.
+    pub fn formatted_valid_from(&self) -> Option<String> {
+        format_month(self.valid_from.as_deref()?)
+    }
+
+    /// The [shown][shown_digits] last digits of the card number, or [None] if it is too short for
+    /// any digit to be shown.
+    pub(super) fn last_digits(&self) -> Option<String> {
+        let digits = self.digits();
+        let shown = shown_digits(digits.len());
+        (shown > 0).then(|| {
+            digits[digits.len() - shown..]
+                .iter()
+                .map(|digit| (b'0' + digit) as char)
+                .collect()
+        })
+    }
+
+    /// The digits of the card number.
+    fn digits(&self) -> Vec<u8> {
+        self.number
+            .bytes()
+            .filter(u8::is_ascii_digit)
+            .map(|digit| digit - b'0')
+            .collect()
+    }
+
+    /// Split `value`, which has one character per digit of the number, into the groups of the
+    /// network of the card.
+    fn group(&self, value: &str) -> String {
+        let chars: Vec<char> = value.chars().collect();
+        let mut start = 0;
+        CardNetwork::groups(self.network(), chars.len())
+            .into_iter()
+            .map(|len| {
+                let group: String = chars[start..start + len].iter().collect();
+                start += len;
+                group
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Format a `YYYY-MM` value as `MM/YY`.
+fn format_month(value: &str) -> Option<String> {
+    let (year, month) = value.trim().split_once('-')?;
+    let valid = year.len() == 4
+        && month.len() == 2
+        && year
+            .bytes()
+            .chain(month.bytes())
+            .all(|byte| byte.is_ascii_digit())
+        && (1..=12).contains(&month.parse::<u32>().ok()?);
+    valid.then(|| format!("{month}/{}", &year[2..]))
+}
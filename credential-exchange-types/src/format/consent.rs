@@ -0,0 +1,107 @@
+//! # Consent Summaries
+//!
+//! Describes what an [ExportRequest] would take out of the exporter's vault, so that the exporter
+//! can render the confirmation dialog which the user must accept before any data leaves it.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::{CredentialType, Extension, Header};
+use crate::protocol::{ExportRequest, KnownExtension};
+
+/// The content of an export, as returned by [Header::consent_summary].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsentSummary {
+    /// The [importer][ExportRequest::importer] requesting the export.
+    pub importer: String,
+    /// The credential types requested by the importer, or [None] if it requested all of them.
+    pub requested_types: Option<Vec<CredentialType>>,
+    /// The number of [Accounts][super::Account] included.
+    pub accounts: usize,
+    /// The number of [Items][super::Item] included, i.e. those holding at least one credential of
+    /// a requested type, or all of them if every type is requested.
+    pub items: usize,
+    /// The number of included credentials of each type.
+    pub credential_types: HashMap<CredentialType, usize>,
+    /// The number of credentials left out because their type wasn't requested.
+    pub excluded_credentials: usize,
+    /// The number of included extensions of the header, accounts, collections and items, by name.
+    /// [External][Extension::External] extensions and unknown extensions without a name are
+    /// counted under an empty name.
+    pub extensions: BTreeMap<String, usize>,
+    /// The names of the included extensions which the importer didn't list in its
+    /// [known extensions][ExportRequest::known_extensions].
+    pub unknown_extensions: BTreeSet<String>,
+}
+
+impl ConsentSummary {
+    /// The total number of included credentials.
+    pub fn credentials(&self) -> usize {
+        self.credential_types.values().sum()
+    }
+
+    fn extensions<E>(&mut self, extensions: &Option<Vec<Extension<E>>>) {
+        for extension in extensions.iter().flatten() {
+            let name = extension.name().unwrap_or_default();
+            *self.extensions.entry(name.to_owned()).or_default() += 1;
+        }
+    }
+}
+
+impl<E> Header<E> {
+    /// Summarize what exporting this vault in response to `request` would include.
+    pub fn consent_summary(&self, request: &ExportRequest) -> ConsentSummary {
+        let requested_types: Option<Vec<CredentialType>> = request
+            .credential_types
+            .as_ref()
+            .map(|types| types.iter().cloned().map(Into::into).collect());
+        let requested =
+            |ty: &CredentialType| !matches!(&requested_types, Some(types) if !types.contains(ty));
+
+        let mut summary = ConsentSummary {
+            importer: request.importer.clone(),
+            accounts: self.accounts.len(),
+            ..ConsentSummary::default()
+        };
+        summary.extensions(&self.extensions);
+        for account in &self.accounts {
+            summary.extensions(&account.extensions);
+            for (_, collection) in account.iter_all_collections() {
+                summary.extensions(&collection.extensions);
+            }
+            for item in &account.items {
+                let mut included = requested_types.is_none();
+                for credential in &item.credentials {
+                    let ty = credential.credential_type();
+                    if requested(&ty) {
+                        included = true;
+                        *summary.credential_types.entry(ty).or_default() += 1;
+                    } else {
+                        summary.excluded_credentials += 1;
+                    }
+                }
+                if included {
+                    summary.items += 1;
+                    summary.extensions(&item.extensions);
+                }
+            }
+        }
+
+        let known: Vec<&str> = request
+            .known_extensions
+            .iter()
+            .flatten()
+            .map(|extension| match extension {
+                KnownExtension::Shared => "shared",
+                KnownExtension::Unknown(name) => name.as_str(),
+            })
+            .collect();
+        summary.unknown_extensions = summary
+            .extensions
+            .keys()
+            .filter(|name| !known.contains(&name.as_str()))
+            .cloned()
+            .collect();
+        summary.requested_types = requested_types;
+        summary
+    }
+}
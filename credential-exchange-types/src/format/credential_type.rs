@@ -0,0 +1,127 @@
+//! # Credential Types
+//!
+//! Identifies the type of a [Credential] without its content, e.g. to filter the credentials of
+//! an export or to count them.
+
+use serde::{Deserialize, Serialize};
+
+use super::Credential;
+#[cfg(feature = "protocol")]
+use crate::protocol;
+
+/// The type of a [Credential], as written in its `type` member.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum CredentialType {
+    BasicAuth,
+    Passkey,
+    CreditCard,
+    Note,
+    Totp,
+    DriversLicense,
+    Address,
+    ItemReference,
+    File,
+    SshKey,
+    IdentityDocument,
+    Passport,
+    PersonName,
+    #[cfg(feature = "non-standard-credentials")]
+    BirthCertificate,
+    #[cfg(feature = "non-standard-credentials")]
+    InsurancePolicy,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl Credential {
+    /// The [CredentialType] of this credential.
+    pub fn credential_type(&self) -> CredentialType {
+        match self {
+            Credential::BasicAuth(_) => CredentialType::BasicAuth,
+            Credential::Passkey(_) => CredentialType::Passkey,
+            Credential::CreditCard(_) => CredentialType::CreditCard,
+            Credential::Note(_) => CredentialType::Note,
+            Credential::Totp(_) => CredentialType::Totp,
+            Credential::DriversLicense(_) => CredentialType::DriversLicense,
+            Credential::Address(_) => CredentialType::Address,
+            Credential::ItemReference(_) => CredentialType::ItemReference,
+            Credential::File(_) => CredentialType::File,
+            Credential::SshKey(_) => CredentialType::SshKey,
+            Credential::IdentityDocument(_) => CredentialType::IdentityDocument,
+            Credential::Passport(_) => CredentialType::Passport,
+            Credential::PersonName(_) => CredentialType::PersonName,
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::BirthCertificate(_) => CredentialType::BirthCertificate,
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(_) => CredentialType::InsurancePolicy,
+            Credential::Unknown { ty, .. } => CredentialType::Unknown(ty.clone()),
+        }
+    }
+}
+
+/// The credential types which the format does not model yet, such as `api-key`, are kept as
+/// [CredentialType::Unknown] and mapped to and from their dedicated protocol variant.
+#[cfg(feature = "protocol")]
+impl From<CredentialType> for protocol::CredentialType {
+    fn from(src: CredentialType) -> Self {
+        match src {
+            CredentialType::BasicAuth => Self::BasicAuth,
+            CredentialType::Passkey => Self::Passkey,
+            CredentialType::CreditCard => Self::CreditCard,
+            CredentialType::Note => Self::Note,
+            CredentialType::Totp => Self::Totp,
+            CredentialType::DriversLicense => Self::DriversLicense,
+            CredentialType::Address => Self::Address,
+            CredentialType::ItemReference => Self::ItemReference,
+            CredentialType::File => Self::File,
+            CredentialType::SshKey => Self::SshKey,
+            CredentialType::IdentityDocument => Self::IdentityDocument,
+            CredentialType::Passport => Self::Passport,
+            CredentialType::PersonName => Self::PersonName,
+            #[cfg(feature = "non-standard-credentials")]
+            CredentialType::BirthCertificate => Self::BirthCertificate,
+            #[cfg(feature = "non-standard-credentials")]
+            CredentialType::InsurancePolicy => Self::InsurancePolicy,
+            CredentialType::Unknown(ty) => match ty.as_str() {
+                "api-key" => Self::ApiKey,
+                "wifi" => Self::Wifi,
+                "generated-password" => Self::GeneratedPassword,
+                "custom-fields" => Self::CustomFields,
+                _ => Self::Unknown(ty),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "protocol")]
+impl From<protocol::CredentialType> for CredentialType {
+    fn from(src: protocol::CredentialType) -> Self {
+        match src {
+            protocol::CredentialType::BasicAuth => Self::BasicAuth,
+            protocol::CredentialType::Passkey => Self::Passkey,
+            protocol::CredentialType::Totp => Self::Totp,
+            protocol::CredentialType::Note => Self::Note,
+            protocol::CredentialType::File => Self::File,
+            protocol::CredentialType::Address => Self::Address,
+            protocol::CredentialType::CreditCard => Self::CreditCard,
+            protocol::CredentialType::DriversLicense => Self::DriversLicense,
+            protocol::CredentialType::ItemReference => Self::ItemReference,
+            protocol::CredentialType::IdentityDocument => Self::IdentityDocument,
+            protocol::CredentialType::Passport => Self::Passport,
+            protocol::CredentialType::PersonName => Self::PersonName,
+            #[cfg(feature = "non-standard-credentials")]
+            protocol::CredentialType::BirthCertificate => Self::BirthCertificate,
+            #[cfg(feature = "non-standard-credentials")]
+            protocol::CredentialType::InsurancePolicy => Self::InsurancePolicy,
+            protocol::CredentialType::SshKey => Self::SshKey,
+            protocol::CredentialType::ApiKey => Self::Unknown("api-key".to_owned()),
+            protocol::CredentialType::Wifi => Self::Unknown("wifi".to_owned()),
+            protocol::CredentialType::GeneratedPassword => {
+                Self::Unknown("generated-password".to_owned())
+            }
+            protocol::CredentialType::CustomFields => Self::Unknown("custom-fields".to_owned()),
+            protocol::CredentialType::Unknown(ty) => Self::Unknown(ty),
+        }
+    }
+}
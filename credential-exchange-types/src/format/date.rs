@@ -0,0 +1,85 @@
+//! # Dates
+//!
+//! Parsing of the values of [date][super::FieldType::Date] fields.
+
+use chrono::{DateTime, NaiveDate};
+
+use super::EditableField;
+
+/// The date format mandated by the specification, an
+/// [RFC3339](https://www.rfc-editor.org/rfc/rfc3339#section-5.6) `full-date`.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Defines how strictly the value of a [date][super::FieldType::Date] field is parsed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateParsing {
+    /// Only accept the `YYYY-MM-DD` format mandated by the specification.
+    #[default]
+    Strict,
+    /// Additionally accept the variants encountered in real exports: full RFC3339 date-times, of
+    /// which only the date is kept, `YYYY/MM/DD` and `MM/DD/YYYY`. Surrounding whitespace is
+    /// ignored.
+    Lenient,
+}
+
+/// An error returned when a value is not a valid date.
+#[derive(Debug)]
+pub struct InvalidDate;
+
+impl std::fmt::Display for InvalidDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Value isn't a valid date")
+    }
+}
+
+/// Parse the value of a [date][super::FieldType::Date] field.
+pub fn parse_date(value: &str, parsing: DateParsing) -> Result<NaiveDate, InvalidDate> {
+    if let Some(date) = parse_canonical(value) {
+        return Ok(date);
+    }
+    if parsing == DateParsing::Strict {
+        return Err(InvalidDate);
+    }
+
+    let value = value.trim();
+    parse_canonical(value)
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|datetime| datetime.date_naive())
+        })
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y/%m/%d").ok())
+        .or_else(|| NaiveDate::parse_from_str(value, "%m/%d/%Y").ok())
+        .ok_or(InvalidDate)
+}
+
+/// Parse a date in the exact `YYYY-MM-DD` format, rejecting the unpadded or signed variants
+/// otherwise accepted by [NaiveDate::parse_from_str].
+fn parse_canonical(value: &str) -> Option<NaiveDate> {
+    let bytes = value.as_bytes();
+    let well_formed = bytes.len() == 10
+        && bytes.iter().enumerate().all(|(index, byte)| match index {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        });
+    well_formed
+        .then(|| NaiveDate::parse_from_str(value, DATE_FORMAT).ok())
+        .flatten()
+}
+
+impl EditableField {
+    /// Parse the [value][EditableField::value] of this field as a date. This is meant for fields
+    /// of the [date][super::FieldType::Date] type.
+    pub fn date(&self, parsing: DateParsing) -> Result<NaiveDate, InvalidDate> {
+        parse_date(&self.value, parsing)
+    }
+
+    /// Parse the [value][EditableField::value] of this field using [DateParsing::Lenient] and
+    /// rewrite it in the canonical `YYYY-MM-DD` format. The value is left untouched if it is not a
+    /// valid date.
+    pub fn normalize_date(&mut self) -> Result<(), InvalidDate> {
+        let date = self.date(DateParsing::Lenient)?;
+        self.value = date.format(DATE_FORMAT).to_string();
+        Ok(())
+    }
+}
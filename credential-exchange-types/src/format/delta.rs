@@ -0,0 +1,228 @@
+//! # Incremental Exchanges
+//!
+//! Providers which exchange the same vault repeatedly, e.g. to keep two providers in sync, can
+//! transmit only the [Items][super::Item] which changed since the previous exchange. The exporter
+//! keeps the [ItemHashes] of the previous exchange, computes a [Delta] with [Header::delta_since],
+//! and the importer applies it to its copy of the previous exchange with [Header::apply_delta].
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{hash::ContentHash, Account, Header};
+use crate::B64Url;
+
+/// The [content hashes][super::Item::content_hash] of the items of an exchange, by [Account] id
+/// and then by item id, as returned by [Header::item_hashes].
+pub type ItemHashes = HashMap<B64Url, HashMap<B64Url, ContentHash>>;
+
+/// The changes of an exchange since a previous one, as returned by [Header::delta_since].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", bound(deserialize = "E: Deserialize<'de>"))]
+pub struct Delta<E = ()> {
+    /// The [timestamp][Header::timestamp] of the previous exchange.
+    #[serde(with = "crate::timestamp")]
+    pub since: DateTime<Utc>,
+    /// The [timestamp][Header::timestamp] of the exchange after the delta is applied.
+    #[serde(with = "crate::timestamp")]
+    pub timestamp: DateTime<Utc>,
+    /// The changes of every [Account] of the exchange.
+    pub accounts: Vec<AccountDelta<E>>,
+    /// The ids of the [Accounts][Account] which were removed.
+    pub removed_accounts: Vec<B64Url>,
+}
+
+/// The changes of a single [Account], see [Delta].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", bound(deserialize = "E: Deserialize<'de>"))]
+pub struct AccountDelta<E = ()> {
+    /// The account with all its metadata and [Collections][super::Collection], but only the items
+    /// which were added or changed.
+    pub account: Account<E>,
+    /// The ids of the [Items][super::Item] which were removed.
+    pub removed_items: Vec<B64Url>,
+}
+
+impl<E: Serialize> Header<E> {
+    /// Compute the [ItemHashes] of this exchange, to be kept by the exporter until the next
+    /// [delta][Header::delta_since]. Items whose extensions cannot be serialized are left out, and
+    /// are therefore always part of the next delta.
+    pub fn item_hashes(&self) -> ItemHashes {
+        self.accounts
+            .iter()
+            .map(|account| {
+                let hashes = account
+                    .items
+                    .iter()
+                    .filter_map(|item| Some((item.id.clone(), item.content_hash().ok()?)))
+                    .collect();
+                (account.id.clone(), hashes)
+            })
+            .collect()
+    }
+}
+
+impl<E: Clone + Serialize> Header<E> {
+    /// Compute the changes of this exchange since the previous exchange made at `since`, whose
+    /// [item hashes][Header::item_hashes] are `previous`.
+    ///
+    /// Items present in `previous` which were not [modified][super::Item::modified_at] after
+    /// `since` are assumed to be unchanged without being hashed. Other items are part of the
+    /// delta if they are not in `previous` or if their content hash changed.
+    pub fn delta_since(&self, since: DateTime<Utc>, previous: &ItemHashes) -> Delta<E> {
+        let empty = HashMap::new();
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|account| {
+                let previous = previous.get(&account.id).unwrap_or(&empty);
+                let items = account
+                    .items
+                    .iter()
+                    .filter(|item| {
+                        let Some(hash) = previous.get(&item.id) else {
+                            return true;
+                        };
+                        let modified =
+                            !matches!(item.modified_at, Some(modified) if modified <= since);
+                        modified && item.content_hash().ok().as_ref() != Some(hash)
+                    })
+                    .cloned()
+                    .collect();
+                let current: HashSet<&B64Url> = account.items.iter().map(|item| &item.id).collect();
+                let removed_items = previous
+                    .keys()
+                    .filter(|id| !current.contains(id))
+                    .cloned()
+                    .collect();
+                AccountDelta {
+                    account: Account {
+                        collections: account.collections.clone(),
+                        items,
+                        ..account.clone_metadata()
+                    },
+                    removed_items,
+                }
+            })
+            .collect();
+
+        let current: HashSet<&B64Url> = self.accounts.iter().map(|account| &account.id).collect();
+        let removed_accounts = previous
+            .keys()
+            .filter(|id| !current.contains(id))
+            .cloned()
+            .collect();
+
+        Delta {
+            since,
+            timestamp: self.timestamp,
+            accounts,
+            removed_accounts,
+        }
+    }
+}
+
+impl<E> Header<E> {
+    /// Apply a [Delta] computed by the exporter to this exchange, which must be the previous
+    /// exchange the delta was computed from.
+    ///
+    /// The metadata and collections of the accounts are replaced, removed items and accounts are
+    /// dropped, and added or changed items replace the items with the same id, or are appended.
+    pub fn apply_delta(&mut self, delta: Delta<E>) {
+        let removed_accounts: HashSet<&B64Url> = delta.removed_accounts.iter().collect();
+        self.accounts
+            .retain(|account| !removed_accounts.contains(&account.id));
+        let mut accounts = positions(self.accounts.iter().map(|account| &account.id));
+
+        for AccountDelta {
+            mut account,
+            removed_items,
+        } in delta.accounts
+        {
+            let Some(&index) = accounts.get(&account.id) else {
+                accounts.insert(account.id.clone(), self.accounts.len());
+                self.accounts.push(account);
+                continue;
+            };
+            let existing = &mut self.accounts[index];
+
+            let removed_items: HashSet<&B64Url> = removed_items.iter().collect();
+            let mut items = std::mem::take(&mut existing.items);
+            items.retain(|item| !removed_items.contains(&item.id));
+            let mut positions = positions(items.iter().map(|item| &item.id));
+            for item in account.items.drain(..) {
+                match positions.get(&item.id) {
+                    Some(&index) => items[index] = item,
+                    None => {
+                        positions.insert(item.id.clone(), items.len());
+                        items.push(item);
+                    }
+                }
+            }
+            account.items = items;
+            *existing = account;
+        }
+        self.timestamp = delta.timestamp;
+    }
+}
+
+/// Index the given ids by their position.
+fn positions<'a>(ids: impl Iterator<Item = &'a B64Url>) -> HashMap<B64Url, usize> {
+    ids.enumerate()
+        .map(|(index, id)| (id.clone(), index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::format::fixtures::{self, header, id, note};
+
+    fn item(item: u8, title: &str) -> Value {
+        fixtures::item(item, title, json!([note(title)]))
+    }
+
+    fn account(account: u8, items: Value) -> Account {
+        fixtures::account(account, items, json!([]))
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        let mut previous = header(
+            1,
+            vec![
+                account(
+                    1,
+                    json!([item(1, "kept"), item(2, "old"), item(3, "removed")]),
+                ),
+                account(2, json!([item(4, "removed")])),
+            ],
+        );
+        let current = header(
+            2,
+            vec![
+                account(
+                    1,
+                    json!([item(1, "kept"), item(2, "new"), item(5, "added")]),
+                ),
+                account(3, json!([item(6, "added")])),
+            ],
+        );
+
+        let delta = current.delta_since(previous.timestamp, &previous.item_hashes());
+        let items: Vec<Vec<&B64Url>> = delta
+            .accounts
+            .iter()
+            .map(|delta| delta.account.items.iter().map(|item| &item.id).collect())
+            .collect();
+        assert_eq!(items, [vec![&id(2), &id(5)], vec![&id(6)]]);
+        assert_eq!(delta.accounts[0].removed_items, [id(3)]);
+        assert_eq!(delta.removed_accounts, [id(2)]);
+
+        previous.apply_delta(delta);
+        assert_eq!(previous, current);
+    }
+}
@@ -0,0 +1,167 @@
+//! # Account Diffing
+//!
+//! Compares two versions of the same [Account], e.g. when a user re-imports an updated export
+//! from the same source.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{hash::ContentHash, Account};
+use crate::{trace, B64Url};
+
+/// The differences between two versions of an [Account], as returned by [Account::diff].
+///
+/// Items and collections are matched by [id][super::Item::id]. Entities which only exist on one
+/// side are then matched by content, so that an entity whose identifier was regenerated by the
+/// exporter but is otherwise unchanged is not reported. Items are compared by
+/// [content hash][super::Item::content_hash], so changes of their timestamps alone are not
+/// reported either.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// The ids of the [Items][super::Item] only present in the new version.
+    pub added_items: Vec<B64Url>,
+    /// The ids of the [Items][super::Item] only present in the old version.
+    pub removed_items: Vec<B64Url>,
+    /// The ids of the [Items][super::Item] present in both versions with a different content.
+    pub modified_items: Vec<B64Url>,
+    /// The ids of the [Collections][super::Collection] only present in the new version, at any
+    /// depth.
+    pub added_collections: Vec<B64Url>,
+    /// The ids of the [Collections][super::Collection] only present in the old version, at any
+    /// depth.
+    pub removed_collections: Vec<B64Url>,
+    /// The ids of the [Collections][super::Collection] present in both versions with a different
+    /// content or a different parent.
+    pub modified_collections: Vec<B64Url>,
+}
+
+impl AccountDiff {
+    /// Whether both versions of the account hold the same items and collections.
+    pub fn is_empty(&self) -> bool {
+        self.added_items.is_empty()
+            && self.removed_items.is_empty()
+            && self.modified_items.is_empty()
+            && self.added_collections.is_empty()
+            && self.removed_collections.is_empty()
+            && self.modified_collections.is_empty()
+    }
+}
+
+impl<E: Serialize> Account<E> {
+    /// Compute the items and collections which were added, removed or modified in `other`,
+    /// considering this [Account] as the previous version.
+    pub fn diff(&self, other: &Account<E>) -> AccountDiff {
+        trace::debug_span!(
+            "diff",
+            items = self.items.len(),
+            other_items = other.items.len()
+        );
+        let items = compare(
+            self.items
+                .iter()
+                .map(|item| (&item.id, item.content_hash().ok())),
+            other
+                .items
+                .iter()
+                .map(|item| (&item.id, item.content_hash().ok())),
+        );
+        let collections = compare(collections(self), collections(other));
+
+        AccountDiff {
+            added_items: items.added,
+            removed_items: items.removed,
+            modified_items: items.modified,
+            added_collections: collections.added,
+            removed_collections: collections.removed,
+            modified_collections: collections.modified,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Changes {
+    added: Vec<B64Url>,
+    removed: Vec<B64Url>,
+    modified: Vec<B64Url>,
+}
+
+/// Compare two sets of entities given as `(id, content)` pairs.
+fn compare<'a>(
+    old: impl Iterator<Item = (&'a B64Url, Option<ContentHash>)>,
+    new: impl Iterator<Item = (&'a B64Url, Option<ContentHash>)>,
+) -> Changes {
+    let old: Vec<_> = old.collect();
+    let new: Vec<_> = new.collect();
+    let old_by_id: HashMap<&B64Url, &Option<ContentHash>> =
+        old.iter().map(|(id, content)| (*id, content)).collect();
+    let new_by_id: HashMap<&B64Url, &Option<ContentHash>> =
+        new.iter().map(|(id, content)| (*id, content)).collect();
+
+    // The contents of the entities only present on one side, used to match the entities whose
+    // identifier was regenerated.
+    let mut removed_contents = count_contents(&old, &new_by_id);
+    let mut added_contents = count_contents(&new, &old_by_id);
+
+    let mut changes = Changes::default();
+    for (id, content) in &new {
+        match old_by_id.get(id) {
+            Some(previous) if *previous != content => changes.modified.push((*id).clone()),
+            Some(_) => {}
+            None if take(&mut removed_contents, content) => {}
+            None => changes.added.push((*id).clone()),
+        }
+    }
+    for (id, content) in &old {
+        if !new_by_id.contains_key(id) && !take(&mut added_contents, content) {
+            changes.removed.push((*id).clone());
+        }
+    }
+    changes
+}
+
+/// Count the contents of the `entities` which are not part of `others`.
+fn count_contents(
+    entities: &[(&B64Url, Option<ContentHash>)],
+    others: &HashMap<&B64Url, &Option<ContentHash>>,
+) -> HashMap<ContentHash, usize> {
+    let mut counts = HashMap::new();
+    for (_, content) in entities.iter().filter(|(id, _)| !others.contains_key(id)) {
+        if let Some(content) = content {
+            *counts.entry(*content).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Consume one occurrence of `content` from `counts`, returning whether there was one.
+fn take(counts: &mut HashMap<ContentHash, usize>, content: &Option<ContentHash>) -> bool {
+    match content.as_ref().and_then(|content| counts.get_mut(content)) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Every collection of the account, at any depth, with its content. The content excludes the
+/// sub-collections, which are compared on their own, but includes the id of the parent so that
+/// moving a collection is reported as a modification.
+fn collections<E: Serialize>(
+    account: &Account<E>,
+) -> impl Iterator<Item = (&B64Url, Option<ContentHash>)> {
+    account.iter_all_collections().map(|(path, collection)| {
+        let parent = path.last().map(|parent| &parent.id);
+        let content = ContentHash::of(&(
+            &collection.title,
+            &collection.subtitle,
+            &collection.icon,
+            &collection.items,
+            &collection.extensions,
+            parent,
+        ))
+        .ok();
+        (&collection.id, content)
+    })
+}
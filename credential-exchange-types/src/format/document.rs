@@ -4,20 +4,29 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::B64Url;
+use crate::{format::redact::debug_masked, B64Url};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct NoteCredential {
     /// This member is a user-defined value encoded as a UTF-8 string.
     pub content: String,
+    /// The unknown members of the [NoteCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+debug_masked!(NoteCredential {} masked { content });
+
 /// A [FileCredential] acts as a placeholder to an arbitrary binary file holding its associated
 /// metadata. When an importing provider encounters a file credential, they MAY request the file
 /// afterwards if they have a direct exchange. If the exchange will produce an export response file,
 /// then the associated encrypted file MUST be stored in the documents folder of the zip archive.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct FileCredential {
     /// The file’s identifier, used as the file name in the zip archive.
@@ -29,4 +38,16 @@ pub struct FileCredential {
     /// The SHA256 hash of the decrypted file. This hash MUST be used by the importing provider
     /// when the file is decrypted to ensure that it has not been corrupted.
     pub integration_hash: B64Url,
+    /// The unknown members of the [FileCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
+
+debug_masked!(FileCredential {
+    id,
+    name,
+    decrypted_size,
+    integration_hash
+});
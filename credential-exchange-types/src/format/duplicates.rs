@@ -0,0 +1,133 @@
+//! # Duplicate Detection
+//!
+//! Finds [Items][Item] of an [Account] which hold the same credential, so that import flows can
+//! warn the user before committing them.
+
+use std::collections::HashMap;
+
+use super::{Account, BasicAuthCredential, CredentialKind, Item, PasskeyCredential};
+use crate::{trace, uri::Origin, B64Url, RpId, Uri};
+
+/// A set of [Items][Item] which hold an identical credential.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Why the items are considered duplicates of each other.
+    pub reason: DuplicateReason,
+    /// The [ids][Item::id] of the duplicated items, in the order they appear in the [Account]. A
+    /// group always contains at least two items.
+    pub items: Vec<B64Url>,
+}
+
+/// The credential shared by the items of a [DuplicateGroup]. Secrets are never part of the
+/// reason.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DuplicateReason {
+    /// The items contain a [BasicAuthCredential] with the same username and password for the
+    /// same website.
+    BasicAuth {
+        /// The username shared by the credentials, if any.
+        username: Option<String>,
+        /// The URL, as written in the first credential of the group.
+        url: Uri,
+    },
+    /// The items contain a [PasskeyCredential] with the same
+    /// [credential id][PasskeyCredential::credential_id].
+    Passkey {
+        /// The shared credential id.
+        credential_id: B64Url,
+        /// The relying party of the first passkey of the group.
+        rp_id: RpId,
+    },
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum Key<'a> {
+    BasicAuth {
+        username: Option<&'a str>,
+        password: Option<&'a str>,
+        origin: Origin,
+    },
+    Passkey(&'a B64Url),
+}
+
+/// Group the [Items][Item] of `account` which contain the same login, i.e. a
+/// [BasicAuthCredential] with identical username and password for the same website, or the same
+/// [PasskeyCredential].
+///
+/// URLs are compared by scheme, host and port in the same way as [Account::items_for_url]. An item
+/// can be part of several groups if it holds multiple credentials.
+pub fn find_duplicates<E>(account: &Account<E>) -> Vec<DuplicateGroup> {
+    trace::debug_span!("find_duplicates", items = account.items.len());
+    let mut groups: Vec<(DuplicateReason, Vec<&B64Url>)> = Vec::new();
+    let mut index: HashMap<Key, usize> = HashMap::new();
+
+    for item in &account.items {
+        for (key, reason) in keys(item) {
+            let group = *index.entry(key).or_insert_with(|| {
+                groups.push((reason, Vec::new()));
+                groups.len() - 1
+            });
+            let items = &mut groups[group].1;
+            if !items.contains(&&item.id) {
+                items.push(&item.id);
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, items)| items.len() > 1)
+        .map(|(reason, items)| DuplicateGroup {
+            reason,
+            items: items.into_iter().cloned().collect(),
+        })
+        .collect()
+}
+
+/// The deduplication keys of every credential of an item, along with the reason that would be
+/// reported for them.
+fn keys<E>(item: &Item<E>) -> Vec<(Key<'_>, DuplicateReason)> {
+    let mut keys = Vec::new();
+
+    for credential in &item.credentials {
+        if let Some(basic_auth) = BasicAuthCredential::from_credential(credential) {
+            let username = basic_auth
+                .username
+                .as_ref()
+                .map(|field| field.value.as_str());
+            let password = basic_auth
+                .password
+                .as_ref()
+                .map(|field| field.value.as_str());
+            if username.is_none() && password.is_none() {
+                continue;
+            }
+            for url in &basic_auth.urls {
+                let Some(origin) = Origin::parse(url.as_str()) else {
+                    continue;
+                };
+                keys.push((
+                    Key::BasicAuth {
+                        username,
+                        password,
+                        origin,
+                    },
+                    DuplicateReason::BasicAuth {
+                        username: username.map(str::to_owned),
+                        url: url.clone(),
+                    },
+                ));
+            }
+        } else if let Some(passkey) = PasskeyCredential::from_credential(credential) {
+            keys.push((
+                Key::Passkey(&passkey.credential_id),
+                DuplicateReason::Passkey {
+                    credential_id: passkey.credential_id.clone(),
+                    rp_id: passkey.rp_id.clone(),
+                },
+            ));
+        }
+    }
+
+    keys
+}
@@ -0,0 +1,127 @@
+//! # Expiring Credentials
+//!
+//! Finds the credentials which expire soon or already expired, so that importers can surface them
+//! right after an exchange.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde_json::Value;
+
+use super::{parse_date, Credential, CredentialType, DateParsing, EditableField, Header};
+use crate::B64Url;
+
+/// A credential with an expiry date, see [Header::expiring_within].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpiringCredential {
+    /// The [id][super::Account::id] of the account holding the credential.
+    pub account: B64Url,
+    /// The [id][super::Item::id] of the item holding the credential.
+    pub item: B64Url,
+    /// The type of the credential.
+    pub credential_type: CredentialType,
+    /// The last day on which the credential is valid.
+    pub expiry_date: NaiveDate,
+}
+
+/// The credentials of an exchange which expire within a window, as returned by
+/// [Header::expiring_within]. Both lists are sorted by expiry date.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpiryReport {
+    /// The credentials whose expiry date is before the date of the exchange.
+    pub expired: Vec<ExpiringCredential>,
+    /// The credentials which expire on the date of the exchange or within the window.
+    pub expiring: Vec<ExpiringCredential>,
+}
+
+impl ExpiryReport {
+    /// Whether no credential expired or expires within the window.
+    pub fn is_empty(&self) -> bool {
+        self.expired.is_empty() && self.expiring.is_empty()
+    }
+}
+
+impl<E> Header<E> {
+    /// Find the credit cards, passports, driver's licenses, identity documents, API keys, SSH keys
+    /// and, with the `non-standard-credentials` feature, insurance policies of this exchange which
+    /// expire within `window` of its [timestamp][Header::timestamp], or already expired.
+    ///
+    /// Expiry dates are parsed with [DateParsing::Lenient], and a card valid through a month
+    /// expires on its last day. Credentials without a valid expiry date are ignored.
+    pub fn expiring_within(&self, window: Duration) -> ExpiryReport {
+        let today = self.timestamp.date_naive();
+        let limit = self
+            .timestamp
+            .checked_add_signed(window)
+            .map_or(NaiveDate::MAX, |limit| limit.date_naive());
+
+        let mut report = ExpiryReport::default();
+        for account in &self.accounts {
+            for item in &account.items {
+                for credential in &item.credentials {
+                    let Some(expiry_date) = credential.expiry_date() else {
+                        continue;
+                    };
+                    if expiry_date > limit {
+                        continue;
+                    }
+                    let expiring = ExpiringCredential {
+                        account: account.id.clone(),
+                        item: item.id.clone(),
+                        credential_type: credential.credential_type(),
+                        expiry_date,
+                    };
+                    if expiry_date < today {
+                        report.expired.push(expiring);
+                    } else {
+                        report.expiring.push(expiring);
+                    }
+                }
+            }
+        }
+        report.expired.sort_by_key(|expiring| expiring.expiry_date);
+        report.expiring.sort_by_key(|expiring| expiring.expiry_date);
+        report
+    }
+}
+
+impl Credential {
+    /// The last day on which this credential is valid, if it has a valid expiry date.
+    fn expiry_date(&self) -> Option<NaiveDate> {
+        match self {
+            Credential::CreditCard(card) => card_expiry_date(card.expiry_date.as_deref()?),
+            Credential::Passport(passport) => field_date(passport.expiry_date()),
+            Credential::DriversLicense(license) => field_date(license.expiry_date.as_ref()),
+            Credential::IdentityDocument(document) => field_date(document.expiry_date.as_ref()),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(policy) => field_date(policy.expiry_date.as_ref()),
+            Credential::SshKey(key) => field_date(key.expiration_date()),
+            Credential::Unknown { ty, content } if ty == "api-key" => {
+                let value = content
+                    .get("expiryDate")?
+                    .get("value")
+                    .and_then(Value::as_str)?;
+                parse_date(value, DateParsing::Lenient).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+fn field_date(field: Option<&EditableField>) -> Option<NaiveDate> {
+    field?.date(DateParsing::Lenient).ok()
+}
+
+/// Parse the `YYYY-MM` expiry date of a card, which is valid until the end of the month. Full
+/// dates are accepted as well.
+fn card_expiry_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if let Ok(date) = parse_date(value, DateParsing::Lenient) {
+        return Some(date);
+    }
+    let (year, month) = value.split_once('-')?;
+    let first = NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)?;
+    let next = match first.month() {
+        12 => NaiveDate::from_ymd_opt(first.year() + 1, 1, 1)?,
+        month => NaiveDate::from_ymd_opt(first.year(), month + 1, 1)?,
+    };
+    next.pred_opt()
+}
@@ -0,0 +1,46 @@
+//! # Extension Sets
+//!
+//! The type parameter `E` of [Extension][super::Extension] accepts a single external type.
+//! [ExtensionSet] combines two independent extension types into one, so that crates can layer their
+//! extensions without writing a combined enum by hand. More types are combined by nesting sets:
+//!
+//! ```
+//! use credential_exchange_types::format::{Extension, ExtensionSet};
+//! # type A = (); type B = (); type C = ();
+//!
+//! type MyExtension = Extension<ExtensionSet<A, ExtensionSet<B, C>>>;
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// One of two extension types, usable as the `E` parameter of [Extension][super::Extension].
+///
+/// Deserialization first attempts `A` and falls back to `B`, like an untagged enum. Both types must
+/// therefore only accept their own extensions, which is the case of internally tagged enums on
+/// `name` without a catch-all variant. Extensions accepted by neither are kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(untagged)]
+pub enum ExtensionSet<A, B> {
+    First(A),
+    Second(B),
+}
+
+impl<A, B> ExtensionSet<A, B> {
+    /// The extension, if it is of the first type.
+    pub fn first(&self) -> Option<&A> {
+        match self {
+            ExtensionSet::First(extension) => Some(extension),
+            ExtensionSet::Second(_) => None,
+        }
+    }
+
+    /// The extension, if it is of the second type.
+    pub fn second(&self) -> Option<&B> {
+        match self {
+            ExtensionSet::First(_) => None,
+            ExtensionSet::Second(extension) => Some(extension),
+        }
+    }
+}
@@ -0,0 +1,554 @@
+//! # Extensions
+//!
+//! The [Extensions][super::Extension] known to this crate. They are parsed into their dedicated
+//! variant of [Extension][super::Extension], while any other extension is parsed into the external
+//! type `E` or kept as [Extension::Unknown][super::Extension::Unknown].
+//!
+//! The extensions which are not part of the specification are only parsed with the
+//! `non-standard-extensions` feature, so that the extensions of other providers which share their
+//! name are not read as the ones of this crate.
+
+#[cfg(feature = "non-standard-extensions")]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "non-standard-extensions")]
+use super::{Extension, FileCredential, Item, PasskeyCredential};
+#[cfg(feature = "non-standard-extensions")]
+use crate::Uri;
+use crate::{uri::Origin, B64Url, RpId};
+
+/// Carries the lifecycle state of an [Item][super::Item], so that archived or deleted items are not
+/// restored as regular items on import.
+///
+/// This type is not part of the specification and requires the `non-standard-extensions` feature.
+/// Without it, `item-state` extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct ItemStateExtension {
+    /// The state of the [Item][super::Item].
+    pub state: ItemState,
+    /// The time, serialized as a UNIX timestamp in seconds, at which the [Item][super::Item] was
+    /// moved to the trash. Only relevant for [ItemState::Trashed] items.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serialization::is_omitted",
+        with = "crate::timestamp::option"
+    )]
+    #[cfg_attr(
+        feature = "arbitrary",
+        arbitrary(with = crate::arbitrary_values::optional_timestamp)
+    )]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// The unknown members of the [ItemStateExtension], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The lifecycle state of an [Item][super::Item], see [ItemStateExtension].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "kebab-case")]
+pub enum ItemState {
+    /// The [Item][super::Item] was archived by the user. It is kept but hidden from the regular
+    /// views.
+    Archived,
+    /// The [Item][super::Item] was deleted by the user and is pending permanent removal.
+    Trashed,
+    /// The [Item][super::Item] was disabled, e.g. by an administrator, and must not be used for
+    /// autofill.
+    Disabled,
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Records how an [Item][super::Item] was used, so that the importing provider can rank its
+/// autofill suggestions as the exporting provider did.
+///
+/// This type is not part of the specification and requires the `non-standard-extensions` feature.
+/// Without it, `usage` extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct UsageExtension {
+    /// The time, serialized as a UNIX timestamp in seconds, at which the [Item][super::Item] was
+    /// last used.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serialization::is_omitted",
+        with = "crate::timestamp::option"
+    )]
+    #[cfg_attr(
+        feature = "arbitrary",
+        arbitrary(with = crate::arbitrary_values::optional_timestamp)
+    )]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// The number of times the [Item][super::Item] was used.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub use_count: Option<u64>,
+    /// The URL of the website on which the [Item][super::Item] was last filled.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub last_filled_url: Option<Uri>,
+    /// The unknown members of the [UsageExtension], see [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(feature = "non-standard-extensions")]
+impl UsageExtension {
+    /// Start building a [UsageExtension] with all its members unset.
+    pub fn builder() -> UsageExtensionBuilder {
+        UsageExtensionBuilder::default()
+    }
+}
+
+/// Builder for [UsageExtension], created by [UsageExtension::builder].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Default)]
+pub struct UsageExtensionBuilder {
+    extension: UsageExtension,
+}
+
+#[cfg(feature = "non-standard-extensions")]
+impl UsageExtensionBuilder {
+    /// Set the time at which the item was last used.
+    pub fn last_used_at(mut self, last_used_at: DateTime<Utc>) -> Self {
+        self.extension.last_used_at = Some(last_used_at);
+        self
+    }
+
+    /// Set the number of times the item was used.
+    pub fn use_count(mut self, use_count: u64) -> Self {
+        self.extension.use_count = Some(use_count);
+        self
+    }
+
+    /// Set the URL of the website on which the item was last filled.
+    pub fn last_filled_url(mut self, url: Uri) -> Self {
+        self.extension.last_filled_url = Some(url);
+        self
+    }
+
+    /// Create the [UsageExtension].
+    pub fn build(self) -> UsageExtension {
+        self.extension
+    }
+}
+
+/// Describes how a [Collection][super::Collection] is displayed by the exporting provider, so that
+/// imported folders can be told apart as they were before the exchange.
+///
+/// This type is not part of the specification and requires the `non-standard-extensions` feature.
+/// Without it, `appearance` extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct AppearanceExtension {
+    /// The color of the [Collection][super::Collection], as a CSS hexadecimal color such as
+    /// `#1e90ff`.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub color: Option<String>,
+    /// An identifier of the icon of the [Collection][super::Collection] in the exporting
+    /// provider’s icon set. Importers SHOULD map it to the closest icon they provide, if any.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub icon: Option<String>,
+    /// The position of the [Collection][super::Collection] among its siblings, in ascending
+    /// order.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub sort_order: Option<i64>,
+    /// The unknown members of the [AppearanceExtension], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Describes a [FileCredential] of the same [Item], so that importers can present the attachment
+/// without inspecting its content.
+///
+/// This type is not part of the specification and requires the `non-standard-extensions` feature.
+/// Without it, `attachment` extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentExtension {
+    /// The [id][FileCredential::id] of the [FileCredential] being described.
+    pub file: B64Url,
+    /// The media type of the file, as defined by [RFC6838](https://www.rfc-editor.org/rfc/rfc6838),
+    /// e.g. `application/pdf`.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub mime_type: Option<String>,
+    /// The time, serialized as a UNIX timestamp in seconds, at which the original file was
+    /// created.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::serialization::is_omitted",
+        with = "crate::timestamp::option"
+    )]
+    #[cfg_attr(
+        feature = "arbitrary",
+        arbitrary(with = crate::arbitrary_values::optional_timestamp)
+    )]
+    pub creation_at: Option<DateTime<Utc>>,
+    /// The [id][FileCredential::id] of another [FileCredential] of the same [Item] holding a
+    /// thumbnail of the file.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub thumbnail: Option<B64Url>,
+    /// The unknown members of the [AttachmentExtension], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Preserves the provenance of a [PasskeyCredential] of the same [Item], as reported by the
+/// [authenticator data](https://www.w3.org/TR/webauthn-3/#sctn-authenticator-data) of the
+/// authenticator it was created on.
+///
+/// This type is not part of the specification and requires the `non-standard-extensions` feature.
+/// Without it, `passkey-provenance` extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyProvenanceExtension {
+    /// The [credential_id][PasskeyCredential::credential_id] of the [PasskeyCredential] being
+    /// described.
+    pub credential_id: B64Url,
+    /// The [backup eligibility](https://www.w3.org/TR/webauthn-3/#backup-eligibility) flag of the
+    /// passkey.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub backup_eligible: Option<bool>,
+    /// The [backup state](https://www.w3.org/TR/webauthn-3/#backup-state) flag of the passkey.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub backup_state: Option<bool>,
+    /// The [AAGUID](https://www.w3.org/TR/webauthn-3/#aaguid) of the authenticator which created
+    /// the passkey.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub aaguid: Option<B64Url>,
+    /// The unknown members of the [PasskeyProvenanceExtension], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Describes the organization owning an [Account][super::Account] or a
+/// [Collection][super::Collection], and the restrictions the organization applies to the members
+/// accessing that collection, so that enterprise vaults keep their access control on import.
+///
+/// This type is not part of the specification and requires the `non-standard-extensions` feature.
+/// Without it, `organization` extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[cfg(feature = "non-standard-extensions")]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationExtension {
+    /// The identifier of the organization in the exporting provider.
+    pub organization_id: B64Url,
+    /// The display name of the organization.
+    pub organization_name: String,
+    /// Whether the members can only read the items of the [Collection][super::Collection].
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub read_only: Option<bool>,
+    /// Whether the passwords and other secrets of the items of the
+    /// [Collection][super::Collection] are hidden from the members, who may only use them for
+    /// autofill.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub hide_passwords: Option<bool>,
+    /// The unknown members of the [OrganizationExtension], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Lists who an [Item][super::Item] or [Collection][super::Collection] is shared with, and what
+/// each of them is allowed to do with it.
+///
+/// Importers SHOULD call [SharedExtension::sanitize] before applying the extension, so that
+/// accessors and permissions they do not understand are ignored as mandated by the specification.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct SharedExtension {
+    /// The accessors of the shared entity.
+    pub accessors: Vec<SharingAccessor>,
+    /// The unknown members of the [SharedExtension], see [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SharedExtension {
+    /// Start building a [SharedExtension] without any accessor.
+    pub fn builder() -> SharedExtensionBuilder {
+        SharedExtensionBuilder::default()
+    }
+
+    /// Remove the accessors of an unknown [type][SharingAccessor::ty] and the unknown
+    /// permissions, then the accessors left without any permission.
+    pub fn sanitize(&mut self) {
+        self.accessors.retain_mut(|accessor| {
+            accessor
+                .permissions
+                .retain(|permission| !matches!(permission, SharingAccessorPermission::Unknown(_)));
+            !matches!(accessor.ty, SharingAccessorType::Unknown(_))
+                && !accessor.permissions.is_empty()
+        });
+    }
+}
+
+/// Builder for [SharedExtension], created by [SharedExtension::builder].
+#[derive(Clone, Debug, Default)]
+pub struct SharedExtensionBuilder {
+    extension: SharedExtension,
+}
+
+impl SharedExtensionBuilder {
+    /// Share the entity with the user of the [Account][super::Account] `account_id`.
+    pub fn user(
+        self,
+        account_id: B64Url,
+        name: impl Into<String>,
+        permissions: impl IntoIterator<Item = SharingAccessorPermission>,
+    ) -> Self {
+        self.accessor(SharingAccessorType::User, account_id, name, permissions)
+    }
+
+    /// Share the entity with the group `group_id`.
+    pub fn group(
+        self,
+        group_id: B64Url,
+        name: impl Into<String>,
+        permissions: impl IntoIterator<Item = SharingAccessorPermission>,
+    ) -> Self {
+        self.accessor(SharingAccessorType::Group, group_id, name, permissions)
+    }
+
+    fn accessor(
+        mut self,
+        ty: SharingAccessorType,
+        account_id: B64Url,
+        name: impl Into<String>,
+        permissions: impl IntoIterator<Item = SharingAccessorPermission>,
+    ) -> Self {
+        self.extension.accessors.push(SharingAccessor {
+            ty,
+            account_id,
+            name: name.into(),
+            permissions: permissions.into_iter().collect(),
+            extra: Default::default(),
+        });
+        self
+    }
+
+    /// Create the [SharedExtension].
+    pub fn build(self) -> SharedExtension {
+        self.extension
+    }
+}
+
+/// A user or group a [SharedExtension] grants access to.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct SharingAccessor {
+    /// Whether the accessor is a single user or a group of users.
+    #[serde(rename = "type")]
+    pub ty: SharingAccessorType,
+    /// The [id][super::Account::id] of the user’s account, or the identifier of the group.
+    pub account_id: B64Url,
+    /// The display name of the user or group.
+    pub name: String,
+    /// The permissions granted to the accessor.
+    pub permissions: Vec<SharingAccessorPermission>,
+    /// The unknown members of the [SharingAccessor], see [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SharingAccessor {
+    /// Whether the accessor is granted `permission`, either directly or implied by another
+    /// permission, see [SharingAccessorPermission::implies].
+    pub fn can(&self, permission: SharingAccessorPermission) -> bool {
+        self.permissions
+            .iter()
+            .any(|granted| granted.implies(&permission))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "kebab-case")]
+pub enum SharingAccessorType {
+    /// A single user, identified by their [Account][super::Account].
+    User,
+    /// A group of users.
+    Group,
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum SharingAccessorPermission {
+    /// The accessor can see the entity, but not its secrets.
+    Read,
+    /// The accessor can see the entity including its secrets, such as passwords.
+    ReadSecret,
+    /// The accessor can modify the entity.
+    Update,
+    /// The accessor can create new items in the shared [Collection][super::Collection].
+    Create,
+    /// The accessor can delete the entity.
+    Delete,
+    /// The accessor can share the entity with others.
+    Share,
+    /// The accessor has full control over the entity, including its sharing.
+    Manage,
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+impl SharingAccessorPermission {
+    /// Whether being granted this permission grants `other` as well. Every permission implies
+    /// itself, [Manage][Self::Manage] implies all the known permissions, and any known
+    /// permission implies [Read][Self::Read].
+    pub fn implies(&self, other: &Self) -> bool {
+        use SharingAccessorPermission::*;
+        match (self, other) {
+            (Unknown(_), _) | (_, Unknown(_)) => self == other,
+            (Manage, _) | (_, Read) => true,
+            _ => self == other,
+        }
+    }
+}
+
+#[cfg(feature = "non-standard-extensions")]
+impl<E> Item<E> {
+    /// Find the [AttachmentExtension] describing the given [FileCredential] of this [Item].
+    pub fn attachment(&self, file: &FileCredential) -> Option<&AttachmentExtension> {
+        self.extensions
+            .iter()
+            .flatten()
+            .find_map(|extension| match extension {
+                Extension::Attachment(attachment) if attachment.file == file.id => {
+                    Some(attachment.as_ref())
+                }
+                _ => None,
+            })
+    }
+
+    /// Find the [PasskeyProvenanceExtension] describing the given [PasskeyCredential] of this
+    /// [Item].
+    pub fn passkey_provenance(
+        &self,
+        passkey: &PasskeyCredential,
+    ) -> Option<&PasskeyProvenanceExtension> {
+        self.extensions
+            .iter()
+            .flatten()
+            .find_map(|extension| match extension {
+                Extension::PasskeyProvenance(provenance)
+                    if provenance.credential_id == passkey.credential_id =>
+                {
+                    Some(provenance.as_ref())
+                }
+                _ => None,
+            })
+    }
+}
+
+/// A set of domains which belong to the same website, e.g. `amazon.com` and `amazon.de`. When
+/// part of an [Account][super::Account] it applies to all of its items, when part of an
+/// [Item][super::Item] only to that item.
+///
+/// As the extension is provided by the exporter, nothing guarantees that its domains really belong
+/// to the same owner, so it is not used by [Account::items_for_url][super::Account::items_for_url].
+/// Importers which trust a set of domains, e.g. after confirming it with the user, can pass it to
+/// [its variant][super::Account::items_for_url_with_equivalent_domains] instead.
+///
+/// The `equivalent-domains` extension is not part of the specification, and is only parsed into
+/// this type with the `non-standard-extensions` feature. Without it, `equivalent-domains`
+/// extensions are parsed into the external type `E` or kept as
+/// [Extension::Unknown][super::Extension::Unknown].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct EquivalentDomainsExtension {
+    /// The equivalent domains. Only the listed hosts are equivalent, not their subdomains, and
+    /// single labels and public suffixes such as `com` or `co.uk` are ignored, see
+    /// [RpId::is_valid][crate::RpId::is_valid].
+    pub domains: Vec<String>,
+    /// The unknown members of the [EquivalentDomainsExtension], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EquivalentDomainsExtension {
+    /// Whether the hosts of both URLs are part of this set of equivalent domains. A missing scheme
+    /// is treated as `https`, and the hosts are compared case insensitively, in their ASCII form.
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        match (Origin::parse(a), Origin::parse(b)) {
+            (Some(a), Some(b)) => self.contains(&a) && self.contains(&b),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn contains(&self, origin: &Origin) -> bool {
+        self.domains.iter().any(|domain| {
+            crate::idna::domain_to_ascii(domain.trim().trim_end_matches('.'))
+                .and_then(|domain| RpId::parse(&domain).ok())
+                .is_some_and(|domain| domain.as_str() == origin.host())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn domains(domains: &[&str]) -> EquivalentDomainsExtension {
+        EquivalentDomainsExtension {
+            domains: domains.iter().map(|domain| domain.to_string()).collect(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn equivalent_domains_match_exact_hosts() {
+        let domains = domains(&["amazon.com", "Amazon.de."]);
+        assert!(domains.matches("https://amazon.com/login", "amazon.de"));
+        assert!(!domains.matches("https://amazon.com", "https://evil.amazon.de"));
+    }
+
+    #[test]
+    fn equivalent_domains_ignore_public_suffixes() {
+        let domains = domains(&["com", "co.uk", "bank.com"]);
+        assert!(!domains.matches("https://bank.com", "https://evil.com"));
+        assert!(!domains.matches("https://bank.com", "https://evil.co.uk"));
+    }
+}
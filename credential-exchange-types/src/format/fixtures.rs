@@ -0,0 +1,82 @@
+//! # Test Fixtures
+//!
+//! Builders of small documents shared by the tests of the operations on accounts and exchanges.
+//! Identifiers are given as a single byte, see [id].
+
+use serde_json::{json, Value};
+
+use super::{Account, Header};
+use crate::B64Url;
+
+/// The identifier made of the single byte `value`.
+pub(super) fn id(value: u8) -> B64Url {
+    B64Url::from(vec![value])
+}
+
+/// A `login` item with the given credentials.
+pub(super) fn item(item: u8, title: &str, credentials: Value) -> Value {
+    json!({
+        "id": id(item),
+        "type": "login",
+        "title": title,
+        "credentials": credentials,
+    })
+}
+
+/// A `basic-auth` credential for `https://example.com`.
+pub(super) fn basic_auth(username: &str, password: &str) -> Value {
+    json!({
+        "type": "basic-auth",
+        "urls": ["https://example.com"],
+        "username": { "id": id(1), "fieldType": "string", "value": username },
+        "password": { "id": id(2), "fieldType": "concealed-string", "value": password },
+    })
+}
+
+/// A `note` credential.
+pub(super) fn note(content: &str) -> Value {
+    json!({ "type": "note", "content": content })
+}
+
+/// A collection titled after its id.
+pub(super) fn collection(collection: u8, items: Value, sub_collections: Value) -> Value {
+    json!({
+        "id": id(collection),
+        "title": format!("Collection {collection}"),
+        "items": items,
+        "subCollections": sub_collections,
+    })
+}
+
+/// A link to an item of the same account.
+pub(super) fn link(item: u8) -> Value {
+    json!({ "item": id(item) })
+}
+
+/// A link to an item of another account.
+pub(super) fn link_to(item: u8, account: u8) -> Value {
+    json!({ "item": id(item), "account": id(account) })
+}
+
+/// An account of `jane@example.com`.
+pub(super) fn account(account: u8, items: Value, collections: Value) -> Account {
+    serde_json::from_value(json!({
+        "id": id(account),
+        "userName": "",
+        "email": "jane@example.com",
+        "items": items,
+        "collections": collections,
+    }))
+    .expect("the account is valid")
+}
+
+/// An exchange of `exporter.example.com` at the UNIX time `timestamp`.
+pub(super) fn header(timestamp: u64, accounts: Vec<Account>) -> Header {
+    serde_json::from_value(json!({
+        "version": 0,
+        "exporter": "exporter.example.com",
+        "timestamp": timestamp,
+        "accounts": accounts,
+    }))
+    .expect("the header is valid")
+}
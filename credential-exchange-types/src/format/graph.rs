@@ -0,0 +1,232 @@
+//! # Reference Graphs
+//!
+//! A graph of the [Items][super::Item] of an exchange linked by
+//! [ItemReferenceCredentials][super::ItemReferenceCredential], so that importers can handle
+//! clusters of linked items, e.g. a login with its card and billing address, as a unit, and reject
+//! cyclic references.
+
+use std::collections::HashMap;
+
+use super::{Credential, Header};
+use crate::B64Url;
+
+/// Identifies an [Item][super::Item] across the accounts of an exchange.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ItemKey {
+    /// The [id][super::Account::id] of the account owning the item.
+    pub account: B64Url,
+    /// The [id][super::Item::id] of the item.
+    pub item: B64Url,
+}
+
+/// The references between the items of an exchange, as returned by [Header::reference_graph].
+///
+/// Every item of the exchange is a node of the graph, as well as every referenced item which is
+/// not part of the exchange, e.g. an item shared by another account. Each
+/// [ItemReferenceCredential][super::ItemReferenceCredential] is an edge from the item holding it to
+/// the referenced item.
+#[derive(Clone, Debug, Default)]
+pub struct ReferenceGraph {
+    nodes: Vec<ItemKey>,
+    index: HashMap<ItemKey, usize>,
+    outgoing: Vec<Vec<usize>>,
+    incoming: Vec<Vec<usize>>,
+}
+
+impl<E> Header<E> {
+    /// Build the [ReferenceGraph] of the items of this exchange.
+    pub fn reference_graph(&self) -> ReferenceGraph {
+        let mut graph = ReferenceGraph::default();
+        for account in &self.accounts {
+            for item in &account.items {
+                graph.node(ItemKey {
+                    account: account.id.clone(),
+                    item: item.id.clone(),
+                });
+            }
+        }
+        for account in &self.accounts {
+            for item in &account.items {
+                let from = graph.index[&ItemKey {
+                    account: account.id.clone(),
+                    item: item.id.clone(),
+                }];
+                for credential in &item.credentials {
+                    if let Credential::ItemReference(reference) = credential {
+                        let to = graph.node(ItemKey {
+                            account: reference
+                                .reference
+                                .account
+                                .clone()
+                                .unwrap_or_else(|| account.id.clone()),
+                            item: reference.reference.item.clone(),
+                        });
+                        graph.outgoing[from].push(to);
+                        graph.incoming[to].push(from);
+                    }
+                }
+            }
+        }
+        graph
+    }
+}
+
+impl ReferenceGraph {
+    /// Every node of the graph: the items of the exchange in order, followed by the referenced
+    /// items which are not part of it.
+    pub fn nodes(&self) -> &[ItemKey] {
+        &self.nodes
+    }
+
+    /// Whether `item` is a node of the graph.
+    pub fn contains(&self, item: &ItemKey) -> bool {
+        self.index.contains_key(item)
+    }
+
+    /// The items referenced by `item`.
+    pub fn references(&self, item: &ItemKey) -> Vec<&ItemKey> {
+        self.edges(&self.outgoing, item)
+    }
+
+    /// The items referencing `item`.
+    pub fn referenced_by(&self, item: &ItemKey) -> Vec<&ItemKey> {
+        self.edges(&self.incoming, item)
+    }
+
+    /// The items referenced by `item` or referencing it, without duplicates.
+    pub fn neighbors(&self, item: &ItemKey) -> Vec<&ItemKey> {
+        let mut neighbors = self.references(item);
+        for other in self.referenced_by(item) {
+            if !neighbors.contains(&other) {
+                neighbors.push(other);
+            }
+        }
+        neighbors
+    }
+
+    /// The groups of items linked together by references in either direction, in the order of
+    /// their first node. Items without any reference form a group on their own.
+    pub fn components(&self) -> Vec<Vec<&ItemKey>> {
+        let mut component = vec![usize::MAX; self.nodes.len()];
+        let mut components = Vec::new();
+        for root in 0..self.nodes.len() {
+            if component[root] != usize::MAX {
+                continue;
+            }
+            let mut members = Vec::new();
+            let mut stack = vec![root];
+            component[root] = components.len();
+            while let Some(node) = stack.pop() {
+                members.push(node);
+                for &other in self.outgoing[node].iter().chain(&self.incoming[node]) {
+                    if component[other] == usize::MAX {
+                        component[other] = components.len();
+                        stack.push(other);
+                    }
+                }
+            }
+            members.sort_unstable();
+            components.push(members.into_iter().map(|node| &self.nodes[node]).collect());
+        }
+        components
+    }
+
+    /// The groups of items whose references form a cycle, i.e. the strongly connected components
+    /// of the graph with more than one item, and the items referencing themselves.
+    pub fn cycles(&self) -> Vec<Vec<&ItemKey>> {
+        self.strongly_connected()
+            .into_iter()
+            .filter(|nodes| nodes.len() > 1 || self.outgoing[nodes[0]].contains(&nodes[0]))
+            .map(|mut nodes| {
+                nodes.sort_unstable();
+                nodes.into_iter().map(|node| &self.nodes[node]).collect()
+            })
+            .collect()
+    }
+
+    /// Whether the references of any item form a cycle, see [ReferenceGraph::cycles].
+    pub fn has_cycles(&self) -> bool {
+        !self.cycles().is_empty()
+    }
+
+    /// The index of `key`, which is added if it isn't part of the graph yet.
+    fn node(&mut self, key: ItemKey) -> usize {
+        if let Some(index) = self.index.get(&key) {
+            return *index;
+        }
+        let index = self.nodes.len();
+        self.index.insert(key.clone(), index);
+        self.nodes.push(key);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        index
+    }
+
+    fn edges(&self, edges: &[Vec<usize>], item: &ItemKey) -> Vec<&ItemKey> {
+        let Some(&index) = self.index.get(item) else {
+            return Vec::new();
+        };
+        let mut targets: Vec<&ItemKey> = Vec::new();
+        for &target in &edges[index] {
+            if !targets.contains(&&self.nodes[target]) {
+                targets.push(&self.nodes[target]);
+            }
+        }
+        targets
+    }
+
+    /// The strongly connected components of the graph, using an iterative version of Tarjan's
+    /// algorithm so that long chains of references cannot overflow the stack.
+    fn strongly_connected(&self) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+        let count = self.nodes.len();
+        let mut index = vec![UNVISITED; count];
+        let mut low = vec![0; count];
+        let mut on_stack = vec![false; count];
+        let mut stack = Vec::new();
+        let mut next = 0;
+        let mut components = Vec::new();
+
+        for root in 0..count {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            // Each entry is a node and the position of the next edge to follow.
+            let mut work = vec![(root, 0)];
+            while let Some((node, edge)) = work.pop() {
+                if edge == 0 {
+                    index[node] = next;
+                    low[node] = next;
+                    next += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+                if let Some(&target) = self.outgoing[node].get(edge) {
+                    work.push((node, edge + 1));
+                    if index[target] == UNVISITED {
+                        work.push((target, 0));
+                    } else if on_stack[target] {
+                        low[node] = low[node].min(index[target]);
+                    }
+                    continue;
+                }
+
+                if low[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+                if let Some(&(parent, _)) = work.last() {
+                    low[parent] = low[parent].min(low[node]);
+                }
+            }
+        }
+        components
+    }
+}
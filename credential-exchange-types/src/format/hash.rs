@@ -0,0 +1,226 @@
+//! # Content Hashing
+//!
+//! Computes digests of the semantic content of [Items][Item] and [Credentials][Credential], which
+//! stay the same when an exporter regenerates identifiers, updates timestamps or reorders members.
+//! They are used to match entities in [Account::merge][super::Account::merge] and
+//! [Account::diff][super::Account::diff], and allow providers to detect items which were already
+//! imported.
+
+use data_encoding::HEXLOWER;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::{Credential, Item};
+use crate::{
+    serialization::{with_optional_members, OptionalMembers},
+    B64Url,
+};
+
+/// The members holding the identifier and the timestamps of an item, which are not part of its
+/// content.
+const ITEM_IGNORED: [&str; 3] = ["id", "creationAt", "modifiedAt"];
+
+/// A SHA-256 digest of the semantic content of an entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ContentHash([u8; 32]);
+
+impl ContentHash {
+    /// A digest from its raw bytes, e.g. as previously stored by a provider.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// The raw bytes of the digest.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Hash the JSON representation of `value`. Object members are hashed in the order of their
+    /// names, so that the digest does not depend on the order in which they were parsed.
+    pub(crate) fn of<T: Serialize>(value: &T) -> serde_json::Result<Self> {
+        Ok(Self::of_value(&to_value(value)?))
+    }
+
+    fn of_value(value: &Value) -> Self {
+        let mut hasher = Sha256::new();
+        update(&mut hasher, value);
+        Self(hasher.finalize().into())
+    }
+}
+
+impl std::fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&HEXLOWER.encode(&self.0))
+    }
+}
+
+impl Credential {
+    /// Compute the [ContentHash] of this credential, ignoring its identifiers: the
+    /// [id][super::FileCredential::id] of file credentials and the [ids][super::EditableField::id]
+    /// of their fields. The `id` members of [unknown credentials][Credential::Unknown] and unknown
+    /// members are part of the content.
+    pub fn content_hash(&self) -> ContentHash {
+        ContentHash::of_value(&self.content_value())
+    }
+
+    /// Serialize this credential with its identifiers cleared.
+    fn content_value(&self) -> Value {
+        let mut credential = self.clone();
+        if let Credential::File(file) = &mut credential {
+            file.id = B64Url::from(Vec::new());
+        }
+        for field in credential.editable_fields_mut() {
+            field.id = B64Url::from(Vec::new());
+        }
+        to_value(&credential).expect("credentials are always serializable")
+    }
+}
+
+impl<E: Serialize> Item<E> {
+    /// Compute the [ContentHash] of this item, ignoring its [id][Item::id], its
+    /// [creation][Item::creation_at] and [modification][Item::modified_at] timestamps, and the
+    /// identifiers of its credentials, see [Credential::content_hash].
+    ///
+    /// Returns an error if one of the extensions of type `E` cannot be serialized.
+    pub fn content_hash(&self) -> serde_json::Result<ContentHash> {
+        let mut value = to_value(self)?;
+        if let Some(object) = value.as_object_mut() {
+            for member in ITEM_IGNORED {
+                object.remove(member);
+            }
+            let credentials = self.credentials.iter().map(Credential::content_value);
+            object.insert("credentials".to_owned(), credentials.collect());
+        }
+        Ok(ContentHash::of_value(&value))
+    }
+}
+
+/// Serialize `value` with its optional members omitted, so that the digest doesn't depend on how
+/// the caller serializes documents.
+fn to_value<T: Serialize>(value: &T) -> serde_json::Result<Value> {
+    serde_json::to_value(with_optional_members(value, OptionalMembers::Omit))
+}
+
+/// Feed an unambiguous encoding of `value` to the hasher: every value is prefixed by its kind, and
+/// strings and collections by their length.
+fn update(hasher: &mut Sha256, value: &Value) {
+    match value {
+        Value::Null => hasher.update(b"n"),
+        Value::Bool(value) => hasher.update([b'b', u8::from(*value)]),
+        Value::Number(number) => {
+            let number = number.to_string();
+            hasher.update(b"d");
+            update_len(hasher, number.len());
+            hasher.update(number);
+        }
+        Value::String(string) => update_str(hasher, string),
+        Value::Array(values) => {
+            hasher.update(b"a");
+            update_len(hasher, values.len());
+            values.iter().for_each(|value| update(hasher, value));
+        }
+        Value::Object(members) => {
+            let mut members: Vec<_> = members.iter().collect();
+            members.sort_unstable_by_key(|(name, _)| *name);
+            hasher.update(b"o");
+            update_len(hasher, members.len());
+            for (name, value) in members {
+                update_str(hasher, name);
+                update(hasher, value);
+            }
+        }
+    }
+}
+
+fn update_str(hasher: &mut Sha256, value: &str) {
+    hasher.update(b"s");
+    update_len(hasher, value.len());
+    hasher.update(value);
+}
+
+fn update_len(hasher: &mut Sha256, len: usize) {
+    hasher.update((len as u64).to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn credential(field_id: &str, password: &str) -> Credential {
+        serde_json::from_value(json!({
+            "type": "basic-auth",
+            "urls": ["https://example.com"],
+            "password": { "id": field_id, "fieldType": "concealed-string", "value": password },
+        }))
+        .expect("the credential is valid")
+    }
+
+    #[test]
+    fn credential_hash_ignores_nested_ids() {
+        assert_eq!(
+            credential("AAAA", "secret").content_hash(),
+            credential("BBBB", "secret").content_hash()
+        );
+        assert_ne!(
+            credential("AAAA", "secret").content_hash(),
+            credential("AAAA", "other").content_hash()
+        );
+    }
+
+    #[test]
+    fn item_hash_keeps_unknown_ids() {
+        let item = |credential_id: &str, extension_id: &str| -> Item {
+            serde_json::from_value(json!({
+                "id": "AQ",
+                "type": "login",
+                "title": "Example",
+                "credentials": [
+                    credential("AAAA", "secret"),
+                    { "type": "future-credential", "id": credential_id },
+                ],
+                "extensions": [{ "name": "future-extension", "id": extension_id }],
+            }))
+            .expect("the item is valid")
+        };
+
+        let mut renamed = item("AAAA", "AAAA");
+        renamed.id = B64Url::from(vec![2]);
+        renamed.credentials[0] = credential("BBBB", "secret");
+        assert_eq!(
+            item("AAAA", "AAAA").content_hash().unwrap(),
+            renamed.content_hash().unwrap()
+        );
+        assert_ne!(
+            item("AAAA", "AAAA").content_hash().unwrap(),
+            item("BBBB", "AAAA").content_hash().unwrap()
+        );
+        assert_ne!(
+            item("AAAA", "AAAA").content_hash().unwrap(),
+            item("AAAA", "BBBB").content_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn credential_hash_ignores_the_optional_members_mode() {
+        let credential = credential("AAAA", "secret");
+        let hash = credential.content_hash();
+        let serialized = serde_json::to_value(with_optional_members(
+            &HashDuringSerialization(&credential),
+            OptionalMembers::Null,
+        ))
+        .expect("the credential is serializable");
+        assert_eq!(serialized, json!(hash.to_string()));
+    }
+
+    /// Serializes the content hash of a credential, to compute it while a mode is in effect.
+    struct HashDuringSerialization<'a>(&'a Credential);
+
+    impl Serialize for HashDuringSerialization<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&self.0.content_hash().to_string())
+        }
+    }
+}
@@ -4,7 +4,13 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::format::EditableField;
+use crate::{
+    format::{
+        redact::{debug_masked, Masked, MaskedMap},
+        EditableField, FieldType,
+    },
+    B64Url,
+};
 
 /// A [PersonNameCredential] represents a person’s name as fields derived from Unicode Locale Data
 /// Markup Language Part 8: Person Names.
@@ -12,120 +18,194 @@ use crate::format::EditableField;
 /// All fields are marked as optional because an exporting provider SHOULD refrain from making
 /// decisions about splitting up a name into any parts that were not explicitly provided as such,
 /// since that often introduces errors.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct PersonNameCredential {
     /// This OPTIONAL field contains a title or honorific qualifier. For example, "Ms.", "Mr.", or
     /// "Dr".
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     title: Option<EditableField>,
     /// This OPTIONAL field the person’s given name.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     given: Option<EditableField>,
     /// This OPTIONAL field contains a nickname or preferred name.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     given_informal: Option<EditableField>,
     /// This OPTIONAL field contains additional names or middle names.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     given2: Option<EditableField>,
     /// This OPTIONAL field contains the prefix of the surname. For example, "van der" in "van der
     /// Poel" or "bint" in "bint Fadi".
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     surname_prefix: Option<EditableField>,
     /// This OPTIONAL field contains the person’s family name.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     surname: Option<EditableField>,
     /// This OPTIONAL field contains the person’s secondary surname, which is used in some
     /// cultures.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     surname2: Option<EditableField>,
     /// This OPTIONAL field contains a credential or accreditation qualifier. For example, "PhD" or
     /// "MBA".
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     credentials: Option<EditableField>,
     /// This OPTIONAL field contains a generation qualifier. For example, "Jr." or "III".
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     generation: Option<EditableField>,
+    /// The unknown members of the [PersonNameCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+debug_masked!(PersonNameCredential {
+    title,
+    given,
+    given_informal,
+    given2,
+    surname_prefix,
+    surname,
+    surname2,
+    credentials,
+    generation
+});
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct CreditCardCredential {
     pub number: String,
     pub full_name: String,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub card_type: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub verification_number: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub expiry_date: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub valid_from: Option<String>,
+    /// The unknown members of the [CreditCardCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for CreditCardCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreditCardCredential")
+            .field("number", &Masked)
+            .field("full_name", &self.full_name)
+            .field("card_type", &self.card_type)
+            .field(
+                "verification_number",
+                &self.verification_number.as_ref().map(|_| Masked),
+            )
+            .field("expiry_date", &self.expiry_date)
+            .field("valid_from", &self.valid_from)
+            .field("extra", &MaskedMap(&self.extra))
+            .finish()
+    }
 }
 
 /// An [AddressCredential] provides information for autofilling address forms.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct AddressCredential {
     /// The address line for the address. This is intentionally flexible to accommodate different
     /// address formats. Implementers MUST support multi-line addresses for this field, where each
     /// line is separated by a `\n` line feed.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub street_address: Option<EditableField>,
     /// The ZIP or postal code for the address.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub postal_code: Option<EditableField>,
     /// The city for the address.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub city: Option<EditableField>,
     /// The province, state, or territory for the address.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub territory: Option<EditableField>,
     /// The country for the address. This MUST conform to the
     /// [ISO 3166-1 alpha-2](https://www.iso.org/iso-3166-country-codes.html) format.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub country: Option<EditableField>,
     /// The phone number associated with the address.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub tel: Option<EditableField>,
+    /// The unknown members of the [AddressCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+debug_masked!(AddressCredential {
+    street_address,
+    postal_code,
+    city,
+    territory,
+    country,
+    tel
+});
+
 /// A [DriversLicenseCredential] contains information about a person’s driver’s license. The fields
 /// reflect the relevant set of mandatory data fields defined by
 /// [ISO 18013-1](https://www.iso.org/standard/63798.html).
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct DriversLicenseCredential {
     /// The full name of the license holder.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub full_name: Option<EditableField>,
     /// Day, month, and year on which the license holder was born.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub birth_date: Option<EditableField>,
     /// The date on which the license was issued.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub issue_date: Option<EditableField>,
     /// The date on which the license expires.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub expiry_date: Option<EditableField>,
     /// The official body or government agency responsible for issuing the license.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub issuing_authority: Option<EditableField>,
     /// The principal administrative subdivision of the license’s country of origin. Examples of
     /// administrative subdivisions are states or provinces. This MUST conform to the ISO 3166-2
     /// format.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub territory: Option<EditableField>,
     /// The license’s country of origin. This MUST conform to the ISO 3166-1 alpha-2 format.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub country: Option<EditableField>,
     /// The number assigned by the issuing authority.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub license_number: Option<EditableField>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub license_class: Option<EditableField>,
+    /// The unknown members of the [DriversLicenseCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+debug_masked!(DriversLicenseCredential {
+    full_name,
+    birth_date,
+    issue_date,
+    expiry_date,
+    issuing_authority,
+    territory,
+    country,
+    license_number,
+    license_class
+});
+
 /// An [IdentityDocumentCredential] is for any document, card, or number identifying a person or
 /// entity. Examples include national ID cards, Social Security Numbers (SSN), Tax Identification
 /// Numbers (TIN), health insurance cards, or Value-Added Tax (VAT) numbers.
@@ -134,92 +214,590 @@ pub struct DriversLicenseCredential {
 /// identificationNumber field, since the others are generally considered to be undefined in its
 /// case.
 ///
-/// Note: Driver’s licenses and passports may be accepted as identity verification in some
-/// countries, but they are specified separately in the [DriversLicenseCredential] and
-/// [PassportCredential] types, respectively.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Note: Driver’s licenses, passports and birth certificates may be accepted as identity
+/// verification in some countries, but they are specified separately in the
+/// [DriversLicenseCredential], [PassportCredential] and non-standard `BirthCertificateCredential`
+/// types, respectively.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityDocumentCredential {
     /// The document’s issuing country. This MUST conform to the ISO 3166-1 alpha-2 format.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub issuing_country: Option<EditableField>,
     /// The document’s identifying number. This identifying number is tied to the issuance of the
     /// document and is expected to change upon its reissuance, even when the person’s information
     /// might remain the same.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub document_number: Option<EditableField>,
     /// The person’s or other entity’s identification number. This identifying number is generally
     /// expected to remain stable across reissuances of the identity document itself. For
     /// identification numbers that are not an identity document (e.g., SSN, TIN, or VAT), this
     /// field is generally the only one that’s expected to be present in the credential.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub identification_number: Option<EditableField>,
     /// The person’s nationality.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub nationality: Option<EditableField>,
     /// The person’s full name.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub full_name: Option<EditableField>,
     /// The person’s date of birth.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub birth_date: Option<EditableField>,
     /// The person’s place of birth.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub birth_place: Option<EditableField>,
     /// The person’s sex or gender.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub sex: Option<EditableField>,
     /// The date on which the document was issued.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub issue_date: Option<EditableField>,
     /// The date on which the document expires.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub expiry_date: Option<EditableField>,
     /// The official body or government agency responsible for issuing the document.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub issuing_authority: Option<EditableField>,
+    /// The unknown members of the [IdentityDocumentCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+debug_masked!(IdentityDocumentCredential {
+    issuing_country,
+    document_number,
+    identification_number,
+    nationality,
+    full_name,
+    birth_date,
+    birth_place,
+    sex,
+    issue_date,
+    expiry_date,
+    issuing_authority
+});
+
 /// A [PassportCredential] contains the details of a person’s passport. The fields reflect the
 /// relevant set of data elements defined by ICAO Doc 9303 Part 4.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct PassportCredential {
     /// The passport’s issuing country. This MUST conform to the ISO 3166-1 alpha-2 format.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     issuing_country: Option<EditableField>,
     /// The passport’s document type. This MUST be a valid document code as defined in ICAO Doc
     /// 9303 Part 4.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     passport_type: Option<EditableField>,
     /// The passport’s identifying number.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     passport_number: Option<EditableField>,
     /// The person’s national identification number.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     national_identification_number: Option<EditableField>,
     /// The person’s nationality.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     nationality: Option<EditableField>,
     /// The person’s full name.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     full_name: Option<EditableField>,
     /// The person’s date of birth.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     birth_date: Option<EditableField>,
     /// The person’s place of birth.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     birth_place: Option<EditableField>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     /// The person’s sex or gender.
     sex: Option<EditableField>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     /// The date on which the passport was issued.
     issue_date: Option<EditableField>,
     /// The date on which the passport expires.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     expiry_date: Option<EditableField>,
     /// The official body or government agency responsible for issuing the passport.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     issuing_authority: Option<EditableField>,
+    /// The unknown members of the [PassportCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+debug_masked!(PassportCredential {
+    issuing_country,
+    passport_type,
+    passport_number,
+    national_identification_number,
+    nationality,
+    full_name,
+    birth_date,
+    birth_place,
+    sex,
+    issue_date,
+    expiry_date,
+    issuing_authority
+});
+
+/// A [BirthCertificateCredential] contains the details of a person’s birth certificate, as
+/// recorded by a civil registry.
+///
+/// This type is not part of the specification and requires the `non-standard-credentials`
+/// feature. Without it, `birth-certificate` credentials are kept as
+/// [Credential::Unknown][super::Credential::Unknown].
+#[cfg(feature = "non-standard-credentials")]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct BirthCertificateCredential {
+    /// The number under which the birth was registered.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub registration_number: Option<EditableField>,
+    /// The person’s full name.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub full_name: Option<EditableField>,
+    /// The person’s date of birth.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub birth_date: Option<EditableField>,
+    /// The person’s place of birth.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub birth_place: Option<EditableField>,
+    /// The full name of the person’s first parent.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub first_parent_name: Option<EditableField>,
+    /// The full name of the person’s second parent.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub second_parent_name: Option<EditableField>,
+    /// The official body or government agency responsible for issuing the certificate.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub issuing_authority: Option<EditableField>,
+    /// The date on which the certificate was issued.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub issue_date: Option<EditableField>,
+    /// The unknown members of the [BirthCertificateCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(feature = "non-standard-credentials")]
+debug_masked!(BirthCertificateCredential {
+    registration_number,
+    full_name,
+    birth_date,
+    birth_place,
+    first_parent_name,
+    second_parent_name,
+    issuing_authority,
+    issue_date
+});
+
+/// An [InsurancePolicyCredential] contains the details of an insurance policy, e.g. for health,
+/// home, car or life insurance.
+///
+/// This type is not part of the specification and requires the `non-standard-credentials`
+/// feature. Without it, `insurance-policy` credentials are kept as
+/// [Credential::Unknown][super::Credential::Unknown].
+#[cfg(feature = "non-standard-credentials")]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub struct InsurancePolicyCredential {
+    /// The name of the company providing the insurance.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub insurer: Option<EditableField>,
+    /// The policy’s identifying number.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub policy_number: Option<EditableField>,
+    /// The kind of insurance, e.g. health or home.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub policy_type: Option<EditableField>,
+    /// The maximum amount covered by the policy, including its currency.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub coverage_amount: Option<EditableField>,
+    /// The amount paid by the insured person before the insurer covers the rest, including its
+    /// currency.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub deductible: Option<EditableField>,
+    /// The date from which the policy is in effect.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub start_date: Option<EditableField>,
+    /// The date on which the policy expires.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub expiry_date: Option<EditableField>,
+    /// The name of the agent managing the policy.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub agent_name: Option<EditableField>,
+    /// The phone number of the agent managing the policy.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub agent_phone: Option<EditableField>,
+    /// The email address of the agent managing the policy.
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
+    pub agent_email: Option<EditableField>,
+    /// The unknown members of the [InsurancePolicyCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[cfg(feature = "non-standard-credentials")]
+debug_masked!(InsurancePolicyCredential {
+    insurer,
+    policy_number,
+    policy_type,
+    coverage_amount,
+    deductible,
+    start_date,
+    expiry_date,
+    agent_name,
+    agent_phone,
+    agent_email
+});
+
+impl PersonNameCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.title,
+            &self.given,
+            &self.given_informal,
+            &self.given2,
+            &self.surname_prefix,
+            &self.surname,
+            &self.surname2,
+            &self.credentials,
+            &self.generation,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.title,
+            &mut self.given,
+            &mut self.given_informal,
+            &mut self.given2,
+            &mut self.surname_prefix,
+            &mut self.surname,
+            &mut self.surname2,
+            &mut self.credentials,
+            &mut self.generation,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl AddressCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.street_address,
+            &self.postal_code,
+            &self.city,
+            &self.territory,
+            &self.country,
+            &self.tel,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.street_address,
+            &mut self.postal_code,
+            &mut self.city,
+            &mut self.territory,
+            &mut self.country,
+            &mut self.tel,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The lines of the [street address][AddressCredential::street_address], without the empty
+    /// lines.
+    pub fn address_lines(&self) -> Vec<&str> {
+        self.street_address
+            .iter()
+            .flat_map(|field| field.value.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    /// Replace the [street address][AddressCredential::street_address] with `lines`, separated by
+    /// `\n`. The field is created if it is missing, and removed if there are no lines.
+    pub fn set_address_lines(&mut self, lines: &[&str]) {
+        let lines: Vec<&str> = lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.is_empty() {
+            self.street_address = None;
+            return;
+        }
+        let value = lines.join("\n");
+        match &mut self.street_address {
+            Some(field) => field.value = value,
+            None => {
+                self.street_address = Some(EditableField {
+                    id: B64Url::generate_id(),
+                    field_type: FieldType::String,
+                    value,
+                    label: None,
+                    extensions: None,
+                    extra: Default::default(),
+                })
+            }
+        }
+    }
+
+    /// Split a street address as written by exporters into its lines.
+    ///
+    /// Besides `\n`, lines may be separated by `\r\n`, `\r`, a Unicode line separator, an escaped
+    /// `\\n` or an HTML `<br>`. Addresses written on a single line are split on semicolons and
+    /// commas, e.g. `Flat 2, 10 High Street` gives `Flat 2` and `10 High Street`. Lines are
+    /// trimmed and empty lines are removed.
+    pub fn parse_address_lines(value: &str) -> Vec<String> {
+        let mut normalized = value
+            .replace("\r\n", "\n")
+            .replace(['\r', '\u{2028}'], "\n")
+            .replace("\\n", "\n");
+        for tag in ["<br>", "<br/>", "<br />", "<BR>", "<BR/>", "<BR />"] {
+            normalized = normalized.replace(tag, "\n");
+        }
+        let separators: &[char] = if normalized.contains('\n') {
+            &['\n']
+        } else {
+            &[';', ',']
+        };
+        normalized
+            .split(separators)
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Rewrite the [street address][AddressCredential::street_address] with its lines separated by
+    /// `\n`, see [AddressCredential::parse_address_lines]. The field is removed if it has no
+    /// lines.
+    pub fn normalize_address_lines(&mut self) {
+        if let Some(field) = &self.street_address {
+            let lines = Self::parse_address_lines(&field.value);
+            self.set_address_lines(&lines.iter().map(String::as_str).collect::<Vec<_>>());
+        }
+    }
+}
+
+impl DriversLicenseCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.full_name,
+            &self.birth_date,
+            &self.issue_date,
+            &self.expiry_date,
+            &self.issuing_authority,
+            &self.territory,
+            &self.country,
+            &self.license_number,
+            &self.license_class,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.full_name,
+            &mut self.birth_date,
+            &mut self.issue_date,
+            &mut self.expiry_date,
+            &mut self.issuing_authority,
+            &mut self.territory,
+            &mut self.country,
+            &mut self.license_number,
+            &mut self.license_class,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl IdentityDocumentCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.issuing_country,
+            &self.document_number,
+            &self.identification_number,
+            &self.nationality,
+            &self.full_name,
+            &self.birth_date,
+            &self.birth_place,
+            &self.sex,
+            &self.issue_date,
+            &self.expiry_date,
+            &self.issuing_authority,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.issuing_country,
+            &mut self.document_number,
+            &mut self.identification_number,
+            &mut self.nationality,
+            &mut self.full_name,
+            &mut self.birth_date,
+            &mut self.birth_place,
+            &mut self.sex,
+            &mut self.issue_date,
+            &mut self.expiry_date,
+            &mut self.issuing_authority,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl PassportCredential {
+    /// The date on which the passport expires.
+    pub(crate) fn expiry_date(&self) -> Option<&EditableField> {
+        self.expiry_date.as_ref()
+    }
+
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.issuing_country,
+            &self.passport_type,
+            &self.passport_number,
+            &self.national_identification_number,
+            &self.nationality,
+            &self.full_name,
+            &self.birth_date,
+            &self.birth_place,
+            &self.sex,
+            &self.issue_date,
+            &self.expiry_date,
+            &self.issuing_authority,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.issuing_country,
+            &mut self.passport_type,
+            &mut self.passport_number,
+            &mut self.national_identification_number,
+            &mut self.nationality,
+            &mut self.full_name,
+            &mut self.birth_date,
+            &mut self.birth_place,
+            &mut self.sex,
+            &mut self.issue_date,
+            &mut self.expiry_date,
+            &mut self.issuing_authority,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[cfg(feature = "non-standard-credentials")]
+impl BirthCertificateCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.registration_number,
+            &self.full_name,
+            &self.birth_date,
+            &self.birth_place,
+            &self.first_parent_name,
+            &self.second_parent_name,
+            &self.issuing_authority,
+            &self.issue_date,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.registration_number,
+            &mut self.full_name,
+            &mut self.birth_date,
+            &mut self.birth_place,
+            &mut self.first_parent_name,
+            &mut self.second_parent_name,
+            &mut self.issuing_authority,
+            &mut self.issue_date,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[cfg(feature = "non-standard-credentials")]
+impl InsurancePolicyCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.insurer,
+            &self.policy_number,
+            &self.policy_type,
+            &self.coverage_amount,
+            &self.deductible,
+            &self.start_date,
+            &self.expiry_date,
+            &self.agent_name,
+            &self.agent_phone,
+            &self.agent_email,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.insurer,
+            &mut self.policy_number,
+            &mut self.policy_type,
+            &mut self.coverage_amount,
+            &mut self.deductible,
+            &mut self.start_date,
+            &mut self.expiry_date,
+            &mut self.agent_name,
+            &mut self.agent_phone,
+            &mut self.agent_email,
+        ]
+        .into_iter()
+        .flatten()
+    }
 }
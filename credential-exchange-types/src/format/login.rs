@@ -6,24 +6,44 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     b64url::B32,
-    format::{EditableField, Fido2Extensions},
-    B64Url, Uri,
+    format::{
+        redact::{Masked, MaskedField, MaskedMap},
+        EditableField, Fido2Extensions,
+    },
+    B64Url, RpId, Uri,
 };
 
 /// A [BasicAuthCredential] contains a username/password login credential.
 /// Can either represent a [Basic access authentication](https://www.rfc-editor.org/rfc/rfc7617)
 /// or a form on a web page.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct BasicAuthCredential {
     /// The URLs that this credential is associated with.
     pub urls: Vec<Uri>,
     /// The username associated with the credential.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub username: Option<EditableField>,
     /// The password associated with the credential.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub password: Option<EditableField>,
+    /// The unknown members of the [BasicAuthCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for BasicAuthCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasicAuthCredential")
+            .field("urls", &self.urls)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(MaskedField))
+            .field("extra", &MaskedMap(&self.extra))
+            .finish()
+    }
 }
 
 /// Passkey
@@ -32,7 +52,8 @@ pub struct BasicAuthCredential {
 /// exporter SHOULD inform the user that such passkeys are excluded from the export. Importers MUST
 /// set a zero value for the imported passkey signature counters and MUST NOT increment them after
 /// the fact.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct PasskeyCredential {
     /// This member contains a [WebAuthn](https://www.w3.org/TR/webauthn-3)
@@ -47,7 +68,7 @@ pub struct PasskeyCredential {
     /// which the passkey instance is tied to. The value MUST be equal to the
     /// [RP ID](https://www.w3.org/TR/webauthn-3/#rp-id) that was defined by the authenticator
     /// during credential [registration](https://www.w3.org/TR/webauthn-3/#registration).
-    pub rp_id: String,
+    pub rp_id: RpId,
     /// This member contains a [human-palatable](https://www.w3.org/TR/webauthn-3/#human-palatability)
     /// identifier for the [user account](https://www.w3.org/TR/webauthn-3/#user-account) to which
     /// the passkey instance is tied to. The value SHOULD be equal to the value in
@@ -90,15 +111,36 @@ pub struct PasskeyCredential {
     pub key: B64Url,
     /// This OPTIONAL member denotes the WebAuthn or CTAP2 extensions that are associated to this
     /// passkey instance.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub fido2_extensions: Option<Fido2Extensions>,
+    /// The unknown members of the [PasskeyCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for PasskeyCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasskeyCredential")
+            .field("credential_id", &self.credential_id)
+            .field("rp_id", &self.rp_id)
+            .field("user_name", &self.user_name)
+            .field("user_display_name", &self.user_display_name)
+            .field("user_handle", &self.user_handle)
+            .field("key", &Masked)
+            .field("fido2_extensions", &self.fido2_extensions)
+            .field("extra", &MaskedMap(&self.extra))
+            .finish()
+    }
 }
 
 /// Note: Enrollment in TOTP credentials historically has been quite non-standardized but typically
 /// authenticator and RP implementations have more or less aligned with the early Google
 /// Authenticator implementation spelled out at <https://github.com/google/google-authenticator/wiki/Key-Uri-Format>.
 /// This specification was designed with that in mind.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct TotpCredential {
     /// The [shared secret](https://www.rfc-editor.org/rfc/rfc4226#section-5) used to generate the
@@ -123,15 +165,58 @@ pub struct TotpCredential {
     ///
     /// Note: While this member is optional, it is strongly recommended to be included if
     /// available.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub issuer: Option<String>,
+    /// The unknown members of the [TotpCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for TotpCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TotpCredential")
+            .field("secret", &Masked)
+            .field("period", &self.period)
+            .field("digits", &self.digits)
+            .field("username", &self.username)
+            .field("algorithm", &self.algorithm)
+            .field("issuer", &self.issuer)
+            .field("extra", &MaskedMap(&self.extra))
+            .finish()
+    }
+}
+
+impl TotpCredential {
+    /// The period, in seconds, recommended by the specification.
+    pub const DEFAULT_PERIOD: u8 = 30;
+    /// The number of digits recommended by the specification.
+    pub const DEFAULT_DIGITS: u8 = 6;
+
+    /// Create a new [TotpCredential] for the provided shared secret using the recommended
+    /// [period][Self::DEFAULT_PERIOD], [digits][Self::DEFAULT_DIGITS] and
+    /// [algorithm][OTPHashAlgorithm::Sha1]. The username is left empty and no issuer is set.
+    pub fn new(secret: B32) -> Self {
+        Self {
+            secret,
+            period: Self::DEFAULT_PERIOD,
+            digits: Self::DEFAULT_DIGITS,
+            username: String::new(),
+            algorithm: OTPHashAlgorithm::default(),
+            issuer: None,
+            extra: Default::default(),
+        }
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "lowercase")]
 pub enum OTPHashAlgorithm {
     /// This algorithm denotes that [SHA1](https://www.rfc-editor.org/rfc/rfc3174) MUST be used to
-    /// generate the OTP hash.
+    /// generate the OTP hash. This is the default algorithm.
+    #[default]
     Sha1,
     /// This algorithm denotes that [SHA256](https://www.rfc-editor.org/rfc/rfc6234) MUST be used
     /// to generate the OTP hash.
@@ -139,35 +224,190 @@ pub enum OTPHashAlgorithm {
     /// This algorithm denotes that [SHA512](https://www.rfc-editor.org/rfc/rfc6234) MUST be used
     /// to generate the OTP hash.
     Sha512,
+    #[cfg_attr(feature = "arbitrary", arbitrary(skip))]
     #[serde(untagged)]
     Unknown(String),
 }
 
 /// An [SshKeyCredential] represents an SSH (Secure Shell) key pair.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct SshKeyCredential {
     /// The type of SSH key algorithm used. Common values include "ssh-rsa", "ssh-ed25519", or
     /// "ecdsa-sha2-nistp256". This MUST be a string value representing a valid SSH public key
     /// algorithm as defined in IANA SSH Protocol Parameters.
-    key_type: String,
+    pub(crate) key_type: String,
     /// The private part of the SSH key pair. This MUST be a PKCS#8 ASN.1 DER formatted byte string
     /// which is then Base64url encoded.
     private_key: B64Url,
     /// This OPTIONAL member contains a user-defined string to identify or describe the key.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     key_comment: Option<String>,
     /// This OPTIONAL member indicates when the key was created. When present, its internal
     /// fieldType SHOULD be of type date.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     creation_date: Option<EditableField>,
     /// This OPTIONAL member indicates when the key will expire, if applicable. When present, its
     /// internal fieldType SHOULD be of type date.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     expiration_date: Option<EditableField>,
     /// This OPTIONAL member indicates where the key was originally generated. E.g.,
     /// `https://github.com/settings/ssh/new` for GitHub. When present, its internal fieldType
     /// SHOULD be of type string.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     key_generation_source: Option<EditableField>,
+    /// The unknown members of the [SshKeyCredential], see
+    /// [Header::extra][super::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for SshKeyCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SshKeyCredential")
+            .field("key_type", &self.key_type)
+            .field("private_key", &Masked)
+            .field("key_comment", &self.key_comment)
+            .field("creation_date", &self.creation_date)
+            .field("expiration_date", &self.expiration_date)
+            .field("key_generation_source", &self.key_generation_source)
+            .field("extra", &MaskedMap(&self.extra))
+            .finish()
+    }
+}
+
+impl BasicAuthCredential {
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [&self.username, &self.password].into_iter().flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [&mut self.username, &mut self.password]
+            .into_iter()
+            .flatten()
+    }
+}
+
+impl SshKeyCredential {
+    /// Create a new [SshKeyCredential] for the PKCS#8 DER encoded `private_key` of the algorithm
+    /// `key_type`, e.g. `ssh-ed25519`, without any of the optional members.
+    pub fn new(key_type: impl Into<String>, private_key: B64Url) -> Self {
+        Self {
+            key_type: key_type.into(),
+            private_key,
+            key_comment: None,
+            creation_date: None,
+            expiration_date: None,
+            key_generation_source: None,
+            extra: Default::default(),
+        }
+    }
+
+    /// Start building an [SshKeyCredential], see [SshKeyCredential::new].
+    pub fn builder(key_type: impl Into<String>, private_key: B64Url) -> SshKeyCredentialBuilder {
+        SshKeyCredentialBuilder {
+            credential: Self::new(key_type, private_key),
+        }
+    }
+
+    /// The SSH public key algorithm of the key, e.g. `ssh-ed25519`.
+    pub fn key_type(&self) -> &str {
+        &self.key_type
+    }
+
+    /// The PKCS#8 DER encoded private key.
+    pub fn private_key(&self) -> &B64Url {
+        &self.private_key
+    }
+
+    /// The user-defined string identifying or describing the key.
+    pub fn key_comment(&self) -> Option<&str> {
+        self.key_comment.as_deref()
+    }
+
+    /// The date on which the key was created.
+    pub fn creation_date(&self) -> Option<&EditableField> {
+        self.creation_date.as_ref()
+    }
+
+    /// The date on which the key expires.
+    pub fn expiration_date(&self) -> Option<&EditableField> {
+        self.expiration_date.as_ref()
+    }
+
+    /// Where the key was originally generated, e.g. `https://github.com/settings/ssh/new`.
+    pub fn key_generation_source(&self) -> Option<&EditableField> {
+        self.key_generation_source.as_ref()
+    }
+
+    /// The private key of the credential, mutably.
+    pub(crate) fn private_key_mut(&mut self) -> &mut B64Url {
+        &mut self.private_key
+    }
+
+    /// The [EditableField] members of the credential which are present.
+    pub(crate) fn editable_fields(&self) -> impl Iterator<Item = &EditableField> {
+        [
+            &self.creation_date,
+            &self.expiration_date,
+            &self.key_generation_source,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The [EditableField] members of the credential which are present, mutably.
+    pub(crate) fn editable_fields_mut(&mut self) -> impl Iterator<Item = &mut EditableField> {
+        [
+            &mut self.creation_date,
+            &mut self.expiration_date,
+            &mut self.key_generation_source,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// Builder for [SshKeyCredential], created by [SshKeyCredential::builder].
+#[derive(Clone, Debug)]
+pub struct SshKeyCredentialBuilder {
+    credential: SshKeyCredential,
+}
+
+impl SshKeyCredentialBuilder {
+    /// Set the user-defined string identifying or describing the key.
+    pub fn key_comment(mut self, key_comment: impl Into<String>) -> Self {
+        self.credential.key_comment = Some(key_comment.into());
+        self
+    }
+
+    /// Set the date on which the key was created, which SHOULD be a
+    /// [date][super::FieldType::Date] field.
+    pub fn creation_date(mut self, creation_date: EditableField) -> Self {
+        self.credential.creation_date = Some(creation_date);
+        self
+    }
+
+    /// Set the date on which the key expires, which SHOULD be a [date][super::FieldType::Date]
+    /// field.
+    pub fn expiration_date(mut self, expiration_date: EditableField) -> Self {
+        self.credential.expiration_date = Some(expiration_date);
+        self
+    }
+
+    /// Set where the key was originally generated, which SHOULD be a
+    /// [string][super::FieldType::String] field.
+    pub fn key_generation_source(mut self, key_generation_source: EditableField) -> Self {
+        self.credential.key_generation_source = Some(key_generation_source);
+        self
+    }
+
+    /// Create the [SshKeyCredential].
+    pub fn build(self) -> SshKeyCredential {
+        self.credential
+    }
 }
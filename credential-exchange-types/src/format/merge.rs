@@ -0,0 +1,542 @@
+//! # Merging Accounts
+//!
+//! Combines the [Items][super::Item] and [Collections][super::Collection] of several [Account]s
+//! into one, for users consolidating exports from multiple sources into a single vault.
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use super::{
+    hash::ContentHash, visit_collections_mut, Account, Collection, Credential, LinkedItem,
+};
+use crate::{trace, B64Url};
+
+/// Defines how [Account::merge] combines two accounts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MergePolicy {
+    /// How to handle an [Item][super::Item] or [Collection][super::Collection] of the merged
+    /// account which has the same identifier as one in the receiving account.
+    pub id_collision: IdCollision,
+    /// When set, credentials of the merged account with the same
+    /// [content hash][Credential::content_hash] as a credential already present are dropped. Items
+    /// left without any credential are dropped as well.
+    pub deduplicate: bool,
+}
+
+/// The strategy to use when both accounts of a [merge][Account::merge] contain an entity with the
+/// same identifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdCollision {
+    /// Assign a new random identifier to the entity of the merged account, and update every
+    /// [LinkedItem] referencing it.
+    #[default]
+    Reassign,
+    /// Keep the entity of the receiving account and discard the one of the merged account.
+    KeepExisting,
+    /// Replace the entity of the receiving account with the one of the merged account.
+    Replace,
+}
+
+impl<E> Account<E> {
+    /// Merge the items and collections of `other` into this [Account], according to `policy`.
+    ///
+    /// The metadata of this account is kept, missing optional members are taken from `other`, and
+    /// the extensions of both accounts are concatenated. Every [LinkedItem] which referred to
+    /// `other` is rewritten to refer to the merged account, and links to items dropped by
+    /// [deduplication][MergePolicy::deduplicate] are redirected to the item holding the same
+    /// credential.
+    ///
+    /// When a [Collection] is discarded because of an [identifier collision][IdCollision], its
+    /// sub-collections are moved to the collection which is kept.
+    pub fn merge(mut self, mut other: Account<E>, policy: MergePolicy) -> Self {
+        trace::debug_span!(
+            "merge",
+            items = self.items.len(),
+            other_items = other.items.len()
+        );
+        let remapped = merge_items(&mut self, &mut other, policy.id_collision);
+        merge_collections(&mut self, &mut other, policy.id_collision);
+
+        // Links without an account in `other` point to its own items, which are now local to
+        // `self`. Links in `self` towards `other` are local from now on as well, unless both
+        // accounts have the same identifier, in which case they point to the items of `self`.
+        let other_id = other.id.clone();
+        other.for_each_link_mut(|link| {
+            if link.account.is_none() || link.account.as_ref() == Some(&other_id) {
+                relink(link, &remapped);
+            }
+        });
+        if self.id != other_id {
+            self.for_each_link_mut(|link| {
+                if link.account.as_ref() == Some(&other_id) {
+                    relink(link, &remapped);
+                }
+            });
+        }
+
+        if policy.deduplicate {
+            let dropped = deduplicate(&self, &mut other);
+            redirect_links(&mut self, &dropped);
+            redirect_links(&mut other, &dropped);
+        }
+
+        trace::debug!(
+            reassigned = remapped.len(),
+            merged_items = other.items.len(),
+            "merged accounts"
+        );
+        self.full_name = self.full_name.or(other.full_name);
+        self.icon = self.icon.or(other.icon);
+        self.items.append(&mut other.items);
+        self.collections.append(&mut other.collections);
+        self.extensions = match (self.extensions, other.extensions) {
+            (Some(mut extensions), Some(mut others)) => {
+                extensions.append(&mut others);
+                Some(extensions)
+            }
+            (extensions, others) => extensions.or(others),
+        };
+        self
+    }
+}
+
+/// Resolve item identifier collisions between both accounts, returning the identifiers of `other`
+/// which were reassigned.
+fn merge_items<E>(
+    account: &mut Account<E>,
+    other: &mut Account<E>,
+    strategy: IdCollision,
+) -> HashMap<B64Url, B64Url> {
+    let existing: HashSet<B64Url> = account.items.iter().map(|item| item.id.clone()).collect();
+    let mut remapped = HashMap::new();
+
+    match strategy {
+        IdCollision::Reassign => {
+            let mut taken = existing.clone();
+            taken.extend(other.items.iter().map(|item| item.id.clone()));
+            for item in other.items.iter_mut() {
+                if existing.contains(&item.id) {
+                    let id = unique_id(&mut taken);
+                    remapped.insert(std::mem::replace(&mut item.id, id.clone()), id);
+                }
+            }
+        }
+        IdCollision::KeepExisting => other.items.retain(|item| !existing.contains(&item.id)),
+        IdCollision::Replace => {
+            let replaced: HashSet<&B64Url> = other.items.iter().map(|item| &item.id).collect();
+            account.items.retain(|item| !replaced.contains(&item.id));
+        }
+    }
+
+    remapped
+}
+
+/// Resolve collection identifier collisions, at any depth, between both accounts.
+fn merge_collections<E>(account: &mut Account<E>, other: &mut Account<E>, strategy: IdCollision) {
+    let existing: HashSet<B64Url> = account
+        .iter_all_collections()
+        .map(|(_, collection)| collection.id.clone())
+        .collect();
+
+    match strategy {
+        IdCollision::Reassign => {
+            let mut taken = existing.clone();
+            taken.extend(
+                other
+                    .iter_all_collections()
+                    .map(|(_, collection)| collection.id.clone()),
+            );
+            visit_collections_mut(&mut other.collections, |collections| {
+                for collection in collections.iter_mut() {
+                    if existing.contains(&collection.id) {
+                        collection.id = unique_id(&mut taken);
+                    }
+                }
+            });
+        }
+        IdCollision::KeepExisting => {
+            let orphans = discard_collections(&mut other.collections, &existing);
+            adopt_collections(&mut account.collections, orphans);
+        }
+        IdCollision::Replace => {
+            let replaced: HashSet<B64Url> = other
+                .iter_all_collections()
+                .map(|(_, collection)| collection.id.clone())
+                .collect();
+            let orphans = discard_collections(&mut account.collections, &replaced);
+            adopt_collections(&mut other.collections, orphans);
+        }
+    }
+}
+
+/// Remove the collections whose identifier is part of `discarded` from the tree, and return the
+/// sub-collections they contained which aren't discarded themselves, by identifier of their removed
+/// parent.
+fn discard_collections<E>(
+    collections: &mut Vec<Collection<E>>,
+    discarded: &HashSet<B64Url>,
+) -> HashMap<B64Url, Vec<Collection<E>>> {
+    let mut orphans: HashMap<B64Url, Vec<Collection<E>>> = HashMap::new();
+    let mut pending = Vec::new();
+    let visit = |collections: &mut Vec<Collection<E>>, pending: &mut Vec<_>| {
+        visit_collections_mut(collections, |collections| {
+            let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(collections)
+                .into_iter()
+                .partition(|collection| discarded.contains(&collection.id));
+            *collections = kept;
+            pending.extend(
+                removed
+                    .into_iter()
+                    .filter_map(|collection| Some((collection.id, collection.sub_collections?))),
+            );
+        });
+    };
+
+    visit(collections, &mut pending);
+    while let Some((parent, mut children)) = pending.pop() {
+        visit(&mut children, &mut pending);
+        orphans.entry(parent).or_default().append(&mut children);
+    }
+    orphans
+}
+
+/// Move the `orphans` to the [sub-collections][Collection::sub_collections] of the collection of
+/// the tree with the identifier of their removed parent.
+fn adopt_collections<E>(
+    collections: &mut Vec<Collection<E>>,
+    mut orphans: HashMap<B64Url, Vec<Collection<E>>>,
+) {
+    visit_collections_mut(collections, |collections| {
+        for collection in collections.iter_mut() {
+            if let Some(mut children) = orphans.remove(&collection.id) {
+                collection
+                    .sub_collections
+                    .get_or_insert_with(Vec::new)
+                    .append(&mut children);
+            }
+        }
+    });
+}
+
+/// Drop the credentials of `other` which already exist in `account` or earlier in `other`, and the
+/// items left without any credential. Returns the identifiers of the dropped items, each mapped to
+/// the item holding the duplicate of its first credential.
+fn deduplicate<E>(account: &Account<E>, other: &mut Account<E>) -> HashMap<B64Url, B64Url> {
+    let mut seen: HashMap<ContentHash, B64Url> = HashMap::new();
+    for item in &account.items {
+        for credential in &item.credentials {
+            seen.entry(credential.content_hash())
+                .or_insert_with(|| item.id.clone());
+        }
+    }
+
+    let mut dropped = HashMap::new();
+    for item in other.items.iter_mut() {
+        let Some(first) = item.credentials.first().map(Credential::content_hash) else {
+            continue;
+        };
+        let duplicate_of = seen.get(&first).cloned();
+        item.credentials
+            .retain(|credential| match seen.entry(credential.content_hash()) {
+                Entry::Occupied(_) => false,
+                Entry::Vacant(entry) => {
+                    entry.insert(item.id.clone());
+                    true
+                }
+            });
+        if let (true, Some(duplicate_of)) = (item.credentials.is_empty(), duplicate_of) {
+            dropped.insert(item.id.clone(), duplicate_of);
+        }
+    }
+
+    other.items.retain(|item| !dropped.contains_key(&item.id));
+    dropped
+}
+
+/// Point the local links of `account` to the `dropped` items at the items replacing them. A link
+/// is removed instead when its collection already contains the replacing item.
+fn redirect_links<E>(account: &mut Account<E>, dropped: &HashMap<B64Url, B64Url>) {
+    if dropped.is_empty() {
+        return;
+    }
+    visit_collections_mut(&mut account.collections, |collections| {
+        for collection in collections {
+            let mut linked: HashSet<B64Url> = collection
+                .items
+                .iter()
+                .filter(|link| link.account.is_none())
+                .map(|link| link.item.clone())
+                .collect();
+            collection.items.retain_mut(|link| {
+                let Some(id) = dropped.get(&link.item).filter(|_| link.account.is_none()) else {
+                    return true;
+                };
+                link.item = id.clone();
+                linked.insert(id.clone())
+            });
+        }
+    });
+    for item in account.items.iter_mut() {
+        for credential in item.credentials.iter_mut() {
+            if let Credential::ItemReference(reference) = credential {
+                if reference.reference.account.is_none() {
+                    if let Some(id) = dropped.get(&reference.reference.item) {
+                        reference.reference.item = id.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Point a link at the merged account, following any reassigned identifier.
+fn relink(link: &mut LinkedItem, remapped: &HashMap<B64Url, B64Url>) {
+    link.account = None;
+    if let Some(id) = remapped.get(&link.item) {
+        link.item = id.clone();
+    }
+}
+
+/// Generate an identifier which is not part of `taken`, and reserve it.
+pub(super) fn unique_id(taken: &mut HashSet<B64Url>) -> B64Url {
+    loop {
+        let id = B64Url::generate_id();
+        if taken.insert(id.clone()) {
+            return id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::format::fixtures::{self, account, basic_auth, collection, id, link, link_to};
+
+    /// An item titled after its id, whose credential only differs by its password.
+    fn login(item: u8, password: &str) -> Value {
+        fixtures::item(
+            item,
+            &format!("Item {item}"),
+            json!([basic_auth("jane.doe", password)]),
+        )
+    }
+
+    fn policy(id_collision: IdCollision) -> MergePolicy {
+        MergePolicy {
+            id_collision,
+            deduplicate: false,
+        }
+    }
+
+    fn titles(account: &Account) -> Vec<&str> {
+        account
+            .items
+            .iter()
+            .map(|item| item.title.as_str())
+            .collect()
+    }
+
+    fn links(collection: &Collection) -> Vec<(B64Url, Option<B64Url>)> {
+        collection
+            .items
+            .iter()
+            .map(|link| (link.item.clone(), link.account.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn reassign_keeps_both_items_and_follows_links() {
+        let merged = account(1, json!([login(1, "a")]), json!([])).merge(
+            account(
+                2,
+                json!([login(1, "b")]),
+                json!([collection(10, json!([link(1)]), json!([]))]),
+            ),
+            policy(IdCollision::Reassign),
+        );
+
+        assert_eq!(titles(&merged), ["Item 1", "Item 1"]);
+        let reassigned = &merged.items[1].id;
+        assert_ne!(reassigned, &id(1));
+        assert_eq!(links(&merged.collections[0]), [(reassigned.clone(), None)]);
+    }
+
+    #[test]
+    fn keep_existing_discards_colliding_items() {
+        let merged = account(1, json!([login(1, "a")]), json!([])).merge(
+            account(2, json!([login(1, "b"), login(2, "c")]), json!([])),
+            policy(IdCollision::KeepExisting),
+        );
+
+        let ids: Vec<_> = merged.items.iter().map(|item| item.id.clone()).collect();
+        assert_eq!(ids, [id(1), id(2)]);
+        assert_eq!(merged.credentials().count(), 2);
+        assert_eq!(
+            merged.items[0].credentials,
+            account(1, json!([login(1, "a")]), json!([])).items[0].credentials
+        );
+    }
+
+    #[test]
+    fn replace_discards_existing_items() {
+        let merged = account(1, json!([login(1, "a"), login(2, "b")]), json!([])).merge(
+            account(2, json!([login(1, "c")]), json!([])),
+            policy(IdCollision::Replace),
+        );
+
+        let ids: Vec<_> = merged.items.iter().map(|item| item.id.clone()).collect();
+        assert_eq!(ids, [id(2), id(1)]);
+        assert_eq!(
+            merged.items[1].credentials,
+            account(2, json!([login(1, "c")]), json!([])).items[0].credentials
+        );
+    }
+
+    #[test]
+    fn links_towards_the_merged_account_become_local() {
+        let merged = account(
+            1,
+            json!([login(1, "a")]),
+            json!([collection(
+                10,
+                json!([link_to(1, 2), link_to(2, 2)]),
+                json!([])
+            )]),
+        )
+        .merge(
+            account(2, json!([login(1, "b"), login(2, "c")]), json!([])),
+            policy(IdCollision::Reassign),
+        );
+
+        let reassigned = merged.items[1].id.clone();
+        assert_eq!(
+            links(&merged.collections[0]),
+            [(reassigned, None), (id(2), None)]
+        );
+    }
+
+    #[test]
+    fn links_to_own_items_are_kept_when_both_accounts_have_the_same_id() {
+        let merged = account(
+            1,
+            json!([login(1, "a")]),
+            json!([collection(10, json!([link_to(1, 1)]), json!([]))]),
+        )
+        .merge(
+            account(1, json!([login(1, "b")]), json!([])),
+            policy(IdCollision::Reassign),
+        );
+
+        assert_eq!(links(&merged.collections[0]), [(id(1), Some(id(1)))]);
+    }
+
+    #[test]
+    fn deduplicate_redirects_links_to_dropped_items() {
+        let merged = account(
+            1,
+            json!([login(1, "a")]),
+            json!([collection(10, json!([link(1), link_to(2, 2)]), json!([]))]),
+        )
+        .merge(
+            account(
+                2,
+                json!([login(2, "a"), login(3, "b")]),
+                json!([collection(20, json!([link(2), link(3)]), json!([]))]),
+            ),
+            MergePolicy {
+                id_collision: IdCollision::Reassign,
+                deduplicate: true,
+            },
+        );
+
+        let ids: Vec<_> = merged.items.iter().map(|item| item.id.clone()).collect();
+        assert_eq!(ids, [id(1), id(3)]);
+        // The collection already linking the kept item doesn't link it twice.
+        assert_eq!(links(&merged.collections[0]), [(id(1), None)]);
+        assert_eq!(
+            links(&merged.collections[1]),
+            [(id(1), None), (id(3), None)]
+        );
+    }
+
+    #[test]
+    fn reassign_nested_collections() {
+        let merged = account(1, json!([]), json!([collection(10, json!([]), json!([]))])).merge(
+            account(
+                2,
+                json!([]),
+                json!([collection(
+                    20,
+                    json!([]),
+                    json!([collection(10, json!([]), json!([]))])
+                )]),
+            ),
+            policy(IdCollision::Reassign),
+        );
+
+        let ids: Vec<_> = merged
+            .iter_all_collections()
+            .map(|(path, collection)| (path.len(), collection.id.clone()))
+            .collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], (0, id(10)));
+        assert_eq!(ids[1], (0, id(20)));
+        assert_eq!(ids[2].0, 1);
+        assert_ne!(ids[2].1, id(10));
+    }
+
+    #[test]
+    fn keep_existing_keeps_the_sub_collections_of_discarded_collections() {
+        let merged = account(1, json!([]), json!([collection(10, json!([]), json!([]))])).merge(
+            account(
+                2,
+                json!([]),
+                json!([collection(
+                    10,
+                    json!([]),
+                    json!([collection(
+                        11,
+                        json!([]),
+                        json!([collection(
+                            10,
+                            json!([]),
+                            json!([collection(12, json!([]), json!([]))])
+                        )])
+                    )])
+                )]),
+            ),
+            policy(IdCollision::KeepExisting),
+        );
+
+        let ids: Vec<_> = merged
+            .iter_all_collections()
+            .map(|(path, collection)| (path.len(), collection.id.clone()))
+            .collect();
+        assert_eq!(ids, [(0, id(10)), (1, id(11)), (1, id(12))]);
+    }
+
+    #[test]
+    fn replace_keeps_the_sub_collections_of_discarded_collections() {
+        let merged = account(
+            1,
+            json!([]),
+            json!([collection(
+                10,
+                json!([link(1)]),
+                json!([collection(11, json!([]), json!([]))])
+            )]),
+        )
+        .merge(
+            account(2, json!([]), json!([collection(10, json!([]), json!([]))])),
+            policy(IdCollision::Replace),
+        );
+
+        let ids: Vec<_> = merged
+            .iter_all_collections()
+            .map(|(path, collection)| (path.len(), collection.id.clone()))
+            .collect();
+        assert_eq!(ids, [(0, id(10)), (1, id(11))]);
+        assert!(merged.collections[0].items.is_empty());
+    }
+}
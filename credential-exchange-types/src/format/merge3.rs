@@ -0,0 +1,397 @@
+//! # Three-Way Merging
+//!
+//! Reconciles two versions of an [Account] which were both edited since a common base version,
+//! for providers using the format to synchronize vaults. Changes made on only one side are
+//! applied, and changes made on both sides are merged member by member, so that concurrent edits
+//! of different fields of the same [Item] don't overwrite each other.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use super::{Account, Item};
+use crate::{trace, B64Url};
+
+/// The members of an item which are resolved without being compared: the latest modification date
+/// always wins.
+const ITEM_MERGED: [&str; 1] = ["modifiedAt"];
+
+/// The result of [merge3].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreeWayMerge<E = ()> {
+    /// The merged account. Conflicts are resolved in favor of `ours`.
+    pub account: Account<E>,
+    /// The conflicts found while merging, those of the account metadata first and then those of
+    /// the items in the order of the merged items.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A change made on both sides of a [merge3] which could not be reconciled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// Both sides changed the same member of the account metadata differently, and the value of
+    /// `ours` was kept.
+    Account {
+        /// The [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) of the member.
+        path: String,
+    },
+    /// Both sides changed the same member of an [Item] differently, or added two different items
+    /// with the same id, and the value of `ours` was kept.
+    Item {
+        /// The [id][Item::id] of the item.
+        item: B64Url,
+        /// The [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) of the member within the
+        /// item, e.g. `/credentials/0/password/value`.
+        path: String,
+    },
+    /// One side modified an [Item] which the other side removed. The modified item was kept.
+    ModifiedAndRemoved {
+        /// The [id][Item::id] of the item.
+        item: B64Url,
+    },
+    /// Both sides changed the [Collections][super::Collection] of the account, and those of `ours`
+    /// were kept.
+    Collections,
+}
+
+/// Merge the changes made to `base` in `ours` and in `theirs`.
+///
+/// Items are matched by [id][Item::id] and compared by [content hash][Item::content_hash]. An item
+/// changed on both sides is merged member by member, and recursively within objects and within
+/// arrays which have the same length on all sides, e.g. the credentials of an item. Objects whose
+/// `type` differs between both sides, such as a credential replaced by one of another type, are
+/// not merged and are reported as a conflict. The latest
+/// [modification date][Item::modified_at] of both sides is kept. Collections are taken from the
+/// side which changed them, see [Account::diff].
+///
+/// Fails if the extensions of an item or of the account cannot be serialized or deserialized.
+pub fn merge3<E>(
+    base: &Account<E>,
+    ours: &Account<E>,
+    theirs: &Account<E>,
+) -> serde_json::Result<ThreeWayMerge<E>>
+where
+    E: Clone + Serialize + DeserializeOwned,
+{
+    trace::debug_span!(
+        "merge3",
+        items = base.items.len(),
+        ours_items = ours.items.len(),
+        theirs_items = theirs.items.len()
+    );
+    let mut conflicts = Vec::new();
+
+    let base_items: HashMap<&B64Url, &Item<E>> =
+        base.items.iter().map(|item| (&item.id, item)).collect();
+    let ours_ids: HashSet<&B64Url> = ours.items.iter().map(|item| &item.id).collect();
+    let theirs_items: HashMap<&B64Url, &Item<E>> =
+        theirs.items.iter().map(|item| (&item.id, item)).collect();
+
+    let mut items = Vec::new();
+    for item in &ours.items {
+        let base = base_items.get(&item.id).copied();
+        match theirs_items.get(&item.id) {
+            Some(theirs) => items.push(merge_item(base, item, theirs, &mut conflicts)?),
+            None => match base {
+                None => items.push(item.clone()),
+                Some(base) if base.content_hash()? == item.content_hash()? => {}
+                Some(_) => {
+                    conflicts.push(Conflict::ModifiedAndRemoved {
+                        item: item.id.clone(),
+                    });
+                    items.push(item.clone());
+                }
+            },
+        }
+    }
+    for item in theirs
+        .items
+        .iter()
+        .filter(|item| !ours_ids.contains(&item.id))
+    {
+        match base_items.get(&item.id) {
+            None => items.push(item.clone()),
+            Some(base) if base.content_hash()? == item.content_hash()? => {}
+            Some(_) => {
+                conflicts.push(Conflict::ModifiedAndRemoved {
+                    item: item.id.clone(),
+                });
+                items.push(item.clone());
+            }
+        }
+    }
+
+    let collections = if collections_changed(base, theirs) {
+        if collections_changed(base, ours) {
+            conflicts.push(Conflict::Collections);
+            &ours.collections
+        } else {
+            &theirs.collections
+        }
+    } else {
+        &ours.collections
+    };
+
+    let mut paths = Vec::new();
+    let metadata = merge_values(
+        Some(&metadata(base)?),
+        Some(&metadata(ours)?),
+        Some(&metadata(theirs)?),
+        &mut String::new(),
+        &mut paths,
+    );
+    conflicts.splice(
+        0..0,
+        paths.into_iter().map(|path| Conflict::Account { path }),
+    );
+    let mut account: Account<E> = serde_json::from_value(metadata.unwrap_or_default())?;
+    account.collections = collections.clone();
+    account.items = items;
+
+    trace::debug!(conflicts = conflicts.len(), "merged accounts");
+    Ok(ThreeWayMerge { account, conflicts })
+}
+
+/// Merge an item present in both `ours` and `theirs`.
+fn merge_item<E>(
+    base: Option<&Item<E>>,
+    ours: &Item<E>,
+    theirs: &Item<E>,
+    conflicts: &mut Vec<Conflict>,
+) -> serde_json::Result<Item<E>>
+where
+    E: Clone + Serialize + DeserializeOwned,
+{
+    let ours_hash = ours.content_hash()?;
+    let theirs_hash = theirs.content_hash()?;
+    let base_hash = base.map(Item::content_hash).transpose()?;
+    if ours_hash == theirs_hash || base_hash == Some(theirs_hash) {
+        return Ok(ours.clone());
+    }
+    if base_hash == Some(ours_hash) {
+        return Ok(theirs.clone());
+    }
+
+    let mut paths = Vec::new();
+    let merged = merge_values(
+        base.map(item_value).transpose()?.as_ref(),
+        Some(&item_value(ours)?),
+        Some(&item_value(theirs)?),
+        &mut String::new(),
+        &mut paths,
+    );
+    conflicts.extend(paths.into_iter().map(|path| Conflict::Item {
+        item: ours.id.clone(),
+        path,
+    }));
+
+    let mut item: Item<E> = serde_json::from_value(merged.unwrap_or_default())?;
+    item.modified_at = ours.modified_at.max(theirs.modified_at);
+    Ok(item)
+}
+
+/// Merge three versions of a JSON value, a missing value meaning that the member is absent.
+/// Conflicting members are resolved with `ours`, and their paths are appended to `conflicts`.
+fn merge_values(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    path: &mut String,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+
+    match (base, ours, theirs) {
+        (base, Some(Value::Object(ours)), Some(Value::Object(theirs)))
+            if ours.get("type") == theirs.get("type") =>
+        {
+            let base = match base {
+                Some(Value::Object(base)) => Some(base),
+                _ => None,
+            };
+            let keys: Vec<&String> = ours
+                .keys()
+                .chain(theirs.keys().filter(|key| !ours.contains_key(*key)))
+                .collect();
+            let mut merged = Map::new();
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                let value = merge_values(
+                    base.and_then(|base| base.get(key)),
+                    ours.get(key),
+                    theirs.get(key),
+                    path,
+                    conflicts,
+                );
+                path.truncate(len);
+                if let Some(value) = value {
+                    merged.insert(key.clone(), value);
+                }
+            }
+            Some(Value::Object(merged))
+        }
+        (Some(Value::Array(base)), Some(Value::Array(ours)), Some(Value::Array(theirs)))
+            if base.len() == ours.len() && ours.len() == theirs.len() =>
+        {
+            let merged = (0..ours.len())
+                .map(|index| {
+                    let len = path.len();
+                    path.push('/');
+                    path.push_str(&index.to_string());
+                    let value = merge_values(
+                        base.get(index),
+                        ours.get(index),
+                        theirs.get(index),
+                        path,
+                        conflicts,
+                    );
+                    path.truncate(len);
+                    value.unwrap_or_default()
+                })
+                .collect();
+            Some(Value::Array(merged))
+        }
+        _ => {
+            conflicts.push(path.clone());
+            ours.cloned()
+        }
+    }
+}
+
+/// The JSON representation of an item, without the members which are not merged.
+fn item_value<E: Serialize>(item: &Item<E>) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(item)?;
+    if let Value::Object(map) = &mut value {
+        for member in ITEM_MERGED {
+            map.remove(member);
+        }
+    }
+    Ok(value)
+}
+
+/// The JSON representation of the metadata of an account, with empty items and collections as
+/// they are merged on their own.
+fn metadata<E: Clone + Serialize>(account: &Account<E>) -> serde_json::Result<Value> {
+    serde_json::to_value(account.clone_metadata())
+}
+
+/// Whether `account` added, removed or modified any collection of `base`.
+fn collections_changed<E: Serialize>(base: &Account<E>, account: &Account<E>) -> bool {
+    let diff = base.diff(account);
+    !diff.added_collections.is_empty()
+        || !diff.removed_collections.is_empty()
+        || !diff.modified_collections.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::format::fixtures::{self, basic_auth};
+
+    fn account(user_name: &str, items: Value) -> Account {
+        let mut account = fixtures::account(1, items, json!([]));
+        account.user_name = user_name.to_owned();
+        account
+    }
+
+    fn login(item: u8, title: &str, username: &str) -> Value {
+        fixtures::item(item, title, json!([basic_auth(username, "password")]))
+    }
+
+    fn note(item: u8, title: &str) -> Value {
+        fixtures::item(item, title, json!([fixtures::note("content")]))
+    }
+
+    #[test]
+    fn merges_changes_of_different_members() {
+        let base = account("user", json!([login(1, "title", "username")]));
+        let ours = account("user", json!([login(1, "ours", "username")]));
+        let theirs = account("user", json!([login(1, "title", "theirs")]));
+
+        let merged = merge3(&base, &ours, &theirs).expect("the accounts merge");
+        assert_eq!(merged.conflicts, []);
+        assert_eq!(
+            merged.account.items,
+            account("user", json!([login(1, "ours", "theirs")])).items
+        );
+    }
+
+    #[test]
+    fn keeps_ours_on_conflicting_changes() {
+        let base = account("user", json!([login(1, "title", "username")]));
+        let ours = account("ours", json!([login(1, "ours", "username")]));
+        let theirs = account("theirs", json!([login(1, "theirs", "username")]));
+
+        let merged = merge3(&base, &ours, &theirs).expect("the accounts merge");
+        assert_eq!(
+            merged.conflicts,
+            [
+                Conflict::Account {
+                    path: "/userName".to_owned()
+                },
+                Conflict::Item {
+                    item: ours.items[0].id.clone(),
+                    path: "/title".to_owned()
+                },
+            ]
+        );
+        assert_eq!(merged.account, ours);
+    }
+
+    #[test]
+    fn conflicts_on_credential_type_changes() {
+        let base = account("user", json!([login(1, "title", "username")]));
+        let ours = account("user", json!([note(1, "title")]));
+        let theirs = account("user", json!([login(1, "title", "theirs")]));
+
+        let merged = merge3(&base, &ours, &theirs).expect("the accounts merge");
+        assert_eq!(
+            merged.conflicts,
+            [Conflict::Item {
+                item: ours.items[0].id.clone(),
+                path: "/credentials/0".to_owned()
+            }]
+        );
+        assert_eq!(merged.account, ours);
+    }
+
+    #[test]
+    fn applies_one_sided_additions_and_removals() {
+        let base = account(
+            "user",
+            json!([login(1, "removed", "username"), note(2, "kept")]),
+        );
+        let ours = account("user", json!([note(2, "kept"), note(3, "added")]));
+        let theirs = base.clone();
+
+        let merged = merge3(&base, &ours, &theirs).expect("the accounts merge");
+        assert_eq!(merged.conflicts, []);
+        assert_eq!(merged.account, ours);
+    }
+
+    #[test]
+    fn reports_items_modified_and_removed() {
+        let base = account("user", json!([login(1, "title", "username")]));
+        let ours = account("user", json!([]));
+        let theirs = account("user", json!([login(1, "theirs", "username")]));
+
+        let merged = merge3(&base, &ours, &theirs).expect("the accounts merge");
+        assert_eq!(
+            merged.conflicts,
+            [Conflict::ModifiedAndRemoved {
+                item: theirs.items[0].id.clone()
+            }]
+        );
+        assert_eq!(merged.account.items, theirs.items);
+    }
+}
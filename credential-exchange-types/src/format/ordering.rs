@@ -0,0 +1,70 @@
+//! # Stable Ordering
+//!
+//! The order of the lists of the format carries no meaning, so two exports of the same vault may
+//! differ only by the order of their members. [Header::normalize_order] sorts them by stable keys
+//! so that the same logical content always serializes identically, which makes exports diffable
+//! and golden-file testing feasible.
+
+use serde::Serialize;
+
+use super::{Account, Collection, Extension, Header, Item};
+
+impl<E: Serialize> Header<E> {
+    /// Sort the [Accounts][Account] by [id][Account::id] and the extensions by content, and
+    /// normalize each account with [Account::normalize_order].
+    pub fn normalize_order(&mut self) {
+        self.accounts.sort_by(|a, b| a.id.cmp(&b.id));
+        self.accounts.iter_mut().for_each(Account::normalize_order);
+        sort_extensions(&mut self.extensions);
+    }
+}
+
+impl<E: Serialize> Account<E> {
+    /// Sort the [Items][Item] and [Collections][Collection] by id and the extensions by content,
+    /// then normalize each item and collection.
+    pub fn normalize_order(&mut self) {
+        self.items.sort_by(|a, b| a.id.cmp(&b.id));
+        self.items.iter_mut().for_each(Item::normalize_order);
+        sort_collections(&mut self.collections);
+        sort_extensions(&mut self.extensions);
+    }
+}
+
+impl<E: Serialize> Collection<E> {
+    /// Sort the [LinkedItems][super::LinkedItem] by account and item id, the
+    /// [sub-collections][Collection::sub_collections] by id, recursively, and the extensions by
+    /// content.
+    pub fn normalize_order(&mut self) {
+        self.items
+            .sort_by(|a, b| (&a.account, &a.item).cmp(&(&b.account, &b.item)));
+        if let Some(collections) = self.sub_collections.as_mut() {
+            sort_collections(collections);
+        }
+        sort_extensions(&mut self.extensions);
+    }
+}
+
+impl<E: Serialize> Item<E> {
+    /// Sort the [tags][Item::tags] alphabetically and the extensions by content. The
+    /// [credentials][Item::credentials] are left untouched, as exporters may order them by
+    /// relevance.
+    pub fn normalize_order(&mut self) {
+        if let Some(tags) = self.tags.as_mut() {
+            tags.sort();
+        }
+        sort_extensions(&mut self.extensions);
+    }
+}
+
+fn sort_collections<E: Serialize>(collections: &mut [Collection<E>]) {
+    collections.sort_by(|a, b| a.id.cmp(&b.id));
+    collections.iter_mut().for_each(Collection::normalize_order);
+}
+
+/// Extensions have no identifier, so they are ordered by their serialized representation.
+fn sort_extensions<E: Serialize>(extensions: &mut Option<Vec<Extension<E>>>) {
+    if let Some(extensions) = extensions.as_mut() {
+        extensions
+            .sort_by_cached_key(|extension| serde_json::to_string(extension).unwrap_or_default());
+    }
+}
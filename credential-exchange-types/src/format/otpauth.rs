@@ -0,0 +1,318 @@
+//! # Key URIs
+//!
+//! Converts [TotpCredentials][TotpCredential] from and to the `otpauth://totp/` URIs of the
+//! [Key URI format](https://github.com/google/google-authenticator/wiki/Key-Uri-Format), which
+//! most providers and authenticator apps use to store and enroll TOTP seeds.
+
+use super::{OTPHashAlgorithm, TotpCredential};
+use crate::B32;
+
+/// The reasons why a Key URI cannot be parsed, see [TotpCredential::from_otpauth_uri].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OtpauthUriError {
+    /// The URI doesn't start with `otpauth://`.
+    Scheme,
+    /// The OTP type isn't `totp`, e.g. a counter based `hotp` URI.
+    UnsupportedType,
+    /// The label or a parameter contains an invalid percent-encoded sequence or isn't valid
+    /// UTF-8.
+    Encoding,
+    /// The `secret` parameter is missing.
+    MissingSecret,
+    /// The `secret` parameter isn't valid Base32.
+    Secret,
+    /// The `digits` parameter isn't a positive number which fits a [TotpCredential].
+    Digits,
+    /// The `period` parameter isn't a positive number which fits a [TotpCredential].
+    Period,
+}
+
+impl std::fmt::Display for OtpauthUriError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OtpauthUriError::Scheme => "URI doesn't use the otpauth scheme",
+            OtpauthUriError::UnsupportedType => "OTP type isn't totp",
+            OtpauthUriError::Encoding => "URI isn't correctly percent-encoded",
+            OtpauthUriError::MissingSecret => "URI doesn't have a secret",
+            OtpauthUriError::Secret => "Secret isn't valid Base32",
+            OtpauthUriError::Digits => "Digits isn't a valid number of digits",
+            OtpauthUriError::Period => "Period isn't a valid number of seconds",
+        })
+    }
+}
+
+impl TotpCredential {
+    /// Parse an `otpauth://totp/` Key URI.
+    ///
+    /// The label is split into the issuer and the username at its first colon. The `issuer`
+    /// parameter takes precedence over the issuer of the label. Missing `digits`, `period` and
+    /// `algorithm` parameters take their [default values][TotpCredential::new], unknown
+    /// algorithms are kept as [OTPHashAlgorithm::Unknown], and unknown parameters, such as
+    /// `image`, are ignored.
+    pub fn from_otpauth_uri(uri: &str) -> Result<Self, OtpauthUriError> {
+        let rest = uri
+            .get(..10)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("otpauth://"))
+            .map(|_| &uri[10..])
+            .ok_or(OtpauthUriError::Scheme)?;
+        let (ty, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        if !ty.eq_ignore_ascii_case("totp") {
+            return Err(OtpauthUriError::UnsupportedType);
+        }
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let label = percent_decode(label)?;
+        let (mut issuer, username) = match label.split_once(':') {
+            Some((issuer, username)) => (Some(issuer.to_owned()), username.trim_start()),
+            None => (None, label.as_str()),
+        };
+        let mut totp = TotpCredential::new(B32::from(Vec::new()));
+        totp.username = username.to_owned();
+
+        let mut secret = None;
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            let value = percent_decode(value)?;
+            match name {
+                "secret" => secret = Some(value),
+                "issuer" => issuer = Some(value),
+                "algorithm" => {
+                    totp.algorithm = match value.to_ascii_uppercase().as_str() {
+                        "SHA1" => OTPHashAlgorithm::Sha1,
+                        "SHA256" => OTPHashAlgorithm::Sha256,
+                        "SHA512" => OTPHashAlgorithm::Sha512,
+                        _ => OTPHashAlgorithm::Unknown(value),
+                    };
+                }
+                "digits" => {
+                    totp.digits = value
+                        .parse()
+                        .ok()
+                        .filter(|digits| *digits > 0)
+                        .ok_or(OtpauthUriError::Digits)?;
+                }
+                "period" => {
+                    totp.period = value
+                        .parse()
+                        .ok()
+                        .filter(|period| *period > 0)
+                        .ok_or(OtpauthUriError::Period)?;
+                }
+                _ => {}
+            }
+        }
+
+        let secret = secret.ok_or(OtpauthUriError::MissingSecret)?;
+        totp.secret = B32::try_from(secret.as_str()).map_err(|_| OtpauthUriError::Secret)?;
+        totp.issuer = issuer.filter(|issuer| !issuer.is_empty());
+        Ok(totp)
+    }
+
+    /// Build the `otpauth://totp/` Key URI of this credential, for providers which only accept
+    /// Key URIs or to enroll it in an authenticator app. The label is the issuer and the username
+    /// separated by a colon, as recommended by the Key URI format, and every parameter is
+    /// included, even those with default values.
+    pub fn to_otpauth_uri(&self) -> String {
+        let label = match &self.issuer {
+            Some(issuer) => format!(
+                "{}:{}",
+                percent_encode(issuer),
+                percent_encode(&self.username)
+            ),
+            None => percent_encode(&self.username),
+        };
+        let secret: String = self.secret.clone().into();
+        let algorithm = match &self.algorithm {
+            OTPHashAlgorithm::Sha1 => "SHA1",
+            OTPHashAlgorithm::Sha256 => "SHA256",
+            OTPHashAlgorithm::Sha512 => "SHA512",
+            OTPHashAlgorithm::Unknown(algorithm) => algorithm,
+        };
+
+        let mut uri = format!("otpauth://totp/{label}?secret={secret}");
+        if let Some(issuer) = &self.issuer {
+            uri.push_str(&format!("&issuer={}", percent_encode(issuer)));
+        }
+        uri.push_str(&format!(
+            "&algorithm={}&digits={}&period={}",
+            percent_encode(algorithm),
+            self.digits,
+            self.period
+        ));
+        uri
+    }
+}
+
+/// Percent-encode every byte of `value` except the
+/// [unreserved characters](https://www.rfc-editor.org/rfc/rfc3986#section-2.3).
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decode the percent-encoded sequences of `value`.
+fn percent_decode(value: &str) -> Result<String, OtpauthUriError> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or(OtpauthUriError::Encoding)?;
+            bytes.push(hex);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| OtpauthUriError::Encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_uri() {
+        let totp = TotpCredential::from_otpauth_uri(
+            "otpauth://totp/ACME%20Co:john.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ\
+             &issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60&image=ignored",
+        )
+        .unwrap();
+
+        assert_eq!(totp.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(totp.username, "john.doe@email.com");
+        assert_eq!(
+            String::from(totp.secret.clone()),
+            "HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ"
+        );
+        assert_eq!(totp.algorithm, OTPHashAlgorithm::Sha256);
+        assert_eq!(totp.digits, 8);
+        assert_eq!(totp.period, 60);
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let totp =
+            TotpCredential::from_otpauth_uri("otpauth://totp/alice?secret=JBSWY3DP").unwrap();
+
+        assert_eq!(totp.issuer, None);
+        assert_eq!(totp.username, "alice");
+        assert_eq!(totp.algorithm, OTPHashAlgorithm::Sha1);
+        assert_eq!(totp.digits, TotpCredential::DEFAULT_DIGITS);
+        assert_eq!(totp.period, TotpCredential::DEFAULT_PERIOD);
+    }
+
+    #[test]
+    fn issuer_parameter_takes_precedence() {
+        let totp = TotpCredential::from_otpauth_uri(
+            "otpauth://totp/Old:%20alice?secret=JBSWY3DP&issuer=New",
+        )
+        .unwrap();
+
+        assert_eq!(totp.issuer.as_deref(), Some("New"));
+        assert_eq!(totp.username, "alice");
+    }
+
+    #[test]
+    fn unknown_algorithm() {
+        let totp =
+            TotpCredential::from_otpauth_uri("otpauth://totp/alice?secret=JBSWY3DP&algorithm=MD5")
+                .unwrap();
+
+        assert_eq!(totp.algorithm, OTPHashAlgorithm::Unknown("MD5".to_owned()));
+    }
+
+    #[test]
+    fn invalid_uris() {
+        let cases = [
+            (
+                "https://totp/alice?secret=JBSWY3DP",
+                OtpauthUriError::Scheme,
+            ),
+            (
+                "otpauth://hotp/alice?secret=JBSWY3DP",
+                OtpauthUriError::UnsupportedType,
+            ),
+            (
+                "otpauth://totp/alice%2?secret=JBSWY3DP",
+                OtpauthUriError::Encoding,
+            ),
+            (
+                "otpauth://totp/alice%FF?secret=JBSWY3DP",
+                OtpauthUriError::Encoding,
+            ),
+            (
+                "otpauth://totp/alice?issuer=ACME",
+                OtpauthUriError::MissingSecret,
+            ),
+            (
+                "otpauth://totp/alice?secret=not-base32",
+                OtpauthUriError::Secret,
+            ),
+            (
+                "otpauth://totp/alice?secret=JBSWY3DP&digits=0",
+                OtpauthUriError::Digits,
+            ),
+            (
+                "otpauth://totp/alice?secret=JBSWY3DP&digits=256",
+                OtpauthUriError::Digits,
+            ),
+            (
+                "otpauth://totp/alice?secret=JBSWY3DP&period=0",
+                OtpauthUriError::Period,
+            ),
+            (
+                "otpauth://totp/alice?secret=JBSWY3DP&period=x",
+                OtpauthUriError::Period,
+            ),
+        ];
+        for (uri, error) in cases {
+            assert_eq!(
+                TotpCredential::from_otpauth_uri(uri).err(),
+                Some(error),
+                "{uri}"
+            );
+        }
+    }
+
+    #[test]
+    fn format_uri() {
+        let mut totp =
+            TotpCredential::from_otpauth_uri("otpauth://totp/alice?secret=JBSWY3DP").unwrap();
+        assert_eq!(
+            totp.to_otpauth_uri(),
+            "otpauth://totp/alice?secret=JBSWY3DP&algorithm=SHA1&digits=6&period=30"
+        );
+
+        totp.issuer = Some("ACME Co".to_owned());
+        totp.username = "alice@example.com".to_owned();
+        assert_eq!(
+            totp.to_otpauth_uri(),
+            "otpauth://totp/ACME%20Co:alice%40example.com?secret=JBSWY3DP&issuer=ACME%20Co\
+             &algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let uri = "otpauth://totp/ACME%20Co:alice%3Aadmin?secret=JBSWY3DP&issuer=ACME%20Co\
+                   &algorithm=SHA512&digits=8&period=60";
+        let totp = TotpCredential::from_otpauth_uri(uri).unwrap();
+
+        assert_eq!(totp.username, "alice:admin");
+        assert_eq!(totp.to_otpauth_uri(), uri);
+        assert!(TotpCredential::from_otpauth_uri(&totp.to_otpauth_uri()).unwrap() == totp);
+    }
+}
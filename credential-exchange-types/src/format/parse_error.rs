@@ -0,0 +1,314 @@
+//! # Parse Errors
+//!
+//! Locates the entity and member of a document which failed to deserialize, so that interop
+//! failures can be diagnosed from a single log line.
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{Account, Collection, Credential, Header, Item};
+use crate::trace;
+
+/// An error returned by [Header::from_json], with the location of the offending value.
+///
+/// Its [Display] implementation gives every detail on a single line, e.g.
+/// `/accounts/0/items/2/favorite: expected a boolean at line 1 column 348 (account: vJx2, item:
+/// f2Q1; found a string)`. As the offending value may be a secret, the messages of
+/// serde quoting it are reduced to what was expected, and the underlying error, which keeps the
+/// value, is only available through [ParseError::inner].
+#[derive(Debug)]
+pub struct ParseError {
+    pointer: String,
+    ids: Vec<(&'static str, String)>,
+    value_type: Option<&'static str>,
+    source: serde_json::Error,
+}
+
+impl ParseError {
+    /// The [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) of the offending value, or of
+    /// the innermost entity which could not be deserialized when the value cannot be narrowed
+    /// down further, e.g. for missing members. Empty for syntax errors.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// The kind and `id` of the entities on the path to the offending value, from the outermost
+    /// to the innermost, e.g. `("account", "vJx2")`. Entities without a string `id` are skipped.
+    pub fn ids(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.ids.iter().map(|(kind, id)| (*kind, id.as_str()))
+    }
+
+    /// The JSON type of the value at [ParseError::pointer], such as `a string` or `an object`.
+    pub fn value_type(&self) -> Option<&'static str> {
+        self.value_type
+    }
+
+    /// The underlying error, with the line and column of the failure in the document. Its message
+    /// may quote the offending value.
+    pub fn inner(&self) -> &serde_json::Error {
+        &self.source
+    }
+
+    /// Locate the value of `json` responsible for `error`, which was returned when deserializing
+    /// it as a [Header].
+    fn locate<E: DeserializeOwned>(json: &str, error: serde_json::Error) -> Self {
+        let mut location = Location::default();
+        if error.is_data() {
+            if let Ok(header) = serde_json::from_str::<Value>(json) {
+                location.header::<E>(&header);
+            }
+        }
+        trace::debug!(pointer = location.pointer, "failed to parse exchange");
+        Self {
+            pointer: location.pointer,
+            ids: location.ids,
+            value_type: location.value_type,
+            source: error,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.pointer.is_empty() {
+            write!(f, "{}: ", self.pointer)?;
+        }
+        write!(f, "{}", message(&self.source))?;
+        if self.source.line() != 0 {
+            write!(
+                f,
+                " at line {} column {}",
+                self.source.line(),
+                self.source.column()
+            )?;
+        }
+
+        let ids: Vec<String> = self
+            .ids()
+            .map(|(kind, id)| format!("{kind}: {id}"))
+            .collect();
+        match (ids.is_empty(), self.value_type) {
+            (true, None) => Ok(()),
+            (true, Some(value_type)) => write!(f, " (found {value_type})"),
+            (false, None) => write!(f, " ({})", ids.join(", ")),
+            (false, Some(value_type)) => write!(f, " ({}; found {value_type})", ids.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The message of `error`, without its location and without the value quoted by the `invalid
+/// type`, `invalid value`, `invalid length` and `unknown variant` errors of serde, which are
+/// reduced to what was expected. The other messages only name members or describe a format.
+fn message(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    let message = message
+        .strip_suffix(&format!(
+            " at line {} column {}",
+            error.line(),
+            error.column()
+        ))
+        .unwrap_or(&message);
+    let quotes_value = [
+        "invalid type",
+        "invalid value",
+        "invalid length",
+        "unknown variant",
+    ]
+    .iter()
+    .any(|prefix| message.starts_with(prefix));
+    if !quotes_value {
+        return message.to_owned();
+    }
+    match message.rsplit_once(", expected ") {
+        Some((_, expected)) => format!("expected {expected}"),
+        None => "invalid value".to_owned(),
+    }
+}
+
+impl<E: DeserializeOwned> Header<E> {
+    /// Deserialize an exchange from JSON. This is equivalent to [serde_json::from_str], except
+    /// that failures are reported with the location of the offending value, see [ParseError].
+    pub fn from_json(json: &str) -> Result<Self, ParseError> {
+        trace::debug_span!("parse", bytes = json.len());
+        let header: Self =
+            serde_json::from_str(json).map_err(|error| ParseError::locate::<E>(json, error))?;
+        trace::debug!(
+            accounts = header.accounts.len(),
+            items = header
+                .accounts
+                .iter()
+                .map(|account| account.items.len())
+                .sum::<usize>(),
+            "parsed exchange"
+        );
+        Ok(header)
+    }
+}
+
+/// The path to the innermost value which failed to deserialize.
+#[derive(Default)]
+struct Location {
+    pointer: String,
+    ids: Vec<(&'static str, String)>,
+    value_type: Option<&'static str>,
+}
+
+impl Location {
+    fn header<E: DeserializeOwned>(&mut self, header: &Value) {
+        match self.element::<Account<E>>(header, "accounts", "account") {
+            Some(account) => self.account::<E>(account),
+            None => self.member::<Header<E>>(header),
+        }
+    }
+
+    fn account<E: DeserializeOwned>(&mut self, account: &Value) {
+        if let Some(item) = self.element::<Item<E>>(account, "items", "item") {
+            self.item::<E>(item);
+        } else if let Some(collection) =
+            self.element::<Collection<E>>(account, "collections", "collection")
+        {
+            self.collection::<E>(collection);
+        } else {
+            self.member::<Account<E>>(account);
+        }
+    }
+
+    fn collection<E: DeserializeOwned>(&mut self, collection: &Value) {
+        match self.element::<Collection<E>>(collection, "subCollections", "collection") {
+            Some(sub_collection) => self.collection::<E>(sub_collection),
+            None => self.member::<Collection<E>>(collection),
+        }
+    }
+
+    fn item<E: DeserializeOwned>(&mut self, item: &Value) {
+        match self.element::<Credential>(item, "credentials", "credential") {
+            Some(credential) => self.member::<Credential>(credential),
+            None => self.member::<Item<E>>(item),
+        }
+    }
+
+    /// Find the first element of the `member` array of `entity` which cannot be deserialized as
+    /// `T`, and add it to the path.
+    fn element<'a, T: DeserializeOwned>(
+        &mut self,
+        entity: &'a Value,
+        member: &str,
+        kind: &'static str,
+    ) -> Option<&'a Value> {
+        let (index, element) = entity
+            .get(member)?
+            .as_array()?
+            .iter()
+            .enumerate()
+            .find(|(_, element)| T::deserialize(*element).is_err())?;
+
+        self.push(member);
+        self.push(&index.to_string());
+        if let Some(id) = element.get("id").and_then(Value::as_str) {
+            self.ids.push((kind, id.to_owned()));
+        }
+        Some(element)
+    }
+
+    /// Find the member of `entity` responsible for it not being deserializable as `T`, by
+    /// removing each member in turn until the error changes. Missing members cannot be narrowed
+    /// down further, the path then ends at the entity.
+    fn member<T: DeserializeOwned>(&mut self, entity: &Value) {
+        self.value_type = Some(json_type(entity));
+        let (Some(members), Err(error)) = (entity.as_object(), T::deserialize(entity)) else {
+            return;
+        };
+        let error = error.to_string();
+        if error.starts_with("missing field") {
+            return;
+        }
+
+        let culprit = members.iter().find(|(key, _)| {
+            let mut members = members.clone();
+            members.remove(*key);
+            match T::deserialize(&Value::Object(members)) {
+                Ok(_) => true,
+                Err(without) => without.to_string() == format!("missing field `{key}`"),
+            }
+        });
+        if let Some((key, value)) = culprit {
+            self.push(key);
+            self.value_type = Some(json_type(value));
+        }
+    }
+
+    /// Append a reference token to the pointer, escaped as defined by
+    /// [RFC6901](https://www.rfc-editor.org/rfc/rfc6901#section-3).
+    fn push(&mut self, token: &str) {
+        self.pointer.push('/');
+        self.pointer
+            .push_str(&token.replace('~', "~0").replace('/', "~1"));
+    }
+}
+
+/// The JSON type of a value, as worded by serde.
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(header: Value) -> String {
+        Header::<()>::from_json(&header.to_string())
+            .expect_err("the header is invalid")
+            .to_string()
+    }
+
+    #[test]
+    fn display_omits_offending_values() {
+        let account = json!({
+            "id": "vJx2",
+            "userName": "",
+            "email": "jane@example.com",
+            "collections": [],
+            "items": [{ "id": "f2Q1", "type": "login", "title": "", "favorite": "secret-favorite", "credentials": [] }],
+        });
+        let error = parse(json!({
+            "version": 0,
+            "exporter": "exporter.example.com",
+            "timestamp": 0,
+            "accounts": [account],
+        }));
+        assert!(!error.contains("secret-favorite"), "{error}");
+        assert!(
+            error.starts_with("/accounts/0/items/0/favorite: expected a boolean"),
+            "{error}"
+        );
+        assert!(
+            error.ends_with("(account: vJx2, item: f2Q1; found a string)"),
+            "{error}"
+        );
+
+        let error = parse(json!({
+            "version": "secret-version",
+            "exporter": "exporter.example.com",
+            "timestamp": 0,
+            "accounts": [],
+        }));
+        assert!(!error.contains("secret-version"), "{error}");
+        assert!(
+            error.starts_with("/version: expected u8 at line 1 column"),
+            "{error}"
+        );
+    }
+}
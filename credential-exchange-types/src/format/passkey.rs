@@ -1,42 +1,90 @@
 use serde::{Deserialize, Serialize};
 
-use crate::b64url::B64Url;
+use crate::{
+    b64url::B64Url,
+    format::redact::{debug_masked, Masked, MaskedMap},
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct Fido2Extensions {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub hmac_secret: Option<Fido2HmacSecret>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub cred_blob: Option<B64Url>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub large_blob: Option<Fido2LargeBlob>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub payments: Option<bool>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub supplemental_keys: Option<Fido2SupplementalKeys>,
+    /// The unknown members of the [Fido2Extensions], see
+    /// [Header::extra][crate::format::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+debug_masked!(Fido2Extensions {
+    hmac_secret,
+    large_blob,
+    payments,
+    supplemental_keys
+} masked { cred_blob });
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct Fido2HmacSecret {
     pub alias: String,
     pub hmac_secret: B64Url,
+    /// The unknown members of the [Fido2HmacSecret], see
+    /// [Header::extra][crate::format::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl std::fmt::Debug for Fido2HmacSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fido2HmacSecret")
+            .field("alias", &self.alias)
+            .field("hmac_secret", &Masked)
+            .field("extra", &MaskedMap(&self.extra))
+            .finish()
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct Fido2LargeBlob {
     pub size: u64,
     pub alg: String,
     pub data: B64Url,
+    /// The unknown members of the [Fido2LargeBlob], see
+    /// [Header::extra][crate::format::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+debug_masked!(Fido2LargeBlob { size, alg } masked { data });
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(rename_all = "camelCase")]
 pub struct Fido2SupplementalKeys {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub device: Option<bool>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "crate::serialization::is_omitted")]
     pub provider: Option<bool>,
+    /// The unknown members of the [Fido2SupplementalKeys], see
+    /// [Header::extra][crate::format::Header::extra].
+    #[serde(flatten)]
+    #[cfg_attr(feature = "arbitrary", arbitrary(default))]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
+
+debug_masked!(Fido2SupplementalKeys { device, provider });
@@ -0,0 +1,266 @@
+//! # Import Previews
+//!
+//! Describes what importing an exchange would do, given the [ImporterCapabilities] of the
+//! importing provider, without modifying anything. Providers use it to drive their pre-import
+//! confirmation screen. Exporters can also list what the importer would lose with
+//! [Header::unsupported_against].
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{Credential, CredentialType, Extension, FieldType, Header};
+use crate::B64Url;
+
+/// What an importing provider can store.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImporterCapabilities {
+    /// The types of the credentials which are imported as-is.
+    pub credential_types: HashSet<CredentialType>,
+    /// The types of the [EditableFields][super::EditableField] which are imported as-is, or
+    /// [None] if all of them are.
+    pub field_types: Option<HashSet<FieldType>>,
+    /// The names of the extensions which are imported, or [None] if all of them are.
+    pub extensions: Option<HashSet<String>>,
+    /// The maximum [decrypted size][super::FileCredential::decrypted_size] in bytes of an
+    /// imported file, or [None] if there is no limit.
+    pub max_file_size: Option<u64>,
+    /// The maximum length in characters of the value of an imported
+    /// [EditableField][super::EditableField], or [None] if there is no limit.
+    pub max_value_length: Option<usize>,
+}
+
+impl ImporterCapabilities {
+    /// The capabilities of an importer supporting the given credential types, every field type
+    /// and extension, without any size limit.
+    pub fn new(credential_types: impl IntoIterator<Item = CredentialType>) -> Self {
+        Self {
+            credential_types: credential_types.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether credentials of type `ty` are imported as-is.
+    pub fn supports(&self, ty: &CredentialType) -> bool {
+        self.credential_types.contains(ty)
+    }
+
+    /// Whether fields of type `ty` are imported as-is.
+    pub fn supports_field_type(&self, ty: &FieldType) -> bool {
+        !matches!(&self.field_types, Some(types) if !types.contains(ty))
+    }
+
+    /// Whether the extensions named `name` are imported.
+    pub fn supports_extension(&self, name: &str) -> bool {
+        !matches!(&self.extensions, Some(names) if !names.contains(name))
+    }
+}
+
+/// The content of an exchange which an importer would not import as-is, as returned by
+/// [Header::unsupported_against].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnsupportedContent {
+    /// The number of credentials of each unsupported type.
+    pub credential_types: HashMap<CredentialType, usize>,
+    /// The number of fields of each unsupported type, among the supported credentials.
+    pub field_types: HashMap<FieldType, usize>,
+    /// The number of unsupported extensions of the header, accounts, collections, items and
+    /// fields, by name. [External][Extension::External] extensions and unknown extensions
+    /// without a name are counted under an empty name.
+    pub extensions: BTreeMap<String, usize>,
+    /// The number of files larger than [ImporterCapabilities::max_file_size].
+    pub oversized_files: usize,
+    /// The number of field values longer than [ImporterCapabilities::max_value_length], among the
+    /// supported credentials.
+    pub oversized_values: usize,
+}
+
+impl UnsupportedContent {
+    /// Whether the importer would import everything as-is.
+    pub fn is_empty(&self) -> bool {
+        self.credential_types.is_empty()
+            && self.field_types.is_empty()
+            && self.extensions.is_empty()
+            && self.oversized_files == 0
+            && self.oversized_values == 0
+    }
+
+    fn credential(&mut self, credential: &Credential, capabilities: &ImporterCapabilities) {
+        let ty = credential.credential_type();
+        if !capabilities.supports(&ty) {
+            *self.credential_types.entry(ty).or_default() += 1;
+            return;
+        }
+        if let Credential::File(file) = credential {
+            if capabilities
+                .max_file_size
+                .is_some_and(|max| file.decrypted_size > max)
+            {
+                self.oversized_files += 1;
+            }
+        }
+        for field in credential.editable_fields() {
+            if !capabilities.supports_field_type(&field.field_type) {
+                *self
+                    .field_types
+                    .entry(field.field_type.clone())
+                    .or_default() += 1;
+            }
+            if capabilities
+                .max_value_length
+                .is_some_and(|max| field.value.chars().count() > max)
+            {
+                self.oversized_values += 1;
+            }
+            self.extensions(&field.extensions, capabilities);
+        }
+    }
+
+    fn extensions<E>(
+        &mut self,
+        extensions: &Option<Vec<Extension<E>>>,
+        capabilities: &ImporterCapabilities,
+    ) {
+        for extension in extensions.iter().flatten() {
+            let name = extension.name().unwrap_or_default();
+            if !capabilities.supports_extension(name) {
+                *self.extensions.entry(name.to_owned()).or_default() += 1;
+            }
+        }
+    }
+}
+
+impl<E> Header<E> {
+    /// List the content of this exchange which an importer with the given `capabilities` would
+    /// not import as-is. Fields and files are only checked for the supported credential types,
+    /// since the others are lost as a whole.
+    pub fn unsupported_against(&self, capabilities: &ImporterCapabilities) -> UnsupportedContent {
+        let mut unsupported = UnsupportedContent::default();
+        unsupported.extensions(&self.extensions, capabilities);
+        for account in &self.accounts {
+            unsupported.extensions(&account.extensions, capabilities);
+            for (_, collection) in account.iter_all_collections() {
+                unsupported.extensions(&collection.extensions, capabilities);
+            }
+            for item in &account.items {
+                unsupported.extensions(&item.extensions, capabilities);
+                for credential in &item.credentials {
+                    unsupported.credential(credential, capabilities);
+                }
+            }
+        }
+        unsupported
+    }
+}
+
+/// What happens to a credential, or to an item, on import.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ImportOutcome {
+    /// Imported as-is.
+    Imported,
+    /// Imported as a [NoteCredential][super::NoteCredential] holding a description of the
+    /// credential, because its type is not supported. For an item, some of its credentials are
+    /// degraded or skipped, but not all of them are skipped.
+    Degraded,
+    /// Not imported at all. For an item, all of its credentials are skipped.
+    Skipped,
+}
+
+/// The outcome of the import of an [Item][super::Item] and of each of its credentials, see
+/// [ImportPreview].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemPreview {
+    /// The [id][super::Account::id] of the account holding the item.
+    pub account: B64Url,
+    /// The [id][super::Item::id] of the item.
+    pub item: B64Url,
+    /// The [title][super::Item::title] of the item.
+    pub title: String,
+    /// The outcome of the item as a whole.
+    pub outcome: ImportOutcome,
+    /// The type and outcome of each credential of the item, in order.
+    pub credentials: Vec<(CredentialType, ImportOutcome)>,
+}
+
+/// What importing an exchange would do, as returned by [preview].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportPreview {
+    /// Every item of the exchange, in order.
+    pub items: Vec<ItemPreview>,
+}
+
+impl ImportPreview {
+    /// The number of items with the given outcome.
+    pub fn count(&self, outcome: ImportOutcome) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.outcome == outcome)
+            .count()
+    }
+
+    /// Whether every item would be imported as-is.
+    pub fn is_lossless(&self) -> bool {
+        self.items
+            .iter()
+            .all(|item| item.outcome == ImportOutcome::Imported)
+    }
+}
+
+/// Preview the import of `header` by an importer with the given `capabilities`.
+///
+/// Credentials of a supported type are imported. The others, including the unknown credentials,
+/// are degraded into notes if the importer supports [notes][CredentialType::Note], except for
+/// [files][CredentialType::File] and [item references][CredentialType::ItemReference], which
+/// have no meaningful textual form and are skipped. An item without any credential is imported.
+pub fn preview<E>(header: &Header<E>, capabilities: &ImporterCapabilities) -> ImportPreview {
+    let items = header
+        .accounts
+        .iter()
+        .flat_map(|account| {
+            account.items.iter().map(|item| {
+                let credentials: Vec<_> = item
+                    .credentials
+                    .iter()
+                    .map(|credential| {
+                        (
+                            credential.credential_type(),
+                            credential_outcome(credential, capabilities),
+                        )
+                    })
+                    .collect();
+                ItemPreview {
+                    account: account.id.clone(),
+                    item: item.id.clone(),
+                    title: item.title.clone(),
+                    outcome: item_outcome(&credentials),
+                    credentials,
+                }
+            })
+        })
+        .collect();
+    ImportPreview { items }
+}
+
+fn credential_outcome(
+    credential: &Credential,
+    capabilities: &ImporterCapabilities,
+) -> ImportOutcome {
+    let ty = credential.credential_type();
+    if capabilities.supports(&ty) {
+        return ImportOutcome::Imported;
+    }
+    match ty {
+        CredentialType::File | CredentialType::ItemReference => ImportOutcome::Skipped,
+        _ if capabilities.supports(&CredentialType::Note) => ImportOutcome::Degraded,
+        _ => ImportOutcome::Skipped,
+    }
+}
+
+fn item_outcome(credentials: &[(CredentialType, ImportOutcome)]) -> ImportOutcome {
+    let all = |outcome| credentials.iter().all(|(_, other)| *other == outcome);
+    if all(ImportOutcome::Imported) {
+        ImportOutcome::Imported
+    } else if all(ImportOutcome::Skipped) {
+        ImportOutcome::Skipped
+    } else {
+        ImportOutcome::Degraded
+    }
+}
@@ -0,0 +1,19 @@
+//! # QR Codes
+//!
+//! Renders individual credentials as QR codes, so that providers can let users transfer them to
+//! devices which don't support the Credential Exchange Protocol, e.g. an authenticator app.
+
+use qrcode::{types::QrError, QrCode};
+
+use super::TotpCredential;
+
+impl TotpCredential {
+    /// Render this credential as a QR code holding its
+    /// [Key URI][TotpCredential::to_otpauth_uri], which is understood by most authenticator apps.
+    /// The code can be rendered with [QrCode::render], e.g. to SVG or to text.
+    ///
+    /// This fails if the credential is too long to fit in a QR code.
+    pub fn to_qr_code(&self) -> Result<QrCode, QrError> {
+        QrCode::new(self.to_otpauth_uri())
+    }
+}
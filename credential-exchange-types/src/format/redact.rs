@@ -0,0 +1,530 @@
+//! # Redaction
+//!
+//! Replaces the secrets of an exchange with placeholders while keeping its structure, to produce
+//! shareable bug reports and test fixtures from real exports. The [Debug][fmt::Debug]
+//! implementations of the credentials and their fields mask their secrets, the content of unknown
+//! credentials and the values of their unknown members as well, so that logging a credential
+//! doesn't leak them.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use super::{
+    card::shown_digits, Account, Collection, Credential, EditableField, Extension, FieldType,
+    Header, Item, LinkedItem, PasskeyCredential,
+};
+use crate::{b64url::B32, B64Url, Email};
+
+/// The placeholder of redacted text.
+const PLACEHOLDER: &str = "REDACTED";
+
+/// Defines what [Header::redact] and [Account::redact] replace.
+///
+/// Secrets are always replaced: passwords, [concealed][FieldType::ConcealedString] fields and
+/// fields of unknown types, notes, private keys, TOTP seeds, passkey extension secrets, card
+/// verification numbers and card numbers, but for the last 4 digits of those long enough to be
+/// card numbers. As their meaning isn't known, so are the strings held by the unknown members of
+/// every entity, by unknown credentials and by unknown extensions, except for the `name` of the
+/// latter. Binary secrets are replaced by zeros of the same length.
+///
+/// Identifiers, dates, URLs, relying parties and the other members of the known extensions are
+/// always kept, as they are usually needed to reproduce an issue. [External][Extension::External]
+/// extensions are kept as they are, since their content can't be inspected.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RedactionPolicy {
+    /// When set, personal data is replaced as well: the names and email addresses of accounts,
+    /// the titles and subtitles of items and collections, item tags, user names, card holder
+    /// names, file names, field labels, organization and sharing accessor names, attachment
+    /// thumbnails and every field of the identity credentials.
+    pub personal_data: bool,
+}
+
+impl<E> Header<E> {
+    /// Replace the secrets of every [Account] of this exchange according to `policy`, see
+    /// [RedactionPolicy].
+    pub fn redact(&mut self, policy: RedactionPolicy) {
+        for account in &mut self.accounts {
+            account.redact(policy);
+        }
+        redact_extensions(&mut self.extensions, policy.personal_data);
+        redact_map(&mut self.extra);
+    }
+}
+
+impl<E> Account<E> {
+    /// Replace the secrets of this [Account] according to `policy`, see [RedactionPolicy].
+    pub fn redact(&mut self, policy: RedactionPolicy) {
+        if policy.personal_data {
+            self.user_name = PLACEHOLDER.to_owned();
+            self.email = placeholder_email();
+            if let Some(full_name) = &mut self.full_name {
+                *full_name = PLACEHOLDER.to_owned();
+            }
+        }
+        let mut stack: Vec<&mut Collection<E>> = self.collections.iter_mut().collect();
+        while let Some(collection) = stack.pop() {
+            if policy.personal_data {
+                collection.title = PLACEHOLDER.to_owned();
+                redact_option(&mut collection.subtitle);
+            }
+            collection.items.iter_mut().for_each(LinkedItem::redact);
+            redact_extensions(&mut collection.extensions, policy.personal_data);
+            redact_map(&mut collection.extra);
+            stack.extend(collection.sub_collections.iter_mut().flatten());
+        }
+        for item in &mut self.items {
+            item.redact(policy);
+        }
+        redact_extensions(&mut self.extensions, policy.personal_data);
+        redact_map(&mut self.extra);
+    }
+}
+
+impl<E> Item<E> {
+    fn redact(&mut self, policy: RedactionPolicy) {
+        if policy.personal_data {
+            self.title = PLACEHOLDER.to_owned();
+            redact_option(&mut self.subtitle);
+            for tag in self.tags.iter_mut().flatten() {
+                *tag = PLACEHOLDER.to_owned();
+            }
+        }
+        for credential in &mut self.credentials {
+            credential.redact(policy);
+        }
+        redact_extensions(&mut self.extensions, policy.personal_data);
+        redact_map(&mut self.extra);
+    }
+}
+
+impl LinkedItem {
+    fn redact(&mut self) {
+        redact_map(&mut self.extra);
+    }
+}
+
+impl Credential {
+    fn redact(&mut self, policy: RedactionPolicy) {
+        let personal = policy.personal_data;
+        match self {
+            Credential::BasicAuth(credential) => {
+                if let Some(username) = &mut credential.username {
+                    username.redact(personal);
+                }
+                if let Some(password) = &mut credential.password {
+                    password.redact(personal);
+                    password.value = PLACEHOLDER.to_owned();
+                }
+                redact_map(&mut credential.extra);
+            }
+            Credential::Passkey(credential) => {
+                credential.redact(personal);
+            }
+            Credential::CreditCard(credential) => {
+                let digits = credential
+                    .number
+                    .chars()
+                    .filter(char::is_ascii_digit)
+                    .count();
+                let mut masked = digits - shown_digits(digits);
+                credential.number = credential
+                    .number
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_digit() && masked > 0 {
+                            masked -= 1;
+                            '*'
+                        } else {
+                            c
+                        }
+                    })
+                    .collect();
+                redact_option(&mut credential.verification_number);
+                if personal {
+                    credential.full_name = PLACEHOLDER.to_owned();
+                }
+                redact_map(&mut credential.extra);
+            }
+            Credential::Note(credential) => {
+                credential.content = PLACEHOLDER.to_owned();
+                redact_map(&mut credential.extra);
+            }
+            Credential::Totp(credential) => {
+                credential.secret = B32::from(vec![0; credential.secret.as_ref().len()]);
+                if personal {
+                    credential.username = PLACEHOLDER.to_owned();
+                }
+                redact_map(&mut credential.extra);
+            }
+            Credential::DriversLicense(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            Credential::Address(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            Credential::ItemReference(credential) => {
+                credential.reference.redact();
+                redact_map(&mut credential.extra);
+            }
+            Credential::File(credential) => {
+                if personal {
+                    credential.name = PLACEHOLDER.to_owned();
+                }
+                redact_map(&mut credential.extra);
+            }
+            Credential::SshKey(credential) => {
+                let private_key = credential.private_key_mut();
+                *private_key = zeroed(private_key);
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            Credential::IdentityDocument(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            Credential::Passport(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            Credential::PersonName(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::BirthCertificate(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(credential) => {
+                redact_fields(credential.editable_fields_mut(), personal);
+                redact_map(&mut credential.extra);
+            }
+            Credential::Unknown { content, .. } => redact_map(content),
+        }
+    }
+}
+
+impl PasskeyCredential {
+    fn redact(&mut self, personal: bool) {
+        self.key = zeroed(&self.key);
+        if personal {
+            self.user_name = PLACEHOLDER.to_owned();
+            self.user_display_name = PLACEHOLDER.to_owned();
+        }
+        if let Some(extensions) = &mut self.fido2_extensions {
+            if let Some(hmac_secret) = &mut extensions.hmac_secret {
+                hmac_secret.hmac_secret = zeroed(&hmac_secret.hmac_secret);
+            }
+            if let Some(cred_blob) = &mut extensions.cred_blob {
+                *cred_blob = zeroed(cred_blob);
+            }
+            if let Some(large_blob) = &mut extensions.large_blob {
+                large_blob.data = zeroed(&large_blob.data);
+            }
+            redact_map(&mut extensions.extra);
+        }
+        redact_map(&mut self.extra);
+    }
+}
+
+impl EditableField {
+    /// Replace the value of this field if it is concealed or of an unknown type, or if `personal`
+    /// is set. The placeholder keeps the value valid for the
+    /// [field type][EditableField::field_type].
+    fn redact(&mut self, personal: bool) {
+        redact_extensions(&mut self.extensions, personal);
+        redact_map(&mut self.extra);
+        if personal {
+            redact_option(&mut self.label);
+        }
+        self.value = match &self.field_type {
+            FieldType::ConcealedString | FieldType::Unknown(_) => PLACEHOLDER.to_owned(),
+            _ if !personal => return,
+            FieldType::Email => placeholder_email().to_string(),
+            FieldType::Number => "0".to_owned(),
+            FieldType::Boolean => return,
+            FieldType::Date => "1970-01-01".to_owned(),
+            FieldType::String => PLACEHOLDER.to_owned(),
+        };
+    }
+}
+
+fn redact_fields<'a>(fields: impl Iterator<Item = &'a mut EditableField>, personal: bool) {
+    fields.for_each(|field| field.redact(personal));
+}
+
+fn redact_option(value: &mut Option<String>) {
+    if let Some(value) = value {
+        *value = PLACEHOLDER.to_owned();
+    }
+}
+
+/// Replace the unknown members of the extensions, the content of the unknown extensions but for
+/// their `name`, and the personal data of the known extensions if `personal` is set.
+fn redact_extensions<E>(extensions: &mut Option<Vec<Extension<E>>>, personal: bool) {
+    for extension in extensions.iter_mut().flatten() {
+        match extension {
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Appearance(extension) => redact_map(&mut extension.extra),
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Attachment(extension) => {
+                if personal {
+                    if let Some(thumbnail) = &mut extension.thumbnail {
+                        *thumbnail = zeroed(thumbnail);
+                    }
+                }
+                redact_map(&mut extension.extra);
+            }
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::EquivalentDomains(extension) => redact_map(&mut extension.extra),
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::ItemState(extension) => redact_map(&mut extension.extra),
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Organization(extension) => {
+                if personal {
+                    extension.organization_name = PLACEHOLDER.to_owned();
+                }
+                redact_map(&mut extension.extra);
+            }
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::PasskeyProvenance(extension) => redact_map(&mut extension.extra),
+            Extension::Shared(extension) => {
+                for accessor in &mut extension.accessors {
+                    if personal {
+                        accessor.name = PLACEHOLDER.to_owned();
+                    }
+                    redact_map(&mut accessor.extra);
+                }
+                redact_map(&mut extension.extra);
+            }
+            #[cfg(feature = "non-standard-extensions")]
+            Extension::Usage(extension) => redact_map(&mut extension.extra),
+            Extension::External(_) => {}
+            Extension::Unknown(Value::Object(content)) => content
+                .iter_mut()
+                .filter(|(member, _)| *member != "name")
+                .for_each(|(_, value)| redact_value(value)),
+            Extension::Unknown(content) => redact_value(content),
+        }
+    }
+}
+
+/// Replace every string of `map`, at any depth, keeping the members and the other values.
+fn redact_map(map: &mut Map<String, Value>) {
+    map.values_mut().for_each(redact_value);
+}
+
+/// Replace every string of `value`, at any depth, keeping the members and the other values.
+fn redact_value(value: &mut Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+            Value::String(text) => *text = PLACEHOLDER.to_owned(),
+            Value::Array(values) => stack.extend(values.iter_mut()),
+            Value::Object(map) => stack.extend(map.values_mut()),
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+}
+
+fn zeroed(bytes: &B64Url) -> B64Url {
+    B64Url::from(vec![0; bytes.as_ref().len()])
+}
+
+fn placeholder_email() -> Email {
+    Email::parse("redacted@example.com").expect("placeholder is a valid email address")
+}
+
+/// Stands in for a secret in the [Debug][fmt::Debug] output of the type holding it.
+pub(super) struct Masked;
+
+impl fmt::Debug for Masked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"***\"")
+    }
+}
+
+/// Stands in for the unknown members of a type holding secrets in its [Debug][fmt::Debug] output:
+/// the names of the members are shown, but their values are [masked][Masked].
+pub(super) struct MaskedMap<'a>(pub(super) &'a Map<String, Value>);
+
+impl fmt::Debug for MaskedMap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.keys().map(|name| (name, Masked)))
+            .finish()
+    }
+}
+
+/// Implement [Debug][fmt::Debug] for a credential type: the listed members are shown as they are,
+/// while the `masked` members and the [unknown members][MaskedMap] in `extra` are masked.
+macro_rules! debug_masked {
+    ($ty:ident { $($field:ident),* $(,)? } $(masked { $($masked:ident),* $(,)? })?) => {
+        impl std::fmt::Debug for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($ty))
+                    $(.field(stringify!($field), &self.$field))*
+                    $($(.field(stringify!($masked), &$crate::format::redact::Masked))*)?
+                    .field("extra", &$crate::format::redact::MaskedMap(&self.extra))
+                    .finish()
+            }
+        }
+    };
+}
+pub(super) use debug_masked;
+
+/// An [EditableField] shown by [Debug][fmt::Debug] with its value [masked][Masked] whatever its
+/// type, for the fields which are always secret, such as passwords.
+pub(super) struct MaskedField<'a>(pub(super) &'a EditableField);
+
+impl fmt::Debug for MaskedField<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_field(self.0, f, true)
+    }
+}
+
+/// Format `field` for [Debug][fmt::Debug], with its value [masked][Masked] if `masked`.
+pub(super) fn debug_field(
+    field: &EditableField,
+    f: &mut fmt::Formatter<'_>,
+    masked: bool,
+) -> fmt::Result {
+    let mut debug = f.debug_struct("EditableField");
+    debug
+        .field("id", &field.id)
+        .field("field_type", &field.field_type);
+    if masked {
+        debug.field("value", &Masked);
+    } else {
+        debug.field("value", &field.value);
+    }
+    debug
+        .field("label", &field.label)
+        .field("extensions", &field.extensions)
+        .field("extra", &MaskedMap(&field.extra))
+        .finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn debug(credential: Value) -> String {
+        let credential: Credential =
+            serde_json::from_value(credential).expect("the credential is valid");
+        format!("{credential:?}")
+    }
+
+    #[test]
+    fn debug_masks_secrets_and_unknown_members() {
+        let output = debug(json!({
+            "type": "basic-auth",
+            "urls": ["https://example.com"],
+            "password": {
+                "id": "AA",
+                "fieldType": "concealed-string",
+                "value": "password-value",
+                "hint": "hint-value",
+            },
+            "recoveryCode": "code-value",
+        }));
+        assert!(output.contains("https://example.com"));
+        assert!(output.contains("recoveryCode"));
+        for secret in ["password-value", "hint-value", "code-value"] {
+            assert!(!output.contains(secret), "{output}");
+        }
+
+        let output = debug(json!({ "type": "note", "content": "note-value" }));
+        assert!(!output.contains("note-value"), "{output}");
+
+        let output = debug(json!({
+            "type": "passkey",
+            "credentialId": "AQ",
+            "rpId": "example.com",
+            "userName": "jane.doe",
+            "userDisplayName": "Jane Doe",
+            "userHandle": "Ag",
+            "key": "a2V5LXZhbHVl",
+            "fido2Extensions": {
+                "hmacSecret": { "alias": "alias-value", "hmacSecret": "aG1hYy12YWx1ZQ" },
+                "credBlob": "YmxvYi12YWx1ZQ",
+                "largeBlob": { "size": 11, "alg": "deflate", "data": "bGFyZ2UtdmFsdWU" },
+            },
+        }));
+        assert!(output.contains("alias-value"));
+        for secret in ["key-value", "hmac-value", "blob-value", "large-value"] {
+            let bytes = format!("{:?}", secret.as_bytes());
+            assert!(!output.contains(&bytes[1..bytes.len() - 1]), "{output}");
+        }
+    }
+
+    #[test]
+    fn debug_masks_unknown_credentials() {
+        let output = debug(json!({ "type": "future", "secret": "future-value" }));
+        assert!(output.contains("future"));
+        assert!(!output.contains("future-value"), "{output}");
+    }
+
+    #[test]
+    fn redact_replaces_unknown_content_and_personal_data() {
+        let mut item: Item = serde_json::from_value(json!({
+            "id": "AA",
+            "type": "login",
+            "title": "title-value",
+            "tags": ["tag-value"],
+            "credentials": [{
+                "type": "basic-auth",
+                "urls": [],
+                "username": {
+                    "id": "AQ",
+                    "fieldType": "string",
+                    "value": "username-value",
+                    "label": "label-value",
+                },
+                "password": {
+                    "id": "Ag",
+                    "fieldType": "future-type",
+                    "value": "password-value",
+                    "hint": "hint-value",
+                },
+            }],
+            "extensions": [{ "name": "future", "secret": "extension-value" }],
+            "recoveryCode": "code-value",
+        }))
+        .expect("the item is valid");
+
+        item.redact(RedactionPolicy::default());
+        let output = serde_json::to_string(&item).expect("the item serializes");
+        for kept in [
+            "title-value",
+            "tag-value",
+            "username-value",
+            "label-value",
+            "future",
+        ] {
+            assert!(output.contains(kept), "{output}");
+        }
+        for secret in [
+            "password-value",
+            "hint-value",
+            "extension-value",
+            "code-value",
+        ] {
+            assert!(!output.contains(secret), "{output}");
+        }
+
+        item.redact(RedactionPolicy {
+            personal_data: true,
+        });
+        let output = serde_json::to_string(&item).expect("the item serializes");
+        for personal in ["title-value", "tag-value", "username-value", "label-value"] {
+            assert!(!output.contains(personal), "{output}");
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! # Extension Registry
+//!
+//! The type parameter `E` of [Extension] allows a single external type to be parsed. When several
+//! independent extensions need to be understood, they can instead be registered by name in an
+//! [ExtensionRegistry], and the [unknown extensions][Extension::Unknown] resolved against it.
+
+use std::{any::Any, collections::HashMap};
+
+use serde::de::DeserializeOwned;
+
+use super::Extension;
+
+type DeserializeFn =
+    Box<dyn Fn(serde_json::Value) -> serde_json::Result<Box<dyn Any + Send + Sync>> + Send + Sync>;
+
+/// A set of extension types indexed by the value of their `name` member.
+#[derive(Default)]
+pub struct ExtensionRegistry {
+    deserializers: HashMap<String, DeserializeFn>,
+}
+
+impl ExtensionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `T` as the type of the extensions named `name`, replacing any type previously
+    /// registered under that name.
+    ///
+    /// `T` is deserialized from the whole extension object, including its `name` member, which
+    /// it can therefore ignore.
+    pub fn register<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: DeserializeOwned + Any + Send + Sync,
+    {
+        self.deserializers.insert(
+            name.into(),
+            Box::new(|value| {
+                let value: T = serde_json::from_value(value)?;
+                Ok(Box::new(value))
+            }),
+        );
+        self
+    }
+
+    /// Whether a type is registered for the extensions named `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.deserializers.contains_key(name)
+    }
+}
+
+impl std::fmt::Debug for ExtensionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.deserializers.keys()).finish()
+    }
+}
+
+/// An extension deserialized into the type registered for its name in an [ExtensionRegistry].
+#[derive(Debug)]
+pub struct ResolvedExtension {
+    name: String,
+    value: Box<dyn Any + Send + Sync>,
+}
+
+impl ResolvedExtension {
+    /// The `name` member of the extension.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the extension was deserialized into a `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    /// Borrow the extension as a `T`, if it is one.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// Take the extension as a `T`, or give it back if it is of another type.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self.value.downcast() {
+            Ok(value) => Ok(*value),
+            Err(value) => Err(Self {
+                name: self.name,
+                value,
+            }),
+        }
+    }
+}
+
+impl<E> Extension<E> {
+    /// Deserialize an [unknown extension][Extension::Unknown] into the type registered for its
+    /// name in `registry`.
+    ///
+    /// Returns [None] for [external extensions][Extension::External], which are already typed,
+    /// and for extensions whose name is not registered. Returns an error if the extension does not
+    /// match the registered type.
+    pub fn resolve(
+        &self,
+        registry: &ExtensionRegistry,
+    ) -> Option<serde_json::Result<ResolvedExtension>> {
+        let Extension::Unknown(value) = self else {
+            return None;
+        };
+        let name = value.get("name")?.as_str()?;
+        let deserialize = registry.deserializers.get(name)?;
+        Some(deserialize(value.clone()).map(|value| ResolvedExtension {
+            name: name.to_owned(),
+            value,
+        }))
+    }
+}
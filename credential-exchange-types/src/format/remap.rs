@@ -0,0 +1,137 @@
+//! # Identifier Remapping
+//!
+//! Resolves collisions between the identifiers of an imported exchange and those already used by
+//! the importer. Unlike [merging][super::Account::merge], the new identifiers are derived from the
+//! colliding ones, so that remapping the same document against the same existing identifiers
+//! always assigns the same identifiers, e.g. when an import is retried after a failure.
+//!
+//! This doesn't make imports idempotent: once a remapped document is imported, its derived
+//! identifiers are part of the existing ones, so importing it again derives different identifiers
+//! for the colliding items instead of reusing those of the first import. Importers which need to
+//! recognize a re-imported document have to keep the returned [IdRemapping] themselves.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+use super::{visit_collections_mut, Account, Header};
+use crate::B64Url;
+
+/// The identifiers reassigned by [Account::remap_ids], from the old identifier to the new one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IdRemapping {
+    /// The reassigned [Item][super::Item] identifiers.
+    pub items: HashMap<B64Url, B64Url>,
+    /// The reassigned [Collection][super::Collection] identifiers, at any depth.
+    pub collections: HashMap<B64Url, B64Url>,
+}
+
+impl IdRemapping {
+    /// Whether no identifier was reassigned.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.collections.is_empty()
+    }
+}
+
+impl<E> Account<E> {
+    /// Reassign the identifiers of the items and collections of this [Account] which are part of
+    /// `existing`, the identifiers already used by the importer, and update every [LinkedItem]
+    /// referencing a reassigned item of this account. Returns the reassigned identifiers.
+    ///
+    /// The new identifiers are [B64Url::DEFAULT_ID_LENGTH] bytes of a SHA-256 digest of the account
+    /// identifier, of the colliding identifier and of an attempt counter, so the result only
+    /// depends on this account and on `existing`. They never collide with `existing` nor with the
+    /// other identifiers of this account. As the identifiers derived by a first import are part of
+    /// `existing` afterwards, importing the same document again derives different ones: keep the
+    /// returned [IdRemapping] to recognize a re-imported document.
+    ///
+    /// [LinkedItem]: super::LinkedItem
+    pub fn remap_ids(&mut self, existing: &HashSet<B64Url>) -> IdRemapping {
+        let mut taken: HashSet<B64Url> = existing.clone();
+        taken.extend(self.items.iter().map(|item| item.id.clone()));
+        taken.extend(
+            self.iter_all_collections()
+                .map(|(_, collection)| collection.id.clone()),
+        );
+
+        let mut remapping = IdRemapping::default();
+        for item in self.items.iter_mut() {
+            if existing.contains(&item.id) {
+                let id = derived_id(&self.id, &item.id, &mut taken);
+                remapping
+                    .items
+                    .insert(std::mem::replace(&mut item.id, id.clone()), id);
+            }
+        }
+        let account = self.id.clone();
+        visit_collections_mut(&mut self.collections, |collections| {
+            for collection in collections.iter_mut() {
+                if existing.contains(&collection.id) {
+                    let id = derived_id(&account, &collection.id, &mut taken);
+                    remapping
+                        .collections
+                        .insert(std::mem::replace(&mut collection.id, id.clone()), id);
+                }
+            }
+        });
+
+        self.for_each_link_mut(|link| {
+            if !matches!(&link.account, Some(linked) if *linked != account) {
+                if let Some(id) = remapping.items.get(&link.item) {
+                    link.item = id.clone();
+                }
+            }
+        });
+        remapping
+    }
+}
+
+impl<E> Header<E> {
+    /// [Remap the identifiers][Account::remap_ids] of every [Account] of this exchange, and
+    /// update the [LinkedItems][super::LinkedItem] referencing the reassigned items of another
+    /// account. Returns the reassigned identifiers by account, omitting the accounts without any.
+    pub fn remap_ids(&mut self, existing: &HashSet<B64Url>) -> BTreeMap<B64Url, IdRemapping> {
+        let remappings: BTreeMap<B64Url, IdRemapping> = self
+            .accounts
+            .iter_mut()
+            .map(|account| (account.id.clone(), account.remap_ids(existing)))
+            .filter(|(_, remapping)| !remapping.is_empty())
+            .collect();
+
+        for account in &mut self.accounts {
+            let own = account.id.clone();
+            account.for_each_link_mut(|link| {
+                let Some(remapping) = link
+                    .account
+                    .as_ref()
+                    .filter(|linked| **linked != own)
+                    .and_then(|linked| remappings.get(linked))
+                else {
+                    return;
+                };
+                if let Some(id) = remapping.items.get(&link.item) {
+                    link.item = id.clone();
+                }
+            });
+        }
+        remappings
+    }
+}
+
+/// Derive a new identifier for `id` in `account` which is not part of `taken`, and reserve it.
+fn derived_id(account: &B64Url, id: &B64Url, taken: &mut HashSet<B64Url>) -> B64Url {
+    for attempt in 0u32.. {
+        let mut hasher = Sha256::new();
+        for part in [account.as_ref(), id.as_ref()] {
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part);
+        }
+        hasher.update(attempt.to_be_bytes());
+        let digest = hasher.finalize();
+        let candidate = B64Url::from(&digest[..B64Url::DEFAULT_ID_LENGTH]);
+        if taken.insert(candidate.clone()) {
+            return candidate;
+        }
+    }
+    unreachable!("the identifier space is exhausted")
+}
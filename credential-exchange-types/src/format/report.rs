@@ -0,0 +1,189 @@
+//! # Interoperability Reports
+//!
+//! Records the values of a document which are not known to this version of the format, so that
+//! providers can discover what other vendors emit and prioritize their support.
+
+use std::collections::BTreeMap;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "non-standard-extensions")]
+use super::ItemState;
+use super::{
+    Account, Credential, EditableField, Extension, FieldType, Header, Item, ItemType,
+    OTPHashAlgorithm, ParseError, SharingAccessorPermission, SharingAccessorType,
+};
+
+/// The values of an exchange which are not known to this version of the format, each with the
+/// number of times it was encountered.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteropReport {
+    /// The `type` of the [unknown credentials][Credential::Unknown].
+    pub credential_types: BTreeMap<String, usize>,
+    /// The unknown [field types][FieldType::Unknown] of the [EditableField]s.
+    pub field_types: BTreeMap<String, usize>,
+    /// The `name` of the [unknown extensions][Extension::Unknown], or an empty string for those
+    /// without a name.
+    pub extensions: BTreeMap<String, usize>,
+    /// The unknown values of the other enumerations, by name of the enumeration, e.g.
+    /// `ItemType`.
+    pub enum_values: BTreeMap<&'static str, BTreeMap<String, usize>>,
+}
+
+impl InteropReport {
+    /// Whether every value of the exchange is known to this version of the format.
+    pub fn is_empty(&self) -> bool {
+        self.credential_types.is_empty()
+            && self.field_types.is_empty()
+            && self.extensions.is_empty()
+            && self.enum_values.is_empty()
+    }
+
+    fn account<E>(&mut self, account: &Account<E>) {
+        self.extensions(&account.extensions);
+        for (_, collection) in account.iter_all_collections() {
+            self.extensions(&collection.extensions);
+        }
+        for item in &account.items {
+            self.item(item);
+        }
+    }
+
+    fn item<E>(&mut self, item: &Item<E>) {
+        if let ItemType::Unknown(value) = &item.ty {
+            self.enum_value("ItemType", value);
+        }
+        self.extensions(&item.extensions);
+        for credential in &item.credentials {
+            self.credential(credential);
+        }
+    }
+
+    fn credential(&mut self, credential: &Credential) {
+        match credential {
+            Credential::Totp(totp) => {
+                if let OTPHashAlgorithm::Unknown(value) = &totp.algorithm {
+                    self.enum_value("OTPHashAlgorithm", value);
+                }
+            }
+            Credential::Unknown { ty, .. } => count(&mut self.credential_types, ty),
+            _ => {}
+        }
+        for field in credential.editable_fields() {
+            if let FieldType::Unknown(value) = &field.field_type {
+                count(&mut self.field_types, value);
+            }
+            self.extensions(&field.extensions);
+        }
+    }
+
+    fn extensions<E>(&mut self, extensions: &Option<Vec<Extension<E>>>) {
+        for extension in extensions.iter().flatten() {
+            match extension {
+                Extension::Unknown(_) => {
+                    count(&mut self.extensions, extension.name().unwrap_or_default());
+                }
+                #[cfg(feature = "non-standard-extensions")]
+                Extension::ItemState(extension) => {
+                    if let ItemState::Unknown(value) = &extension.state {
+                        self.enum_value("ItemState", value);
+                    }
+                }
+                Extension::Shared(extension) => {
+                    for accessor in &extension.accessors {
+                        if let SharingAccessorType::Unknown(value) = &accessor.ty {
+                            self.enum_value("SharingAccessorType", value);
+                        }
+                        for permission in &accessor.permissions {
+                            if let SharingAccessorPermission::Unknown(value) = permission {
+                                self.enum_value("SharingAccessorPermission", value);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn enum_value(&mut self, name: &'static str, value: &str) {
+        count(self.enum_values.entry(name).or_default(), value);
+    }
+}
+
+impl Credential {
+    /// The [EditableFields][EditableField] of this credential, for the types which hold them.
+    pub(super) fn editable_fields(&self) -> Vec<&EditableField> {
+        match self {
+            Credential::BasicAuth(credential) => credential.editable_fields().collect(),
+            Credential::DriversLicense(credential) => credential.editable_fields().collect(),
+            Credential::Address(credential) => credential.editable_fields().collect(),
+            Credential::SshKey(credential) => credential.editable_fields().collect(),
+            Credential::IdentityDocument(credential) => credential.editable_fields().collect(),
+            Credential::Passport(credential) => credential.editable_fields().collect(),
+            Credential::PersonName(credential) => credential.editable_fields().collect(),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::BirthCertificate(credential) => credential.editable_fields().collect(),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(credential) => credential.editable_fields().collect(),
+            Credential::Passkey(_)
+            | Credential::CreditCard(_)
+            | Credential::Note(_)
+            | Credential::Totp(_)
+            | Credential::ItemReference(_)
+            | Credential::File(_)
+            | Credential::Unknown { .. } => Vec::new(),
+        }
+    }
+
+    /// The [EditableFields][EditableField] of this credential, mutably.
+    pub(super) fn editable_fields_mut(&mut self) -> Vec<&mut EditableField> {
+        match self {
+            Credential::BasicAuth(credential) => credential.editable_fields_mut().collect(),
+            Credential::DriversLicense(credential) => credential.editable_fields_mut().collect(),
+            Credential::Address(credential) => credential.editable_fields_mut().collect(),
+            Credential::SshKey(credential) => credential.editable_fields_mut().collect(),
+            Credential::IdentityDocument(credential) => credential.editable_fields_mut().collect(),
+            Credential::Passport(credential) => credential.editable_fields_mut().collect(),
+            Credential::PersonName(credential) => credential.editable_fields_mut().collect(),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::BirthCertificate(credential) => credential.editable_fields_mut().collect(),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(credential) => credential.editable_fields_mut().collect(),
+            Credential::Passkey(_)
+            | Credential::CreditCard(_)
+            | Credential::Note(_)
+            | Credential::Totp(_)
+            | Credential::ItemReference(_)
+            | Credential::File(_)
+            | Credential::Unknown { .. } => Vec::new(),
+        }
+    }
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, value: &str) {
+    *counts.entry(value.to_owned()).or_default() += 1;
+}
+
+impl<E> Header<E> {
+    /// List the values of this exchange which are not known to this version of the format.
+    pub fn interop_report(&self) -> InteropReport {
+        let mut report = InteropReport::default();
+        report.extensions(&self.extensions);
+        for account in &self.accounts {
+            report.account(account);
+        }
+        report
+    }
+}
+
+impl<E: DeserializeOwned> Header<E> {
+    /// Deserialize an exchange like [Header::from_json], and list its values which are not known
+    /// to this version of the format.
+    pub fn from_json_with_report(json: &str) -> Result<(Self, InteropReport), ParseError> {
+        let header = Self::from_json(json)?;
+        let report = header.interop_report();
+        Ok((header, report))
+    }
+}
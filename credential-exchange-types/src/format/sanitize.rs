@@ -0,0 +1,143 @@
+//! # Display String Sanitization
+//!
+//! The names and titles of an exchange are shown to the user, e.g. in consent dialogs and import
+//! review screens. Control characters, bidirectional overrides and invisible characters can make
+//! them render as something else than what they contain, e.g. a right-to-left override makes
+//! `invoice\u{202E}fdp.exe` read as `invoiceexe.pdf`. [Header::sanitize_display_strings] strips
+//! these characters, while [Header::check_display_strings] rejects exchanges containing them.
+
+use std::borrow::Cow;
+
+use super::{Credential, Header};
+
+/// A display string contains control, bidirectional formatting or invisible characters, see
+/// [Header::check_display_strings].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsafeDisplayString;
+
+impl std::fmt::Display for UnsafeDisplayString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Value contains control, bidirectional or invisible characters")
+    }
+}
+
+/// Remove the characters of `value` which can alter how it is displayed: the control characters,
+/// including line breaks, the bidirectional formatting characters and the zero-width characters
+/// without a linguistic use. The zero-width joiners are kept, as emoji sequences and several
+/// scripts rely on them.
+pub fn sanitize_display(value: &str) -> Cow<'_, str> {
+    if value.chars().any(is_unsafe) {
+        Cow::Owned(value.chars().filter(|c| !is_unsafe(*c)).collect())
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+impl<E> Header<E> {
+    /// [Sanitize][sanitize_display] the strings of this exchange which are meant to be displayed:
+    /// the names of the accounts, the titles and subtitles of the collections and items, the
+    /// tags, the field labels and the user names. Returns the number of modified strings.
+    pub fn sanitize_display_strings(&mut self) -> usize {
+        let mut modified = 0;
+        for value in self.display_strings_mut() {
+            if let Cow::Owned(sanitized) = sanitize_display(value) {
+                *value = sanitized;
+                modified += 1;
+            }
+        }
+        modified
+    }
+
+    /// Reject this exchange if one of the strings listed in [Header::sanitize_display_strings]
+    /// would be modified by [sanitize_display].
+    pub fn check_display_strings(&self) -> Result<(), UnsafeDisplayString> {
+        if self
+            .display_strings()
+            .any(|value| value.chars().any(is_unsafe))
+        {
+            return Err(UnsafeDisplayString);
+        }
+        Ok(())
+    }
+
+    fn display_strings(&self) -> impl Iterator<Item = &String> {
+        let mut strings = Vec::new();
+        for account in &self.accounts {
+            strings.push(&account.user_name);
+            strings.extend(&account.full_name);
+            for (_, collection) in account.iter_all_collections() {
+                strings.push(&collection.title);
+                strings.extend(&collection.subtitle);
+            }
+            for item in &account.items {
+                strings.push(&item.title);
+                strings.extend(&item.subtitle);
+                strings.extend(item.tags.iter().flatten());
+                for credential in &item.credentials {
+                    match credential {
+                        Credential::BasicAuth(credential) => {
+                            strings.extend(credential.username.as_ref().map(|field| &field.value));
+                        }
+                        Credential::Totp(credential) => strings.push(&credential.username),
+                        _ => {}
+                    }
+                    for field in credential.editable_fields() {
+                        strings.extend(&field.label);
+                    }
+                }
+            }
+        }
+        strings.into_iter()
+    }
+
+    fn display_strings_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        let mut strings = Vec::new();
+        for account in &mut self.accounts {
+            strings.push(&mut account.user_name);
+            strings.extend(&mut account.full_name);
+            let mut stack: Vec<_> = account.collections.iter_mut().collect();
+            while let Some(collection) = stack.pop() {
+                strings.push(&mut collection.title);
+                strings.extend(&mut collection.subtitle);
+                stack.extend(collection.sub_collections.iter_mut().flatten());
+            }
+            for item in &mut account.items {
+                strings.push(&mut item.title);
+                strings.extend(&mut item.subtitle);
+                strings.extend(item.tags.iter_mut().flatten());
+                for credential in &mut item.credentials {
+                    match credential {
+                        Credential::Totp(credential) => strings.push(&mut credential.username),
+                        Credential::BasicAuth(credential) => {
+                            if let Some(username) = &mut credential.username {
+                                strings.push(&mut username.value);
+                                strings.extend(&mut username.label);
+                            }
+                            if let Some(password) = &mut credential.password {
+                                strings.extend(&mut password.label);
+                            }
+                        }
+                        credential => {
+                            for field in credential.editable_fields_mut() {
+                                strings.extend(&mut field.label);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        strings.into_iter()
+    }
+}
+
+/// Whether `c` can alter how the surrounding text is displayed.
+fn is_unsafe(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            // Bidirectional formatting: marks, embeddings, overrides and isolates.
+            '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+            // Zero-width space, word joiner, invisible operators and byte order mark.
+            | '\u{180E}' | '\u{200B}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+        )
+}
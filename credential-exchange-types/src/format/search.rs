@@ -0,0 +1,138 @@
+//! # Searching
+//!
+//! Filters the [Items][Item] of an exchange with composable [Query] values, e.g. to review a large
+//! document before importing it:
+//!
+//! ```
+//! use credential_exchange_types::format::{CredentialType, Header, Query};
+//!
+//! fn bank_logins(header: &Header) -> usize {
+//!     let query =
+//!         Query::title_contains("bank").and(Query::credential_type(CredentialType::BasicAuth));
+//!     header.search(query).count()
+//! }
+//! ```
+
+use super::{item_matches, Account, CredentialType, Header, Item, ItemType};
+use crate::uri::Origin;
+
+/// A predicate over the [Items][Item] of an [Account], see [Account::search] and
+/// [Header::search].
+///
+/// Queries are combined with [Query::and], [Query::or] and negated with `!`. Text is compared case
+/// insensitively.
+#[derive(Clone, Debug)]
+pub struct Query(Node);
+
+#[derive(Clone, Debug)]
+enum Node {
+    All,
+    TitleContains(String),
+    Tag(String),
+    ItemType(ItemType),
+    CredentialType(CredentialType),
+    Url(Option<Origin>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+}
+
+impl Query {
+    /// Match every item.
+    pub fn all() -> Self {
+        Self(Node::All)
+    }
+
+    /// Match the items whose [title][Item::title] contains `text`.
+    pub fn title_contains(text: &str) -> Self {
+        Self(Node::TitleContains(text.to_lowercase()))
+    }
+
+    /// Match the items with the [tag][Item::tags] `tag`.
+    pub fn tag(tag: &str) -> Self {
+        Self(Node::Tag(tag.to_lowercase()))
+    }
+
+    /// Match the items of type `ty`.
+    pub fn item_type(ty: ItemType) -> Self {
+        Self(Node::ItemType(ty))
+    }
+
+    /// Match the items holding at least one credential of type `ty`.
+    pub fn credential_type(ty: CredentialType) -> Self {
+        Self(Node::CredentialType(ty))
+    }
+
+    /// Match the items associated with the website at `url`, in the same way as
+    /// [Account::items_for_url]. Matches nothing if the URL cannot be parsed.
+    pub fn url(url: &str) -> Self {
+        Self(Node::Url(Origin::parse(url)))
+    }
+
+    /// Match the items matched by both queries.
+    pub fn and(self, other: Query) -> Self {
+        Self(Node::And(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// Match the items matched by either query.
+    pub fn or(self, other: Query) -> Self {
+        Self(Node::Or(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// Whether `item` is matched by this query.
+    pub fn matches<E>(&self, item: &Item<E>) -> bool {
+        self.0.matches(item)
+    }
+}
+
+/// Match the items which are not matched by the query.
+impl std::ops::Not for Query {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(Node::Not(Box::new(self.0)))
+    }
+}
+
+impl Node {
+    fn matches<E>(&self, item: &Item<E>) -> bool {
+        match self {
+            Node::All => true,
+            Node::TitleContains(text) => item.title.to_lowercase().contains(text),
+            Node::Tag(tag) => item
+                .tags
+                .iter()
+                .flatten()
+                .any(|candidate| candidate.to_lowercase() == *tag),
+            Node::ItemType(ty) => item.ty == *ty,
+            Node::CredentialType(ty) => item
+                .credentials
+                .iter()
+                .any(|credential| credential.credential_type() == *ty),
+            Node::Url(origin) => origin
+                .as_ref()
+                .is_some_and(|origin| item_matches(item, origin, &[])),
+            Node::And(a, b) => a.matches(item) && b.matches(item),
+            Node::Or(a, b) => a.matches(item) || b.matches(item),
+            Node::Not(query) => !query.matches(item),
+        }
+    }
+}
+
+impl<E> Account<E> {
+    /// Find the [Items][Item] owned by this [Account] which are matched by `query`.
+    pub fn search(&self, query: Query) -> impl Iterator<Item = &Item<E>> {
+        self.items.iter().filter(move |item| query.matches(item))
+    }
+}
+
+impl<E> Header<E> {
+    /// Find the [Items][Item] of every [Account] of this exchange which are matched by `query`,
+    /// along with the account owning them.
+    pub fn search(&self, query: Query) -> impl Iterator<Item = (&Account<E>, &Item<E>)> {
+        self.accounts
+            .iter()
+            .flat_map(|account| account.items.iter().map(move |item| (account, item)))
+            .filter(move |(_, item)| query.matches(item))
+    }
+}
@@ -0,0 +1,164 @@
+//! # Shared Exchanges
+//!
+//! A representation of an exchange whose [Items][Item] are reference counted, for pipelines which
+//! convert the same parsed document into several output formats. Cloning a [SharedHeader] or a
+//! [SharedAccount] only clones the metadata and the collections, while the items, which hold the
+//! bulk of the data, are shared by all the clones. The metadata is held by the [Header] and
+//! [Account] types themselves, without their accounts and items.
+
+use std::sync::Arc;
+
+use serde::{
+    ser::{Error as _, SerializeMap},
+    Serialize, Serializer,
+};
+use serde_json::Value;
+
+use super::{Account, Header, Item};
+
+/// A [Header] whose items are reference counted, so that its clones share them. It serializes
+/// like the [Header] it was created from, see [SharedHeader::metadata].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedHeader<E = ()> {
+    /// The metadata of the exchange. Its [accounts][Header::accounts] are always empty, as they
+    /// are held by [SharedHeader::accounts] instead, in their place when serializing.
+    ///
+    /// The members of the metadata are serialized through a [Value], and are therefore sorted by
+    /// name unless the `preserve_order` feature is enabled.
+    pub metadata: Header<E>,
+    /// See [Header::accounts].
+    pub accounts: Vec<SharedAccount<E>>,
+}
+
+/// An [Account] whose items are shared, see [SharedHeader].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedAccount<E = ()> {
+    /// The metadata and the collections of the account. Its [items][Account::items] are always
+    /// empty, as they are held by [SharedAccount::items] instead, in their place when serializing.
+    pub metadata: Account<E>,
+    /// See [Account::items].
+    pub items: Vec<Arc<Item<E>>>,
+}
+
+impl<E> From<Header<E>> for SharedHeader<E> {
+    fn from(mut header: Header<E>) -> Self {
+        let accounts = std::mem::take(&mut header.accounts);
+        SharedHeader {
+            metadata: header,
+            accounts: accounts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl<E> From<Account<E>> for SharedAccount<E> {
+    fn from(mut account: Account<E>) -> Self {
+        let items = std::mem::take(&mut account.items);
+        SharedAccount {
+            metadata: account,
+            items: items.into_iter().map(Arc::new).collect(),
+        }
+    }
+}
+
+impl<E: Clone> SharedHeader<E> {
+    /// Clone this exchange into a [Header], which deep-clones every item.
+    pub fn to_header(&self) -> Header<E> {
+        Header {
+            accounts: self
+                .accounts
+                .iter()
+                .map(SharedAccount::to_account)
+                .collect(),
+            ..self.metadata.clone()
+        }
+    }
+}
+
+impl<E: Clone> SharedAccount<E> {
+    /// Clone this account into an [Account], which deep-clones every item.
+    pub fn to_account(&self) -> Account<E> {
+        Account {
+            items: self.items.iter().map(|item| Item::clone(item)).collect(),
+            ..self.metadata.clone()
+        }
+    }
+}
+
+impl<E: Serialize> Serialize for SharedHeader<E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_replacing(&self.metadata, "accounts", &self.accounts, serializer)
+    }
+}
+
+impl<E: Serialize> Serialize for SharedAccount<E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_replacing(&self.metadata, "items", &Items(&self.items), serializer)
+    }
+}
+
+/// The shared items of a [SharedAccount], serialized as the items themselves.
+struct Items<'a, E>(&'a [Arc<Item<E>>]);
+
+impl<E: Serialize> Serialize for Items<'_, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(|item| item.as_ref()))
+    }
+}
+
+/// Serialize the members of `metadata`, with the value of its `member` replaced by `value`.
+fn serialize_replacing<S: Serializer>(
+    metadata: &impl Serialize,
+    member: &str,
+    value: &impl Serialize,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let Value::Object(metadata) = serde_json::to_value(metadata).map_err(S::Error::custom)? else {
+        return Err(S::Error::custom("the metadata isn't an object"));
+    };
+    let mut map = serializer.serialize_map(Some(metadata.len()))?;
+    for (key, entry) in &metadata {
+        if key == member {
+            map.serialize_entry(key, value)?;
+        } else {
+            map.serialize_entry(key, entry)?;
+        }
+    }
+    map.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn serializes_like_the_header() {
+        let header: Header = serde_json::from_value(json!({
+            "version": 0,
+            "exporter": "exporter.example.com",
+            "timestamp": 0,
+            "accounts": [{
+                "id": "AA",
+                "userName": "",
+                "email": "jane@example.com",
+                "collections": [],
+                "items": [{ "id": "AQ", "type": "login", "title": "", "credentials": [] }],
+                "vendor": true,
+            }],
+            "vendor": true,
+        }))
+        .expect("the header is valid");
+
+        let shared = SharedHeader::from(header.clone());
+        assert_eq!(
+            serde_json::to_value(&shared).expect("the shared header serializes"),
+            serde_json::to_value(&header).expect("the header serializes")
+        );
+        assert!(Arc::ptr_eq(
+            &shared.accounts[0].items[0],
+            &shared.clone().accounts[0].items[0]
+        ));
+        assert_eq!(shared.to_header(), header);
+    }
+}
@@ -0,0 +1,169 @@
+//! # Presentation Order
+//!
+//! Sorts [Items][Item] and [Collections][Collection] for display, e.g. in import review screens,
+//! as opposed to [Header::normalize_order][super::Header::normalize_order] which sorts by
+//! identifier for reproducible serialization.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use super::{Account, Collection, Item, ItemType};
+use crate::B64Url;
+
+/// The criteria by which [Account::sort_items] orders items. Ties are broken by title, then by
+/// id, so that the order is deterministic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ItemOrder {
+    /// Alphabetically by [title][Item::title], see [compare_titles].
+    #[default]
+    Title,
+    /// Most recently [modified][Item::modified_at] first. Items without a modification date come
+    /// last.
+    ModifiedAt,
+    /// By [type][Item::ty], in the order in which the types are declared in [ItemType], unknown
+    /// types last.
+    ItemType,
+}
+
+impl ItemOrder {
+    /// Compare two items according to this order.
+    pub fn compare<E>(self, a: &Item<E>, b: &Item<E>) -> Ordering {
+        let primary = match self {
+            ItemOrder::Title => Ordering::Equal,
+            ItemOrder::ModifiedAt => b.modified_at.cmp(&a.modified_at),
+            ItemOrder::ItemType => type_rank(&a.ty).cmp(&type_rank(&b.ty)),
+        };
+        primary
+            .then_with(|| compare_titles(&a.title, &b.title))
+            .then_with(|| a.id.cmp(&b.id))
+    }
+}
+
+fn type_rank(ty: &ItemType) -> (u8, &str) {
+    match ty {
+        ItemType::Login => (0, ""),
+        ItemType::Document => (1, ""),
+        ItemType::Identity => (2, ""),
+        ItemType::Unknown(name) => (3, name),
+    }
+}
+
+/// Compare two titles for display.
+///
+/// Letters are compared case insensitively and without their diacritics, e.g. `é` sorts with `e`,
+/// and runs of digits are compared by numeric value, so `Item 2` sorts before `Item 10`. Titles
+/// which are only distinguished by case or diacritics are then ordered by code point. This is a
+/// locale independent approximation of the default Unicode collation for Latin scripts, full
+/// locale specific collation requires a collation library such as ICU.
+pub fn compare_titles(a: &str, b: &str) -> Ordering {
+    collation_key(a)
+        .cmp(&collation_key(b))
+        .then_with(|| a.cmp(b))
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    /// A run of digits without its leading zeros, ordered by length first so that it is compared
+    /// by numeric value.
+    Number(usize, String),
+    Char(char),
+}
+
+fn collation_key(title: &str) -> Vec<Segment> {
+    let mut key = Vec::new();
+    let mut chars = title.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            let mut digits = String::from(c);
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            let digits = digits.trim_start_matches('0').to_owned();
+            key.push(Segment::Number(digits.len(), digits));
+        } else {
+            key.extend(c.to_lowercase().map(|c| Segment::Char(fold(c))));
+        }
+    }
+    key
+}
+
+/// Remove the diacritics of the lowercase letters of the Latin-1 Supplement and Latin Extended-A
+/// blocks.
+fn fold(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'ĥ' | 'ħ' => 'h',
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'ĵ' => 'j',
+        'ķ' => 'k',
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => 'l',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'ţ' | 'ť' | 'ŧ' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'ŵ' => 'w',
+        'ý' | 'ÿ' | 'ŷ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+impl<E> Account<E> {
+    /// Sort the [Items][Item] of this [Account] by `order`, and the
+    /// [LinkedItems][super::LinkedItem] of every collection in the same order. Links to items of
+    /// other accounts come last, by account and item id.
+    pub fn sort_items(&mut self, order: ItemOrder) {
+        self.items.sort_by(|a, b| order.compare(a, b));
+
+        let positions: HashMap<&B64Url, usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(position, item)| (&item.id, position))
+            .collect();
+        let mut stack: Vec<&mut Collection<E>> = self.collections.iter_mut().collect();
+        while let Some(collection) = stack.pop() {
+            collection.items.sort_by_cached_key(|link| {
+                let position = match link.account {
+                    None => positions.get(&link.item).copied(),
+                    Some(_) => None,
+                };
+                (
+                    position.is_none(),
+                    position,
+                    link.account.clone(),
+                    link.item.clone(),
+                )
+            });
+            stack.extend(collection.sub_collections.iter_mut().flatten());
+        }
+    }
+
+    /// Sort the [Collections][Collection] of this [Account] by title with [compare_titles], at
+    /// every depth.
+    pub fn sort_collections(&mut self) {
+        sort_collections(&mut self.collections);
+    }
+}
+
+impl<E> Collection<E> {
+    /// Sort the [sub-collections][Collection::sub_collections] of this [Collection] by title with
+    /// [compare_titles], at every depth.
+    pub fn sort_sub_collections(&mut self) {
+        if let Some(collections) = self.sub_collections.as_mut() {
+            sort_collections(collections);
+        }
+    }
+}
+
+fn sort_collections<E>(collections: &mut [Collection<E>]) {
+    collections.sort_by(|a, b| compare_titles(&a.title, &b.title).then_with(|| a.id.cmp(&b.id)));
+    collections
+        .iter_mut()
+        .for_each(Collection::sort_sub_collections);
+}
@@ -0,0 +1,497 @@
+//! # Splitting Exchanges
+//!
+//! Partitions a large exchange into several smaller [Headers][Header], each of them a valid
+//! document on its own, for transports which limit the size of a message. The importer
+//! reassembles them with [Header::combine].
+//!
+//! Exchanges holding several accounts, e.g. for family or organization migrations, can also be
+//! assembled from the exports of each account with [Header::add_account], and split back with
+//! [Header::split_by_account].
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use super::{merge::unique_id, Account, Collection, Credential, Header, Item};
+use crate::B64Url;
+
+impl<E: Clone + Serialize> Header<E> {
+    /// Split this exchange into parts whose JSON serialization is at most `max_bytes` long.
+    ///
+    /// Every part holds the metadata of the header and of the accounts it contains. Items linked
+    /// together by an [ItemReferenceCredential][super::ItemReferenceCredential], and the top-level
+    /// [Collections][Collection] with the items they contain, are kept in the same part unless
+    /// they don't fit in a single one. A part may still exceed `max_bytes` if a single item or
+    /// collection tree is larger on its own.
+    ///
+    /// Fails if the extensions cannot be serialized.
+    pub fn split(&self, max_bytes: usize) -> serde_json::Result<Vec<Header<E>>> {
+        let mut splitter = Splitter {
+            template: self.clone_without_accounts(),
+            overhead: serde_json::to_vec(&self.clone_without_accounts())?.len(),
+            max_bytes,
+            parts: Vec::new(),
+            size: 0,
+        };
+        splitter.start_part();
+
+        for account in &self.accounts {
+            let metadata = account.clone_metadata();
+            let account_size = serde_json::to_vec(&metadata)?.len();
+
+            for cluster in clusters(account) {
+                let entries = cluster
+                    .trees
+                    .iter()
+                    .map(|&tree| Entry::Collection(&account.collections[tree]))
+                    .chain(
+                        cluster
+                            .items
+                            .iter()
+                            .map(|&item| Entry::Item(&account.items[item])),
+                    )
+                    .map(|entry| Ok((serde_json::to_vec(&entry)?.len(), entry)))
+                    .collect::<serde_json::Result<Vec<_>>>()?;
+
+                // An upper bound of the size of the whole cluster, counting a separator per entry.
+                let size = entries.iter().map(|(size, _)| size + 1).sum::<usize>()
+                    + splitter.cost(&metadata, account_size, None, 0);
+                if !splitter.fits(size) {
+                    splitter.start_part();
+                }
+                for (size, entry) in entries {
+                    if !splitter.fits(splitter.cost(&metadata, account_size, Some(&entry), size)) {
+                        splitter.start_part();
+                    }
+                    splitter.push(&metadata, account_size, entry, size);
+                }
+            }
+
+            // An account without any item or collection is still part of the exchange.
+            if account.items.is_empty() && account.collections.is_empty() {
+                if !splitter.fits(splitter.cost(&metadata, account_size, None, 0)) {
+                    splitter.start_part();
+                }
+                splitter.account(&metadata, account_size);
+            }
+        }
+
+        let mut parts = splitter.parts;
+        parts.retain(|part| !part.accounts.is_empty());
+        if parts.is_empty() {
+            parts.push(self.clone_without_accounts());
+        }
+        Ok(parts)
+    }
+}
+
+impl<E> Header<E> {
+    /// Reassemble the parts of an exchange produced by [Header::split].
+    ///
+    /// The metadata of the header is taken from the first part. Accounts with the same
+    /// [id][Account::id] are combined by concatenating their items and collections. Returns [None]
+    /// if there are no parts.
+    pub fn combine(parts: impl IntoIterator<Item = Header<E>>) -> Option<Header<E>> {
+        let mut parts = parts.into_iter();
+        let mut header = parts.next()?;
+        for part in parts {
+            for mut account in part.accounts {
+                match header
+                    .accounts
+                    .iter_mut()
+                    .find(|existing| existing.id == account.id)
+                {
+                    Some(existing) => {
+                        existing.items.append(&mut account.items);
+                        existing.collections.append(&mut account.collections);
+                    }
+                    None => header.accounts.push(account),
+                }
+            }
+        }
+        Some(header)
+    }
+}
+
+impl<E> Header<E> {
+    /// Add `account` to this exchange. If another account already has the same
+    /// [id][Account::id], the added account is assigned a new random id, and the links referring
+    /// to it are updated: its own links to its previous id, and the links of the other accounts
+    /// to an item which the added account owns but the existing account with that id doesn't.
+    /// Returns the id of the added account.
+    pub fn add_account(&mut self, mut account: Account<E>) -> B64Url {
+        let mut taken: HashSet<B64Url> = self
+            .accounts
+            .iter()
+            .map(|account| account.id.clone())
+            .collect();
+        if taken.contains(&account.id) {
+            let id = unique_id(&mut taken);
+            let previous = std::mem::replace(&mut account.id, id.clone());
+            account.for_each_link_mut(|link| {
+                if link.account.as_ref() == Some(&previous) {
+                    link.account = Some(id.clone());
+                }
+            });
+
+            let existing: HashSet<B64Url> = self
+                .accounts
+                .iter()
+                .filter(|existing| existing.id == previous)
+                .flat_map(|existing| existing.items.iter().map(|item| item.id.clone()))
+                .collect();
+            let added: HashSet<&B64Url> = account.items.iter().map(|item| &item.id).collect();
+            for other in &mut self.accounts {
+                other.for_each_link_mut(|link| {
+                    if link.account.as_ref() == Some(&previous)
+                        && added.contains(&link.item)
+                        && !existing.contains(&link.item)
+                    {
+                        link.account = Some(id.clone());
+                    }
+                });
+            }
+        }
+        let id = account.id.clone();
+        self.accounts.push(account);
+        id
+    }
+}
+
+impl<E: Clone> Header<E> {
+    /// Split this exchange into one exchange per account, each with the metadata of this header.
+    ///
+    /// The [LinkedItems][super::LinkedItem] of collections and item references whose
+    /// [account][super::LinkedItem::account] is another account of this exchange are kept as they
+    /// are. They dangle in the resulting exchanges, as allowed for the items shared by another
+    /// account, and the importer has to resolve them against the other exchanges.
+    pub fn split_by_account(self) -> Vec<Header<E>> {
+        let template = self.clone_without_accounts();
+        self.accounts
+            .into_iter()
+            .map(|account| Header {
+                accounts: vec![account],
+                ..template.clone_without_accounts()
+            })
+            .collect()
+    }
+
+    /// Clone this header without cloning its accounts.
+    fn clone_without_accounts(&self) -> Self {
+        Header {
+            version: self.version,
+            exporter: self.exporter.clone(),
+            timestamp: self.timestamp,
+            accounts: Vec::new(),
+            extensions: self.extensions.clone(),
+            extra: self.extra.clone(),
+        }
+    }
+}
+
+/// An entity to place in a part.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Entry<'a, E> {
+    Collection(&'a Collection<E>),
+    Item(&'a Item<E>),
+}
+
+/// Builds the parts of [Header::split].
+struct Splitter<E> {
+    template: Header<E>,
+    overhead: usize,
+    max_bytes: usize,
+    parts: Vec<Header<E>>,
+    /// The length of the serialization of the last part.
+    size: usize,
+}
+
+impl<E: Clone> Splitter<E> {
+    fn start_part(&mut self) {
+        if self
+            .parts
+            .last()
+            .is_some_and(|part| part.accounts.is_empty())
+        {
+            return;
+        }
+        self.parts.push(self.template.clone_without_accounts());
+        self.size = self.overhead;
+    }
+
+    fn fits(&self, size: usize) -> bool {
+        self.size + size <= self.max_bytes
+    }
+
+    /// The exact number of bytes added to the last part by adding `entry`, whose serialization
+    /// is `size` bytes long, to `account`, whose metadata is `account_size` bytes long.
+    fn cost(
+        &self,
+        account: &Account<E>,
+        account_size: usize,
+        entry: Option<&Entry<'_, E>>,
+        size: usize,
+    ) -> usize {
+        let (account_cost, target) = match self.current().accounts.last() {
+            Some(last) if last.id == account.id => (0, Some(last)),
+            last => (account_size + usize::from(last.is_some()), None),
+        };
+        let separator = match (entry, target) {
+            (Some(Entry::Collection(_)), Some(target)) => !target.collections.is_empty(),
+            (Some(Entry::Item(_)), Some(target)) => !target.items.is_empty(),
+            _ => false,
+        };
+        account_cost + size + usize::from(separator)
+    }
+
+    /// Add `account`, whose metadata is `size` bytes long, to the last part.
+    fn account(&mut self, account: &Account<E>, size: usize) {
+        self.size += self.cost(account, size, None, 0);
+        self.target(account);
+    }
+
+    /// Add `entry`, whose serialization is `size` bytes long, to `account` in the last part.
+    fn push(
+        &mut self,
+        account: &Account<E>,
+        account_size: usize,
+        entry: Entry<'_, E>,
+        size: usize,
+    ) {
+        self.size += self.cost(account, account_size, Some(&entry), size);
+        let target = self.target(account);
+        match entry {
+            Entry::Collection(collection) => target.collections.push(collection.clone()),
+            Entry::Item(item) => target.items.push(item.clone()),
+        }
+    }
+
+    /// The last part's copy of `account`, added without any item or collection if it isn't part
+    /// of it yet.
+    fn target(&mut self, account: &Account<E>) -> &mut Account<E> {
+        let part = self.current_mut();
+        match part.accounts.last() {
+            Some(last) if last.id == account.id => {}
+            _ => part.accounts.push(account.clone_metadata()),
+        }
+        let index = part.accounts.len() - 1;
+        &mut part.accounts[index]
+    }
+
+    fn current(&self) -> &Header<E> {
+        self.parts.last().expect("a part is always started")
+    }
+
+    fn current_mut(&mut self) -> &mut Header<E> {
+        self.parts.last_mut().expect("a part is always started")
+    }
+}
+
+/// The items and top-level collections of an account which should be kept together, by index.
+struct Cluster {
+    trees: Vec<usize>,
+    items: Vec<usize>,
+}
+
+/// Group the items and top-level collections of `account` which are linked together, in the
+/// order of their first item or collection.
+fn clusters<E>(account: &Account<E>) -> Vec<Cluster> {
+    let items = account.items.len();
+    let index: HashMap<&B64Url, usize> = account
+        .items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| (&item.id, index))
+        .collect();
+    let local = |id: &B64Url, linked_account: &Option<B64Url>| {
+        if linked_account
+            .as_ref()
+            .is_some_and(|linked| *linked != account.id)
+        {
+            return None;
+        }
+        index.get(id).copied()
+    };
+
+    // Items are numbered first, then the top-level collections.
+    let mut parents: Vec<usize> = (0..items + account.collections.len()).collect();
+    for (item_index, item) in account.items.iter().enumerate() {
+        for credential in &item.credentials {
+            if let Credential::ItemReference(reference) = credential {
+                let link = &reference.reference;
+                if let Some(other) = local(&link.item, &link.account) {
+                    union(&mut parents, item_index, other);
+                }
+            }
+        }
+    }
+    for (tree, root) in account.collections.iter().enumerate() {
+        let descendants = root.iter_descendants().map(|(_, collection)| collection);
+        for collection in std::iter::once(root).chain(descendants) {
+            for link in &collection.items {
+                if let Some(item) = local(&link.item, &link.account) {
+                    union(&mut parents, items + tree, item);
+                }
+            }
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut roots: HashMap<usize, usize> = HashMap::new();
+    for node in 0..parents.len() {
+        let root = find(&mut parents, node);
+        let cluster = *roots.entry(root).or_insert_with(|| {
+            clusters.push(Cluster {
+                trees: Vec::new(),
+                items: Vec::new(),
+            });
+            clusters.len() - 1
+        });
+        if node < items {
+            clusters[cluster].items.push(node);
+        } else {
+            clusters[cluster].trees.push(node - items);
+        }
+    }
+    clusters
+}
+
+fn find(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[a.max(b)] = a.min(b);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::format::fixtures::{self, account, collection, header, id, item, link, link_to};
+
+    /// Items whose titles make them large enough to be split.
+    fn items(ids: impl IntoIterator<Item = u8>) -> serde_json::Value {
+        ids.into_iter()
+            .map(|value| item(value, &"x".repeat(40), json!([])))
+            .collect()
+    }
+
+    /// An account with an item `item` and a collection linking to the item `link.1` of the account
+    /// `link.0`.
+    fn linking_account(account: u8, item: u8, link: (u8, u8)) -> Account {
+        fixtures::account(
+            account,
+            json!([fixtures::item(item, "", json!([]))]),
+            json!([collection(
+                item,
+                json!([link_to(link.1, link.0)]),
+                json!([])
+            )]),
+        )
+    }
+
+    fn item_ids(account: &Account) -> Vec<B64Url> {
+        account.items.iter().map(|item| item.id.clone()).collect()
+    }
+
+    fn first_link(account: &Account) -> (B64Url, Option<B64Url>) {
+        let link = &account.collections[0].items[0];
+        (link.item.clone(), link.account.clone())
+    }
+
+    #[test]
+    fn split_and_combine() {
+        let header = header(
+            0,
+            vec![
+                account(1, items(1..=10), json!([])),
+                account(2, items(11..=20), json!([])),
+            ],
+        );
+        let max_bytes = 600;
+
+        let parts = header.split(max_bytes).unwrap();
+        assert!(parts.len() > 2);
+        for part in &parts {
+            assert!(serde_json::to_vec(part).unwrap().len() <= max_bytes);
+            assert_eq!(part.exporter, header.exporter);
+        }
+
+        assert_eq!(Header::combine(parts).unwrap(), header);
+    }
+
+    #[test]
+    fn split_keeps_linked_items_together() {
+        let header = header(
+            0,
+            vec![account(
+                1,
+                items(1..=10),
+                json!([collection(1, json!([link(2), link(9)]), json!([]))]),
+            )],
+        );
+
+        let parts = header.split(600).unwrap();
+        assert!(parts.len() > 1);
+        for part in parts {
+            let account = &part.accounts[0];
+            let ids = item_ids(account);
+            if !account.collections.is_empty() {
+                assert!(ids.contains(&id(2)) && ids.contains(&id(9)));
+            } else {
+                assert!(!ids.contains(&id(2)) && !ids.contains(&id(9)));
+            }
+        }
+    }
+
+    #[test]
+    fn split_without_accounts() {
+        let header = header(0, Vec::new());
+        let parts = header.split(1).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].accounts.is_empty());
+        assert_eq!(Header::<()>::combine(Vec::new()), None);
+    }
+
+    #[test]
+    fn split_by_account() {
+        let header = header(
+            0,
+            vec![
+                account(1, items(1..=2), json!([])),
+                account(2, items(3..=4), json!([])),
+            ],
+        );
+
+        let parts = header.split_by_account();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].accounts[0].id, id(1));
+        assert_eq!(item_ids(&parts[0].accounts[0]), [id(1), id(2)]);
+        assert_eq!(parts[1].accounts[0].id, id(2));
+        assert_eq!(item_ids(&parts[1].accounts[0]), [id(3), id(4)]);
+    }
+
+    #[test]
+    fn add_account_updates_links_to_a_renamed_account() {
+        let mut header = header(0, Vec::new());
+
+        // The first account links to the item of the second one, which has the same id.
+        assert_eq!(header.add_account(linking_account(1, 1, (1, 2))), id(1));
+        let added = header.add_account(linking_account(1, 2, (1, 2)));
+        assert_ne!(added, id(1));
+
+        assert_eq!(
+            first_link(&header.accounts[0]),
+            (id(2), Some(added.clone()))
+        );
+        assert_eq!(first_link(&header.accounts[1]), (id(2), Some(added)));
+    }
+}
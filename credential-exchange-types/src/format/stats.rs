@@ -0,0 +1,113 @@
+//! # Statistics
+//!
+//! Counts the content of an exchange, e.g. for consent screens and import summaries.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+};
+
+use serde::Serialize;
+
+use super::{Account, Credential, CredentialType, Header};
+use crate::{B64Url, RpId};
+
+/// The statistics of an [Account] or of a whole [Header], as returned by [Account::stats] and
+/// [Header::stats].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of [Items][super::Item].
+    pub items: usize,
+    /// The number of credentials of each type.
+    pub credential_types: HashMap<CredentialType, usize>,
+    /// The number of items linked from each [Collection][super::Collection], at any depth, by
+    /// collection id. Items of sub-collections are not counted in their parents.
+    pub collections: BTreeMap<B64Url, usize>,
+    /// The number of items holding at least one [FileCredential][super::FileCredential].
+    pub items_with_files: usize,
+    /// The number of passkeys of each relying party.
+    pub passkeys: BTreeMap<RpId, usize>,
+    /// The estimated size in bytes of the exchange: the length of its compact JSON serialization
+    /// and the [decrypted size][super::FileCredential::decrypted_size] of its files. This is
+    /// [None] if the extensions could not be serialized.
+    pub estimated_size: Option<u64>,
+}
+
+impl Stats {
+    fn add<E: Serialize>(&mut self, account: &Account<E>) {
+        self.items += account.items.len();
+        for item in &account.items {
+            let mut has_files = false;
+            for credential in &item.credentials {
+                *self
+                    .credential_types
+                    .entry(credential.credential_type())
+                    .or_default() += 1;
+                match credential {
+                    Credential::Passkey(passkey) => {
+                        *self.passkeys.entry(passkey.rp_id.clone()).or_default() += 1;
+                    }
+                    Credential::File(file) => {
+                        has_files = true;
+                        self.estimated_size = self
+                            .estimated_size
+                            .map(|size| size.saturating_add(file.decrypted_size));
+                    }
+                    _ => {}
+                }
+            }
+            self.items_with_files += usize::from(has_files);
+        }
+
+        for (_, collection) in account.iter_all_collections() {
+            *self.collections.entry(collection.id.clone()).or_default() += collection.items.len();
+        }
+
+        let mut counter = ByteCounter(0);
+        self.estimated_size = serde_json::to_writer(&mut counter, account)
+            .ok()
+            .and(self.estimated_size)
+            .map(|size| size.saturating_add(counter.0));
+    }
+}
+
+impl<E: Serialize> Account<E> {
+    /// Count the items, credentials, collections and passkeys of this [Account].
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            estimated_size: Some(0),
+            ..Stats::default()
+        };
+        stats.add(self);
+        stats
+    }
+}
+
+impl<E: Serialize> Header<E> {
+    /// Count the items, credentials, collections and passkeys of all the [Accounts][Account] of
+    /// this exchange.
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            estimated_size: Some(0),
+            ..Stats::default()
+        };
+        for account in &self.accounts {
+            stats.add(account);
+        }
+        stats
+    }
+}
+
+/// Counts the bytes written to it without storing them.
+struct ByteCounter(u64);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
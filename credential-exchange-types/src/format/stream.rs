@@ -0,0 +1,706 @@
+//! # Streaming
+//!
+//! Reads and writes exchanges incrementally, so that providers can import or export vaults of
+//! hundreds of thousands of items while only holding one [Item] in memory at a time, instead of
+//! the whole [Header].
+
+use std::io::{self, BufReader, Bytes, Read, Write};
+
+use serde::{
+    de::{DeserializeOwned, Error as _},
+    Serialize,
+};
+use serde_json::{Map, Value};
+
+use super::{Account, Header, Item};
+
+/// A part of an exchange yielded by [HeaderStream].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StreamEvent<E = ()> {
+    /// The start of an [Account], with the members preceding its `items` in the document. Its
+    /// [items][Account::items] are always empty, and so are its
+    /// [collections][Account::collections] if they follow the items.
+    AccountStart(Account<E>),
+    /// An [Item] of the last started account.
+    Item(Item<E>),
+    /// The end of the last started account, with all its members except the items.
+    AccountEnd(Account<E>),
+}
+
+/// An iterator over the accounts and items of a JSON exchange read from an [io::Read], created by
+/// [HeaderStream::from_reader].
+///
+/// The members of the [Account]s which are required, e.g. the `id`, must precede their `items`,
+/// which is the case of the documents serialized by this crate. The remaining members of the
+/// [Header] are returned by [HeaderStream::finish]. The iteration stops after the first error.
+pub struct HeaderStream<R, E = ()> {
+    reader: ByteReader<R>,
+    state: State,
+    header: Map<String, Value>,
+    account: Map<String, Value>,
+    pending: Option<StreamEvent<E>>,
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Start,
+    HeaderMembers { first: bool },
+    Accounts { first: bool },
+    AccountMembers { first: bool, started: bool },
+    Items { first: bool },
+    Done,
+}
+
+impl<R: Read, E: DeserializeOwned> HeaderStream<R, E> {
+    /// Stream the exchange read from `reader`, which is buffered internally.
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            reader: ByteReader::new(reader),
+            state: State::Start,
+            header: Map::new(),
+            account: Map::new(),
+            pending: None,
+        }
+    }
+
+    /// Read the rest of the exchange, discarding the events which weren't consumed, and return
+    /// its members other than the accounts. The [accounts][Header::accounts] of the returned
+    /// [Header] are empty.
+    pub fn finish(mut self) -> Result<Header<E>, serde_json::Error> {
+        for event in &mut self {
+            event?;
+        }
+        let mut header = std::mem::take(&mut self.header);
+        header.insert("accounts".to_owned(), Value::Array(Vec::new()));
+        serde_json::from_value(Value::Object(header))
+    }
+
+    fn next_event(&mut self) -> Result<Option<StreamEvent<E>>, serde_json::Error> {
+        if let Some(event) = self.pending.take() {
+            return Ok(Some(event));
+        }
+        loop {
+            match self.state {
+                State::Start => {
+                    self.reader.expect(b'{')?;
+                    self.state = State::HeaderMembers { first: true };
+                }
+                State::HeaderMembers { first } => {
+                    if self.reader.close(b'}')? {
+                        self.reader.end()?;
+                        self.state = State::Done;
+                        return Ok(None);
+                    }
+                    let key = self.reader.key(first)?;
+                    if key == "accounts" {
+                        self.reader.expect(b'[')?;
+                        self.state = State::Accounts { first: true };
+                    } else {
+                        let value = serde_json::from_slice(&self.reader.value()?)?;
+                        self.header.insert(key, value);
+                        self.state = State::HeaderMembers { first: false };
+                    }
+                }
+                State::Accounts { first } => {
+                    if self.reader.close(b']')? {
+                        self.state = State::HeaderMembers { first: false };
+                        continue;
+                    }
+                    if !first {
+                        self.reader.expect(b',')?;
+                    }
+                    self.reader.expect(b'{')?;
+                    self.account = Map::new();
+                    self.state = State::AccountMembers {
+                        first: true,
+                        started: false,
+                    };
+                }
+                State::AccountMembers { first, started } => {
+                    if self.reader.close(b'}')? {
+                        self.state = State::Accounts { first: false };
+                        let start = if started {
+                            None
+                        } else {
+                            Some(self.account_start()?)
+                        };
+                        let mut account = std::mem::take(&mut self.account);
+                        account.insert("items".to_owned(), Value::Array(Vec::new()));
+                        let end = StreamEvent::AccountEnd(serde_json::from_value(account.into())?);
+                        let Some(start) = start else {
+                            return Ok(Some(end));
+                        };
+                        self.pending = Some(end);
+                        return Ok(Some(start));
+                    }
+                    let key = self.reader.key(first)?;
+                    if key == "items" {
+                        self.reader.expect(b'[')?;
+                        self.state = State::Items { first: true };
+                        return self.account_start().map(Some);
+                    }
+                    let value = serde_json::from_slice(&self.reader.value()?)?;
+                    self.account.insert(key, value);
+                    self.state = State::AccountMembers {
+                        first: false,
+                        started,
+                    };
+                }
+                State::Items { first } => {
+                    if self.reader.close(b']')? {
+                        self.state = State::AccountMembers {
+                            first: false,
+                            started: true,
+                        };
+                        continue;
+                    }
+                    if !first {
+                        self.reader.expect(b',')?;
+                    }
+                    let item = serde_json::from_slice(&self.reader.value()?)?;
+                    self.state = State::Items { first: false };
+                    return Ok(Some(StreamEvent::Item(item)));
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+
+    /// The [StreamEvent::AccountStart] of the members of the current account read so far.
+    fn account_start(&self) -> Result<StreamEvent<E>, serde_json::Error> {
+        let mut account = self.account.clone();
+        account.insert("items".to_owned(), Value::Array(Vec::new()));
+        account
+            .entry("collections")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        Ok(StreamEvent::AccountStart(serde_json::from_value(
+            account.into(),
+        )?))
+    }
+}
+
+impl<R: Read, E: DeserializeOwned> Iterator for HeaderStream<R, E> {
+    type Item = Result<StreamEvent<E>, serde_json::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.next_event();
+        if event.is_err() {
+            self.state = State::Done;
+            self.pending = None;
+        }
+        event.transpose()
+    }
+}
+
+/// Writes a JSON exchange to an [io::Write] one account and one item at a time, the counterpart
+/// of [HeaderStream].
+///
+/// The output is the compact JSON of the equivalent [Header], with the members in the same order
+/// as [Header::to_json_compact]. It is only complete once [HeaderWriter::finish] has been called.
+pub struct HeaderWriter<W, E = ()> {
+    writer: W,
+    first_account: bool,
+    account: Option<OpenAccount>,
+    trailing: Vec<Member>,
+    _extensions: std::marker::PhantomData<E>,
+}
+
+/// The state of the account being written by a [HeaderWriter].
+struct OpenAccount {
+    first_item: bool,
+    trailing: Vec<Member>,
+}
+
+/// The name and raw compact JSON value of a member of an object.
+type Member = (String, Vec<u8>);
+
+impl<W: Write, E: Serialize> HeaderWriter<W, E> {
+    /// Start writing the exchange `header` to `writer`, which should be buffered. The accounts of
+    /// `header` are written first, followed by those started with [HeaderWriter::start_account].
+    pub fn new(writer: W, header: &Header<E>) -> Result<Self, serde_json::Error> {
+        let (leading, accounts, trailing) = split_members(header, "accounts")?;
+        let mut this = Self {
+            writer,
+            first_account: true,
+            account: None,
+            trailing,
+            _extensions: std::marker::PhantomData,
+        };
+        this.write(b"{")?;
+        this.first_account = this.open(leading, "accounts", accounts)?;
+        Ok(this)
+    }
+
+    /// Start writing `account`, after ending the previous one. Its [items][Account::items] are
+    /// written first, followed by those passed to [HeaderWriter::item] until the next account is
+    /// started.
+    pub fn start_account(&mut self, account: &Account<E>) -> Result<(), serde_json::Error> {
+        self.end_account()?;
+        if !std::mem::replace(&mut self.first_account, false) {
+            self.write(b",")?;
+        }
+        let (leading, items, trailing) = split_members(account, "items")?;
+        self.write(b"{")?;
+        let first_item = self.open(leading, "items", items)?;
+        self.account = Some(OpenAccount {
+            first_item,
+            trailing,
+        });
+        Ok(())
+    }
+
+    /// Write an [Item] of the account started last. This fails if no account was started.
+    pub fn item(&mut self, item: &Item<E>) -> Result<(), serde_json::Error> {
+        let Some(account) = &mut self.account else {
+            return Err(serde_json::Error::custom("no account was started"));
+        };
+        if !std::mem::replace(&mut account.first_item, false) {
+            self.write(b",")?;
+        }
+        serde_json::to_writer(&mut self.writer, item)
+    }
+
+    /// Write the end of the exchange, and return the flushed writer.
+    pub fn finish(mut self) -> Result<W, serde_json::Error> {
+        self.end_account()?;
+        let trailing = std::mem::take(&mut self.trailing);
+        self.close(trailing)?;
+        self.writer.flush().map_err(serde_json::Error::io)?;
+        Ok(self.writer)
+    }
+
+    /// Write the end of the account started last, if any.
+    fn end_account(&mut self) -> Result<(), serde_json::Error> {
+        match self.account.take() {
+            Some(account) => self.close(account.trailing),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the `leading` members of an object, then the `key` of its streamed array and the
+    /// elements it already holds, from the raw `array`. Returns whether there are no elements.
+    fn open(
+        &mut self,
+        leading: Vec<Member>,
+        key: &str,
+        array: Vec<u8>,
+    ) -> Result<bool, serde_json::Error> {
+        for (name, value) in &leading {
+            self.member(name, value)?;
+            self.write(b",")?;
+        }
+        serde_json::to_writer(&mut self.writer, key)?;
+        self.write(b":")?;
+        // The array is left open for the elements written afterwards.
+        self.write(&array[..array.len() - 1])?;
+        Ok(array == b"[]")
+    }
+
+    /// Write the end of a streamed array, then the `trailing` members of its object.
+    fn close(&mut self, trailing: Vec<Member>) -> Result<(), serde_json::Error> {
+        self.write(b"]")?;
+        for (name, value) in &trailing {
+            self.write(b",")?;
+            self.member(name, value)?;
+        }
+        self.write(b"}")
+    }
+
+    fn member(&mut self, name: &str, value: &[u8]) -> Result<(), serde_json::Error> {
+        serde_json::to_writer(&mut self.writer, name)?;
+        self.write(b":")?;
+        self.write(value)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), serde_json::Error> {
+        self.writer.write_all(bytes).map_err(serde_json::Error::io)
+    }
+}
+
+/// Serialize `value` as a compact JSON object, and split its members around the array member
+/// `key`, keeping the order in which they are serialized. The raw array is `[]` if `key` is
+/// missing.
+#[allow(clippy::type_complexity)]
+fn split_members(
+    value: &impl Serialize,
+    key: &str,
+) -> Result<(Vec<Member>, Vec<u8>, Vec<Member>), serde_json::Error> {
+    let json = serde_json::to_vec(value)?;
+    let mut reader = ByteReader::new(json.as_slice());
+    reader.expect(b'{')?;
+    let mut leading = Vec::new();
+    let mut array = None;
+    let mut trailing = Vec::new();
+    let mut first = true;
+    while !reader.close(b'}')? {
+        let name = reader.key(std::mem::replace(&mut first, false))?;
+        let member = reader.value()?;
+        if name == key {
+            if member.first() != Some(&b'[') {
+                return Err(serde_json::Error::custom(format!(
+                    "expected `{key}` to be an array"
+                )));
+            }
+            array = Some(member);
+        } else if array.is_none() {
+            leading.push((name, member));
+        } else {
+            trailing.push((name, member));
+        }
+    }
+    reader.end()?;
+    Ok((leading, array.unwrap_or_else(|| b"[]".to_vec()), trailing))
+}
+
+/// Reads the structure of a JSON document byte by byte, leaving the values to [serde_json].
+struct ByteReader<R> {
+    bytes: Bytes<BufReader<R>>,
+    peeked: Option<u8>,
+    offset: usize,
+}
+
+impl<R: Read> ByteReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            peeked: None,
+            offset: 0,
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, serde_json::Error> {
+        if self.peeked.is_none() {
+            self.peeked = self
+                .bytes
+                .next()
+                .transpose()
+                .map_err(serde_json::Error::io)?;
+        }
+        Ok(self.peeked)
+    }
+
+    fn bump(&mut self) -> Result<u8, serde_json::Error> {
+        let byte = self
+            .peek()?
+            .ok_or_else(|| serde_json::Error::io(io::Error::from(io::ErrorKind::UnexpectedEof)))?;
+        self.peeked = None;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), serde_json::Error> {
+        while self.peek()?.is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.bump()?;
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), serde_json::Error> {
+        self.skip_whitespace()?;
+        let offset = self.offset;
+        if self.bump()? != expected {
+            return Err(serde_json::Error::custom(format!(
+                "expected `{}` at byte {offset}",
+                char::from(expected)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Consume `closing` if it is the next structural byte.
+    fn close(&mut self, closing: u8) -> Result<bool, serde_json::Error> {
+        self.skip_whitespace()?;
+        if self.peek()? == Some(closing) {
+            self.bump()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Read the key of the next member of an object, and the colon following it.
+    fn key(&mut self, first: bool) -> Result<String, serde_json::Error> {
+        if !first {
+            self.expect(b',')?;
+        }
+        self.skip_whitespace()?;
+        let mut raw = Vec::new();
+        self.string(&mut raw)?;
+        self.expect(b':')?;
+        serde_json::from_slice(&raw)
+    }
+
+    /// Read the raw bytes of the next value.
+    fn value(&mut self) -> Result<Vec<u8>, serde_json::Error> {
+        self.skip_whitespace()?;
+        let mut raw = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                Some(b'"') => self.string(&mut raw)?,
+                Some(b'{' | b'[') => {
+                    raw.push(self.bump()?);
+                    depth += 1;
+                }
+                Some(b'}' | b']') if depth > 0 => {
+                    raw.push(self.bump()?);
+                    depth -= 1;
+                }
+                Some(b',' | b'}' | b']') | None if depth == 0 => break,
+                Some(byte) if depth == 0 && byte.is_ascii_whitespace() => break,
+                _ => raw.push(self.bump()?),
+            }
+            if depth == 0 && matches!(raw.last(), Some(b'"' | b'}' | b']')) {
+                break;
+            }
+        }
+        Ok(raw)
+    }
+
+    /// Read a string, including its quotes, without decoding its escape sequences.
+    fn string(&mut self, raw: &mut Vec<u8>) -> Result<(), serde_json::Error> {
+        if self.peek()? != Some(b'"') {
+            return Err(serde_json::Error::custom(format!(
+                "expected a string at byte {}",
+                self.offset
+            )));
+        }
+        raw.push(self.bump()?);
+        loop {
+            let byte = self.bump()?;
+            raw.push(byte);
+            match byte {
+                b'\\' => raw.push(self.bump()?),
+                b'"' => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Check that nothing but whitespace follows the document.
+    fn end(&mut self) -> Result<(), serde_json::Error> {
+        self.skip_whitespace()?;
+        if self.peek()?.is_some() {
+            return Err(serde_json::Error::custom(format!(
+                "trailing characters at byte {}",
+                self.offset
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn header(accounts: Value) -> Header {
+        serde_json::from_value(json!({
+            "version": 0,
+            "exporter": "exporter.example.com",
+            "timestamp": 1705228800,
+            "accounts": accounts,
+            "extensions": [{ "name": "future-extension" }],
+        }))
+        .expect("the header is valid")
+    }
+
+    fn account(id: &str, items: Value) -> Account {
+        serde_json::from_value(json!({
+            "id": id,
+            "userName": "jane.doe",
+            "email": "jane.doe@example.com",
+            "collections": [],
+            "items": items,
+            "futureAccountMember": 1,
+        }))
+        .expect("the account is valid")
+    }
+
+    fn item(id: &str) -> Item {
+        serde_json::from_value(json!({
+            "id": id,
+            "type": "login",
+            "title": "Example",
+            "credentials": [],
+            "futureItemMember": { "b": 1, "a": 2 },
+        }))
+        .expect("the item is valid")
+    }
+
+    fn stream(json: &[u8]) -> Result<Vec<StreamEvent>, serde_json::Error> {
+        HeaderStream::from_reader(json).collect()
+    }
+
+    #[test]
+    fn members_around_accounts_and_items() {
+        let json = br#"{
+            "version": 0,
+            "exporter": "exporter.example.com",
+            "accounts": [{
+                "id": "AQ",
+                "userName": "jane.doe",
+                "email": "jane.doe@example.com",
+                "items": [
+                    { "id": "Ag", "type": "login", "title": "Example", "credentials": [] }
+                ],
+                "collections": [{ "id": "Aw", "title": "Work", "items": [{ "item": "Ag" }] }]
+            }],
+            "timestamp": 1705228800
+        }"#;
+
+        let events = stream(json).unwrap();
+        let StreamEvent::AccountStart(start) = &events[0] else {
+            panic!("expected the start of an account");
+        };
+        assert_eq!(start.user_name, "jane.doe");
+        assert!(start.collections.is_empty());
+        assert!(matches!(&events[1], StreamEvent::Item(item) if item.id.as_ref() == [2]));
+        let StreamEvent::AccountEnd(end) = &events[2] else {
+            panic!("expected the end of an account");
+        };
+        assert!(end.items.is_empty());
+        assert_eq!(end.collections.len(), 1);
+        assert_eq!(events.len(), 3);
+
+        let header = HeaderStream::<_>::from_reader(&json[..]).finish().unwrap();
+        assert_eq!(header.exporter.as_str(), "exporter.example.com");
+        assert_eq!(header.timestamp.timestamp(), 1705228800);
+        assert!(header.accounts.is_empty());
+    }
+
+    #[test]
+    fn whitespace_and_escaped_quotes() {
+        let json = b" \n{ \"version\" :0 ,\t\"exporter\":\"exporter.example.com\",\
+            \"timestamp\": 1705228800 , \"fu\\\"ture}\" : [\"]\\\\\", { \"a\" : \"},\" }] ,\
+            \"accounts\" : [ { \"id\" : \"AQ\" , \"userName\" : \"\\\"jane\\\" {doe}\" ,\
+            \"email\":\"jane.doe@example.com\" , \"items\" : [ { \"id\" : \"Ag\" ,\
+            \"type\" : \"login\" , \"title\" : \"[\\\"Example\\\"]\" , \"credentials\" : [ ] } ]\
+            , \"collections\" : [ ] } ] } \r\n";
+
+        let events = stream(json).unwrap();
+        let StreamEvent::AccountStart(start) = &events[0] else {
+            panic!("expected the start of an account");
+        };
+        assert_eq!(start.user_name, "\"jane\" {doe}");
+        let StreamEvent::Item(item) = &events[1] else {
+            panic!("expected an item");
+        };
+        assert_eq!(item.title, "[\"Example\"]");
+        assert!(matches!(events[2], StreamEvent::AccountEnd(_)));
+
+        let header = HeaderStream::<_>::from_reader(&json[..]).finish().unwrap();
+        assert_eq!(header.extra["fu\"ture}"], json!(["]\\", { "a": "}," }]));
+    }
+
+    #[test]
+    fn account_without_items() {
+        let json = br#"{"version":0,"exporter":"exporter.example.com","timestamp":1705228800,
+            "accounts":[{"id":"AQ","userName":"jane.doe","email":"jane.doe@example.com",
+            "collections":[]},{"id":"Ag","userName":"john.doe","email":"john.doe@example.com",
+            "collections":[],"items":[]}]}"#;
+
+        let events = stream(json).unwrap();
+        let ids: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                StreamEvent::AccountStart(account) => ("start", account.id.as_ref()[0]),
+                StreamEvent::AccountEnd(account) => ("end", account.id.as_ref()[0]),
+                StreamEvent::Item(_) => ("item", 0),
+            })
+            .collect();
+        assert_eq!(ids, [("start", 1), ("end", 1), ("start", 2), ("end", 2)]);
+    }
+
+    #[test]
+    fn empty_accounts() {
+        let json =
+            br#"{"version":0,"exporter":"exporter.example.com","timestamp":0,"accounts":[]}"#;
+        assert!(stream(json).unwrap().is_empty());
+    }
+
+    #[test]
+    fn trailing_garbage() {
+        let json =
+            br#"{"version":0,"exporter":"exporter.example.com","timestamp":0,"accounts":[]} x"#;
+        assert!(stream(json).is_err());
+        assert!(HeaderStream::<_>::from_reader(&json[..]).finish().is_err());
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let truncated = br#"{"version":0,"accounts":[{"id":"AQ","items":[{"id":"#;
+        let missing_comma = br#"{"version":0 "accounts":[]}"#;
+        let not_an_object = br#"["version"]"#;
+        let unquoted_key = br#"{version:0}"#;
+        for json in [&truncated[..], missing_comma, not_an_object, unquoted_key] {
+            assert!(stream(json).is_err(), "{}", String::from_utf8_lossy(json));
+        }
+
+        // The iteration stops after the first error.
+        let mut stream = HeaderStream::<_>::from_reader(&truncated[..]);
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn writer_output_is_read_back_by_stream() {
+        let written = account("AQ", json!([item("Ag")]));
+        let mut writer = HeaderWriter::new(Vec::new(), &header(json!([written]))).unwrap();
+        let streamed = account("Aw", json!([item("BA")]));
+        writer.start_account(&streamed).unwrap();
+        writer.item(&item("BQ")).unwrap();
+        let json = writer.finish().unwrap();
+
+        let mut expected = header(json!([written, streamed]));
+        expected.accounts[1].items.push(item("BQ"));
+        assert_eq!(
+            String::from_utf8(json.clone()).unwrap(),
+            expected.to_json_compact().unwrap()
+        );
+
+        // The unknown members are serialized after the items, so they are only part of the end.
+        let end = |id| account(id, json!([]));
+        let start = |id| Account {
+            extra: Map::new(),
+            ..end(id)
+        };
+        assert_eq!(
+            stream(&json).unwrap(),
+            [
+                StreamEvent::AccountStart(start("AQ")),
+                StreamEvent::Item(item("Ag")),
+                StreamEvent::AccountEnd(end("AQ")),
+                StreamEvent::AccountStart(start("Aw")),
+                StreamEvent::Item(item("BA")),
+                StreamEvent::Item(item("BQ")),
+                StreamEvent::AccountEnd(end("Aw")),
+            ]
+        );
+        assert_eq!(
+            HeaderStream::<_>::from_reader(json.as_slice())
+                .finish()
+                .unwrap(),
+            header(json!([]))
+        );
+    }
+
+    #[test]
+    fn writer_without_accounts() {
+        let json = HeaderWriter::new(Vec::new(), &header(json!([])))
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            header(json!([])).to_json_compact().unwrap()
+        );
+    }
+
+    #[test]
+    fn item_without_account() {
+        let mut writer = HeaderWriter::new(Vec::new(), &header(json!([]))).unwrap();
+        assert!(writer.item(&item("AQ")).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! # Summaries
+//!
+//! Short one-line descriptions of [Items][Item] and [Credentials][Credential] through their
+//! [Display] implementation, e.g. `Login: alice@example.com @ github.com`. Summaries never contain
+//! secrets such as passwords, keys or full card numbers, and are suitable for consent dialogs,
+//! command line output and logs.
+//!
+//! [Item::derived_subtitle] follows the same rules to produce the subtitles shown below the titles
+//! of items in password managers.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{Credential, Item, ItemType};
+use crate::{idna::domain_to_unicode, uri::Origin};
+
+impl<E> Display for Item<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.ty {
+            ItemType::Login => write!(f, "Login: {}", self.title),
+            ItemType::Document => write!(f, "Document: {}", self.title),
+            ItemType::Identity => write!(f, "Identity: {}", self.title),
+            ItemType::Unknown(ty) => write!(f, "{ty}: {}", self.title),
+        }
+    }
+}
+
+impl Display for Credential {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Credential::BasicAuth(credential) => {
+                let username = credential.username.as_ref().map(|field| &field.value);
+                let host = credential.urls.first().map(|url| {
+                    Origin::parse(url.as_str()).map_or_else(
+                        || url.to_string(),
+                        |origin| domain_to_unicode(origin.host()),
+                    )
+                });
+                match (username, host) {
+                    (Some(username), Some(host)) => write!(f, "Login: {username} @ {host}"),
+                    (Some(username), None) => write!(f, "Login: {username}"),
+                    (None, Some(host)) => write!(f, "Login: {host}"),
+                    (None, None) => f.write_str("Login"),
+                }
+            }
+            Credential::Passkey(passkey) => {
+                write!(f, "Passkey: {} @ rp={}", passkey.user_name, passkey.rp_id)
+            }
+            Credential::CreditCard(card) => {
+                f.write_str("Card")?;
+                if let Some(card_type) = &card.card_type {
+                    write!(f, ": {card_type}")?;
+                }
+                if let Some(last) = card.last_digits() {
+                    write!(f, " ending in {last}")?;
+                }
+                Ok(())
+            }
+            Credential::Note(_) => f.write_str("Note"),
+            Credential::Totp(totp) => match &totp.issuer {
+                Some(issuer) => write!(f, "TOTP: {issuer} ({})", totp.username),
+                None => write!(f, "TOTP: {}", totp.username),
+            },
+            Credential::DriversLicense(_) => f.write_str("Driver's license"),
+            Credential::Address(_) => f.write_str("Address"),
+            Credential::ItemReference(reference) => {
+                write!(f, "Reference: {}", reference.reference.item)
+            }
+            Credential::File(file) => write!(f, "File: {}", file.name),
+            Credential::SshKey(key) => write!(f, "SSH key: {}", key.key_type),
+            Credential::IdentityDocument(_) => f.write_str("Identity document"),
+            Credential::Passport(_) => f.write_str("Passport"),
+            Credential::PersonName(_) => f.write_str("Name"),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::BirthCertificate(_) => f.write_str("Birth certificate"),
+            #[cfg(feature = "non-standard-credentials")]
+            Credential::InsurancePolicy(policy) => match &policy.insurer {
+                Some(insurer) => write!(f, "Insurance: {}", insurer.value),
+                None => f.write_str("Insurance"),
+            },
+            Credential::Unknown { ty, .. } => write!(f, "Unknown: {ty}"),
+        }
+    }
+}
+
+impl<E> Item<E> {
+    /// Derive a subtitle from the first credential of this item which provides one, following the
+    /// conventions of the major providers:
+    ///
+    /// - the username of a [login][Credential::BasicAuth], e.g. `alice@example.com`,
+    /// - the type and last digits of a [card][Credential::CreditCard], e.g. `Visa, *1234`,
+    /// - the relying party of a [passkey][Credential::Passkey], e.g. `example.com`,
+    /// - the username or issuer of a [TOTP][Credential::Totp],
+    /// - the first line of an [address][Credential::Address],
+    /// - the name of a [file][Credential::File].
+    ///
+    /// Like summaries, subtitles never contain secrets.
+    pub fn derived_subtitle(&self) -> Option<String> {
+        self.credentials.iter().find_map(|credential| {
+            let subtitle = match credential {
+                Credential::BasicAuth(credential) => {
+                    credential.username.as_ref()?.value.trim().to_owned()
+                }
+                Credential::CreditCard(card) => {
+                    let last = card.last_digits().map(|last| format!("*{last}"));
+                    match (card.card_type.as_deref().map(str::trim), last) {
+                        (Some(card_type), Some(last)) if !card_type.is_empty() => {
+                            format!("{card_type}, {last}")
+                        }
+                        (_, Some(last)) => last,
+                        (card_type, None) => card_type?.to_owned(),
+                    }
+                }
+                Credential::Passkey(passkey) => domain_to_unicode(passkey.rp_id.as_str()),
+                Credential::Totp(totp) => match totp.username.trim() {
+                    "" => totp.issuer.as_deref()?.trim().to_owned(),
+                    username => username.to_owned(),
+                },
+                Credential::Address(address) => address.address_lines().first()?.to_string(),
+                Credential::File(file) => file.name.trim().to_owned(),
+                _ => return None,
+            };
+            (!subtitle.is_empty()).then_some(subtitle)
+        })
+    }
+
+    /// Set the [subtitle][Item::subtitle] of this item to its [derived
+    /// subtitle][Item::derived_subtitle], unless it already has one.
+    pub fn fill_subtitle(&mut self) {
+        if self.subtitle.is_none() {
+            self.subtitle = self.derived_subtitle();
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! # TOTP Codes
+//!
+//! Computes the one-time passwords of [TotpCredentials][TotpCredential] as specified by
+//! [RFC 6238](https://www.rfc-editor.org/rfc/rfc6238), so that importers can check that an
+//! imported seed produces the codes the user expects before committing the import.
+
+use chrono::{DateTime, Utc};
+use hmac::{digest::KeyInit, Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use super::{OTPHashAlgorithm, TotpCredential};
+
+impl TotpCredential {
+    /// Compute the code of this credential for the time step containing `at`, zero-padded to
+    /// [digits][TotpCredential::digits].
+    ///
+    /// Returns [None] if no code can be computed: for [unknown][OTPHashAlgorithm::Unknown]
+    /// algorithms, a period or a number of digits of zero, and times before the Unix epoch.
+    pub fn generate_code(&self, at: DateTime<Utc>) -> Option<String> {
+        if self.digits == 0 {
+            return None;
+        }
+        let counter = u64::try_from(at.timestamp())
+            .ok()?
+            .checked_div(self.period.into())?
+            .to_be_bytes();
+        let key = self.secret.as_ref();
+        let digest = match &self.algorithm {
+            OTPHashAlgorithm::Sha1 => sign::<Hmac<Sha1>>(key, &counter),
+            OTPHashAlgorithm::Sha256 => sign::<Hmac<Sha256>>(key, &counter),
+            OTPHashAlgorithm::Sha512 => sign::<Hmac<Sha512>>(key, &counter),
+            OTPHashAlgorithm::Unknown(_) => return None,
+        };
+
+        // Dynamic truncation, see https://www.rfc-editor.org/rfc/rfc4226#section-5.3
+        let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+        let truncated =
+            u32::from_be_bytes(digest[offset..offset + 4].try_into().ok()?) & 0x7fff_ffff;
+        let modulus = 10u64.checked_pow(self.digits.into()).unwrap_or(u64::MAX);
+        let digits = usize::from(self.digits);
+        Some(format!("{:0digits$}", u64::from(truncated) % modulus))
+    }
+}
+
+/// The HMAC of `message` with `key`.
+fn sign<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as KeyInit>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::B32;
+
+    fn totp(algorithm: OTPHashAlgorithm, seed: &[u8]) -> TotpCredential {
+        let mut totp = TotpCredential::new(B32::from(seed.to_vec()));
+        totp.algorithm = algorithm;
+        totp.digits = 8;
+        totp
+    }
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn rfc_6238_test_vectors() {
+        // See https://www.rfc-editor.org/rfc/rfc6238#appendix-B
+        let sha1 = totp(OTPHashAlgorithm::Sha1, b"12345678901234567890");
+        let sha256 = totp(
+            OTPHashAlgorithm::Sha256,
+            b"12345678901234567890123456789012",
+        );
+        let sha512 = totp(
+            OTPHashAlgorithm::Sha512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+        );
+        let vectors = [
+            (59, "94287082", "46119246", "90693936"),
+            (1111111109, "07081804", "68084774", "25091201"),
+            (1111111111, "14050471", "67062674", "99943326"),
+            (1234567890, "89005924", "91819424", "93441116"),
+            (2000000000, "69279037", "90698825", "38618901"),
+            (20000000000, "65353130", "77737706", "47863826"),
+        ];
+        for (seconds, expected_sha1, expected_sha256, expected_sha512) in vectors {
+            assert_eq!(sha1.generate_code(at(seconds)).unwrap(), expected_sha1);
+            assert_eq!(sha256.generate_code(at(seconds)).unwrap(), expected_sha256);
+            assert_eq!(sha512.generate_code(at(seconds)).unwrap(), expected_sha512);
+        }
+    }
+
+    #[test]
+    fn zero_padding() {
+        let mut sha1 = totp(OTPHashAlgorithm::Sha1, b"12345678901234567890");
+        sha1.digits = 6;
+        assert_eq!(sha1.generate_code(at(1111111109)).unwrap(), "081804");
+    }
+
+    #[test]
+    fn no_code() {
+        let mut totp = totp(OTPHashAlgorithm::Sha1, b"12345678901234567890");
+        assert_eq!(totp.generate_code(at(-1)), None);
+
+        totp.period = 0;
+        assert_eq!(totp.generate_code(at(59)), None);
+
+        totp.period = 30;
+        totp.digits = 0;
+        assert_eq!(totp.generate_code(at(59)), None);
+
+        totp.digits = 6;
+        totp.algorithm = OTPHashAlgorithm::Unknown("MD5".to_owned());
+        assert_eq!(totp.generate_code(at(59)), None);
+    }
+}
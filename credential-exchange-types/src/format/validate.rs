@@ -0,0 +1,618 @@
+//! # Validation
+//!
+//! Checks the constraints of the specification which the types of this crate don't enforce, such
+//! as the size of identifiers or the format of field values. Every violation is reported with its
+//! location, so that importers can show the user all the problems of a document at once instead of
+//! failing on the first one.
+
+use std::{fmt, ops::RangeInclusive};
+
+use super::{
+    parse_date, Account, Collection, Credential, DateParsing, EditableField, FieldType, Header,
+    Item, LinkedItem,
+};
+use crate::B64Url;
+
+/// The number of digits of a TOTP code: at least 6 as required by
+/// [RFC 4226](https://www.rfc-editor.org/rfc/rfc4226#section-5.3), and at most 10 as the
+/// truncated HMAC value is a 31-bit number.
+const TOTP_DIGITS: RangeInclusive<u8> = 6..=10;
+
+/// A constraint of the specification violated by a value of an exchange, see [Header::validate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// The [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) of the offending value in the
+    /// serialized exchange, e.g. `/accounts/0/items/2/credentials/0/digits`.
+    pub pointer: String,
+    /// The violated constraint.
+    pub kind: ValidationIssueKind,
+}
+
+/// The constraints checked by [Header::validate].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationIssueKind {
+    /// An identifier is longer than [B64Url::MAX_ID_LENGTH] bytes.
+    IdTooLong,
+    /// A required identifier, title or key is empty.
+    Empty,
+    /// A country isn't an [ISO 3166-1 alpha-2](https://www.iso.org/iso-3166-country-codes.html)
+    /// code, e.g. `DE`.
+    CountryCode,
+    /// A subdivision isn't an [ISO 3166-2](https://www.iso.org/iso-3166-country-codes.html) code,
+    /// e.g. `US-CA`.
+    SubdivisionCode,
+    /// The value of a [date][FieldType::Date] field isn't an RFC3339 `full-date`.
+    Date,
+    /// The [digits][super::TotpCredential::digits] of a TOTP credential are fewer than 6 or more
+    /// than 10.
+    TotpDigits,
+    /// The [period][super::TotpCredential::period] of a TOTP credential is zero.
+    TotpPeriod,
+    /// The [key][super::PasskeyCredential::key] of a passkey isn't a DER encoded PKCS#8 private
+    /// key.
+    PasskeyKey,
+    /// A [LinkedItem] refers to an item which isn't part of the exchange.
+    UnresolvedLink,
+    /// A URL of a [BasicAuthCredential][super::BasicAuthCredential] isn't a
+    /// [valid][crate::Uri::is_valid] URI reference.
+    Uri,
+    /// The [exporter][Header::exporter] or the [RP ID][super::PasskeyCredential::rp_id] of a
+    /// passkey isn't a [valid][crate::RpId::is_valid] relying party identifier.
+    RpId,
+    /// The [email][Account::email] of an account isn't a [valid][crate::Email::is_valid] address.
+    Email,
+}
+
+impl fmt::Display for ValidationIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValidationIssueKind::IdTooLong => "Identifier is longer than 64 bytes",
+            ValidationIssueKind::Empty => "Value is empty",
+            ValidationIssueKind::CountryCode => "Value isn't an ISO 3166-1 alpha-2 country code",
+            ValidationIssueKind::SubdivisionCode => "Value isn't an ISO 3166-2 subdivision code",
+            ValidationIssueKind::Date => "Value isn't a valid date",
+            ValidationIssueKind::TotpDigits => "Digits isn't between 6 and 10",
+            ValidationIssueKind::TotpPeriod => "Period is zero",
+            ValidationIssueKind::PasskeyKey => "Key isn't a PKCS#8 private key",
+            ValidationIssueKind::UnresolvedLink => "Linked item isn't part of the exchange",
+            ValidationIssueKind::Uri => "Value isn't a valid URI reference",
+            ValidationIssueKind::RpId => "Value isn't a valid relying party identifier",
+            ValidationIssueKind::Email => "Value isn't a valid email address",
+        })
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.kind)
+    }
+}
+
+impl<E> Header<E> {
+    /// Check the constraints of the specification which aren't enforced when parsing, and list
+    /// every violation in document order:
+    /// - identifiers are at most [B64Url::MAX_ID_LENGTH] bytes and the identifiers, titles and
+    ///   passkey members which are required aren't empty,
+    /// - countries are ISO 3166-1 alpha-2 codes and subdivisions are ISO 3166-2 codes,
+    /// - the values of [date][FieldType::Date] fields are RFC3339 `full-date`s,
+    /// - TOTP credentials have a non-zero period and between 6 and 10 digits,
+    /// - passkey keys are DER encoded PKCS#8 private keys,
+    /// - every [LinkedItem] [resolves][Header::resolve], including those referring to another
+    ///   account,
+    /// - the URLs of basic authentication credentials are valid URI references, the exporter and
+    ///   the RP IDs of passkeys are valid relying party identifiers, and the emails of accounts are
+    ///   valid addresses.
+    ///
+    /// An empty result doesn't guarantee that the exchange can be imported, e.g. passkeys are not
+    /// checked against their algorithm.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut validator = Validator {
+            header: self,
+            issues: Vec::new(),
+        };
+        if !self.exporter.is_valid() {
+            validator.issue("/exporter".to_owned(), ValidationIssueKind::RpId);
+        }
+        for (index, account) in self.accounts.iter().enumerate() {
+            validator.account(&format!("/accounts/{index}"), account);
+        }
+        validator.issues
+    }
+}
+
+struct Validator<'a, E> {
+    header: &'a Header<E>,
+    issues: Vec<ValidationIssue>,
+}
+
+impl<E> Validator<'_, E> {
+    fn issue(&mut self, pointer: String, kind: ValidationIssueKind) {
+        self.issues.push(ValidationIssue { pointer, kind });
+    }
+
+    fn account(&mut self, pointer: &str, account: &Account<E>) {
+        self.id(format!("{pointer}/id"), &account.id);
+        if !account.email.is_valid() {
+            self.issue(format!("{pointer}/email"), ValidationIssueKind::Email);
+        }
+
+        let mut stack: Vec<(String, &Collection<E>)> = account
+            .collections
+            .iter()
+            .enumerate()
+            .map(|(index, collection)| (format!("{pointer}/collections/{index}"), collection))
+            .rev()
+            .collect();
+        while let Some((pointer, collection)) = stack.pop() {
+            self.id(format!("{pointer}/id"), &collection.id);
+            self.required(format!("{pointer}/title"), collection.title.is_empty());
+            for (index, link) in collection.items.iter().enumerate() {
+                self.link(format!("{pointer}/items/{index}"), &account.id, link);
+            }
+            let sub_collections: Vec<_> = collection
+                .sub_collections
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, sub)| (format!("{pointer}/subCollections/{index}"), sub))
+                .collect();
+            stack.extend(sub_collections.into_iter().rev());
+        }
+
+        for (index, item) in account.items.iter().enumerate() {
+            self.item(&format!("{pointer}/items/{index}"), &account.id, item);
+        }
+    }
+
+    fn item(&mut self, pointer: &str, account_id: &B64Url, item: &Item<E>) {
+        self.id(format!("{pointer}/id"), &item.id);
+        self.required(format!("{pointer}/title"), item.title.is_empty());
+        for (index, credential) in item.credentials.iter().enumerate() {
+            self.credential(
+                &format!("{pointer}/credentials/{index}"),
+                account_id,
+                credential,
+            );
+        }
+    }
+
+    fn credential(&mut self, pointer: &str, account_id: &B64Url, credential: &Credential) {
+        match credential {
+            Credential::BasicAuth(basic_auth) => {
+                for (index, url) in basic_auth.urls.iter().enumerate() {
+                    if !url.is_valid() {
+                        self.issue(format!("{pointer}/urls/{index}"), ValidationIssueKind::Uri);
+                    }
+                }
+            }
+            Credential::Passkey(passkey) => {
+                if !passkey.rp_id.is_valid() {
+                    self.issue(format!("{pointer}/rpId"), ValidationIssueKind::RpId);
+                }
+                self.required(
+                    format!("{pointer}/credentialId"),
+                    passkey.credential_id.as_ref().is_empty(),
+                );
+                self.id(format!("{pointer}/userHandle"), &passkey.user_handle);
+                if !is_pkcs8(passkey.key.as_ref()) {
+                    self.issue(format!("{pointer}/key"), ValidationIssueKind::PasskeyKey);
+                }
+            }
+            Credential::Totp(totp) => {
+                self.required(format!("{pointer}/secret"), totp.secret.as_ref().is_empty());
+                if !TOTP_DIGITS.contains(&totp.digits) {
+                    self.issue(format!("{pointer}/digits"), ValidationIssueKind::TotpDigits);
+                }
+                if totp.period == 0 {
+                    self.issue(format!("{pointer}/period"), ValidationIssueKind::TotpPeriod);
+                }
+            }
+            Credential::ItemReference(reference) => {
+                self.link(
+                    format!("{pointer}/reference"),
+                    account_id,
+                    &reference.reference,
+                );
+            }
+            _ => {}
+        }
+        self.fields(pointer, credential);
+    }
+
+    /// Check the [EditableFields][EditableField] of `credential`, which are located by
+    /// serializing it as the types don't otherwise expose the names of their members.
+    fn fields(&mut self, pointer: &str, credential: &Credential) {
+        let (countries, subdivisions): (&[&str], &[&str]) = match credential {
+            Credential::Address(_) => (&["country"], &[]),
+            Credential::DriversLicense(_) => (&["country"], &["territory"]),
+            Credential::IdentityDocument(_) | Credential::Passport(_) => (&["issuingCountry"], &[]),
+            _ => (&[], &[]),
+        };
+        let Ok(serde_json::Value::Object(members)) = serde_json::to_value(credential) else {
+            return;
+        };
+        for (name, member) in members {
+            if !member.get("fieldType").is_some_and(|ty| ty.is_string()) {
+                continue;
+            }
+            let Ok(field) = serde_json::from_value::<EditableField>(member) else {
+                continue;
+            };
+            let pointer = format!("{pointer}/{name}");
+            self.id(format!("{pointer}/id"), &field.id);
+
+            let kind = if field.field_type == FieldType::Date
+                && parse_date(&field.value, DateParsing::Strict).is_err()
+            {
+                Some(ValidationIssueKind::Date)
+            } else if countries.contains(&name.as_str()) && !is_country_code(&field.value) {
+                Some(ValidationIssueKind::CountryCode)
+            } else if subdivisions.contains(&name.as_str()) && !is_subdivision_code(&field.value) {
+                Some(ValidationIssueKind::SubdivisionCode)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                self.issue(format!("{pointer}/value"), kind);
+            }
+        }
+    }
+
+    fn link(&mut self, pointer: String, account_id: &B64Url, link: &LinkedItem) {
+        self.id(format!("{pointer}/item"), &link.item);
+        if self.header.resolve(account_id, link).is_none() {
+            self.issue(pointer, ValidationIssueKind::UnresolvedLink);
+        }
+    }
+
+    fn id(&mut self, pointer: String, id: &B64Url) {
+        let len = id.as_ref().len();
+        if len == 0 {
+            self.issue(pointer, ValidationIssueKind::Empty);
+        } else if len > B64Url::MAX_ID_LENGTH {
+            self.issue(pointer, ValidationIssueKind::IdTooLong);
+        }
+    }
+
+    fn required(&mut self, pointer: String, empty: bool) {
+        if empty {
+            self.issue(pointer, ValidationIssueKind::Empty);
+        }
+    }
+}
+
+/// Whether `value` has the shape of an ISO 3166-1 alpha-2 code: two uppercase ASCII letters.
+fn is_country_code(value: &str) -> bool {
+    value.len() == 2 && value.bytes().all(|byte| byte.is_ascii_uppercase())
+}
+
+/// Whether `value` has the shape of an ISO 3166-2 code: a country code, a hyphen and one to three
+/// uppercase ASCII letters or digits.
+fn is_subdivision_code(value: &str) -> bool {
+    value.split_once('-').is_some_and(|(country, subdivision)| {
+        is_country_code(country)
+            && (1..=3).contains(&subdivision.len())
+            && subdivision
+                .bytes()
+                .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+    })
+}
+
+/// Whether `key` is a DER encoded PKCS#8 `PrivateKeyInfo` or `OneAsymmetricKey`: a sequence of a
+/// version, an algorithm identifier and the private key, optionally followed by attributes and a
+/// public key. The private key itself isn't parsed.
+fn is_pkcs8(key: &[u8]) -> bool {
+    let Some((0x30, info, [])) = der_element(key) else {
+        return false;
+    };
+    let Some((0x02, [0 | 1], rest)) = der_element(info) else {
+        return false;
+    };
+    let Some((0x30, algorithm, rest)) = der_element(rest) else {
+        return false;
+    };
+    matches!(der_element(algorithm), Some((0x06, _, _)))
+        && matches!(der_element(rest), Some((0x04, _, _)))
+}
+
+/// Split the first DER element of `data` into its tag, its content and the bytes following it.
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (usize::from(first), rest)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > std::mem::size_of::<usize>() {
+            return None;
+        }
+        let len = rest
+            .get(..count)?
+            .iter()
+            .fold(0, |len, byte| len << 8 | usize::from(*byte));
+        (len, &rest[count..])
+    };
+    Some((tag, rest.get(..len)?, &rest[len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// A DER encoded PKCS#8 Ed25519 private key.
+    fn pkcs8() -> Vec<u8> {
+        let mut key = vec![
+            0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22,
+            0x04, 0x20,
+        ];
+        key.extend([7; 32]);
+        key
+    }
+
+    fn field(id: &str, field_type: &str, value: &str) -> Value {
+        json!({ "id": id, "fieldType": field_type, "value": value })
+    }
+
+    /// An exchange without any issue.
+    fn document() -> Value {
+        json!({
+            "version": 0,
+            "exporter": "exporter.example.com",
+            "timestamp": 0,
+            "accounts": [{
+                "id": "AQ",
+                "userName": "jane",
+                "email": "jane@example.com",
+                "collections": [{
+                    "id": "Ag",
+                    "title": "Work",
+                    "items": [{ "item": "Aw" }],
+                    "subCollections": [{ "id": "BA", "title": "Projects", "items": [] }],
+                }],
+                "items": [{
+                    "id": "Aw",
+                    "type": "login",
+                    "title": "Example",
+                    "credentials": [
+                        {
+                            "type": "basic-auth",
+                            "urls": ["https://example.com/login"],
+                            "username": field("BQ", "string", "jane"),
+                        },
+                        {
+                            "type": "passkey",
+                            "credentialId": "AQ",
+                            "rpId": "example.com",
+                            "userName": "jane",
+                            "userDisplayName": "Jane",
+                            "userHandle": "AQ",
+                            "key": B64Url::from(pkcs8()),
+                        },
+                        {
+                            "type": "totp",
+                            "secret": "JBSWY3DPEHPK3PXP",
+                            "period": 30,
+                            "digits": 6,
+                            "username": "jane",
+                            "algorithm": "sha1",
+                        },
+                        {
+                            "type": "drivers-license",
+                            "birthDate": field("Bg", "date", "1990-01-31"),
+                            "country": field("Bw", "string", "US"),
+                            "territory": field("CA", "string", "US-CA"),
+                        },
+                        { "type": "item-reference", "reference": { "item": "Aw" } },
+                    ],
+                }],
+            }],
+        })
+    }
+
+    fn validate(document: Value) -> Vec<ValidationIssue> {
+        serde_json::from_value::<Header>(document)
+            .expect("the header is valid")
+            .validate()
+    }
+
+    /// Validate the [document] with the value at `pointer` replaced.
+    fn validate_with(pointer: &str, value: Value) -> Vec<ValidationIssue> {
+        let mut document = document();
+        *document.pointer_mut(pointer).expect("the member exists") = value;
+        validate(document)
+    }
+
+    fn issue(pointer: &str, kind: ValidationIssueKind) -> ValidationIssue {
+        ValidationIssue {
+            pointer: pointer.to_owned(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn valid_document() {
+        assert_eq!(validate(document()), []);
+    }
+
+    #[test]
+    fn samples_are_valid() {
+        for (name, sample) in crate::samples::all() {
+            let header: Header = serde_json::from_str(sample).unwrap();
+            assert_eq!(header.validate(), [], "{name}");
+        }
+    }
+
+    #[test]
+    fn issues() {
+        let credentials = "/accounts/0/items/0/credentials";
+        let cases = [
+            (
+                "/accounts/0/collections/0/id".to_owned(),
+                json!(B64Url::from(vec![1; 65])),
+                "/accounts/0/collections/0/id".to_owned(),
+                ValidationIssueKind::IdTooLong,
+            ),
+            (
+                "/accounts/0/collections/0/subCollections/0/title".to_owned(),
+                json!(""),
+                "/accounts/0/collections/0/subCollections/0/title".to_owned(),
+                ValidationIssueKind::Empty,
+            ),
+            (
+                format!("{credentials}/3/country/value"),
+                json!("USA"),
+                format!("{credentials}/3/country/value"),
+                ValidationIssueKind::CountryCode,
+            ),
+            (
+                format!("{credentials}/3/territory/value"),
+                json!("California"),
+                format!("{credentials}/3/territory/value"),
+                ValidationIssueKind::SubdivisionCode,
+            ),
+            (
+                format!("{credentials}/3/birthDate/value"),
+                json!("31/01/1990"),
+                format!("{credentials}/3/birthDate/value"),
+                ValidationIssueKind::Date,
+            ),
+            (
+                format!("{credentials}/2/digits"),
+                json!(11),
+                format!("{credentials}/2/digits"),
+                ValidationIssueKind::TotpDigits,
+            ),
+            (
+                format!("{credentials}/2/period"),
+                json!(0),
+                format!("{credentials}/2/period"),
+                ValidationIssueKind::TotpPeriod,
+            ),
+            (
+                format!("{credentials}/1/key"),
+                json!(B64Url::from(pkcs8()[..47].to_vec())),
+                format!("{credentials}/1/key"),
+                ValidationIssueKind::PasskeyKey,
+            ),
+            (
+                "/accounts/0/collections/0/items/0/item".to_owned(),
+                json!("BQ"),
+                "/accounts/0/collections/0/items/0".to_owned(),
+                ValidationIssueKind::UnresolvedLink,
+            ),
+            (
+                format!("{credentials}/4/reference/item"),
+                json!("BQ"),
+                format!("{credentials}/4/reference"),
+                ValidationIssueKind::UnresolvedLink,
+            ),
+            (
+                format!("{credentials}/0/urls/0"),
+                json!("https://exa mple.com"),
+                format!("{credentials}/0/urls/0"),
+                ValidationIssueKind::Uri,
+            ),
+            (
+                "/exporter".to_owned(),
+                json!("localhost"),
+                "/exporter".to_owned(),
+                ValidationIssueKind::RpId,
+            ),
+            (
+                format!("{credentials}/1/rpId"),
+                json!("co.uk"),
+                format!("{credentials}/1/rpId"),
+                ValidationIssueKind::RpId,
+            ),
+            (
+                "/accounts/0/email".to_owned(),
+                json!("jane"),
+                "/accounts/0/email".to_owned(),
+                ValidationIssueKind::Email,
+            ),
+        ];
+        for (pointer, value, expected, kind) in cases {
+            assert_eq!(
+                validate_with(&pointer, value),
+                [issue(&expected, kind)],
+                "{pointer}"
+            );
+        }
+    }
+
+    #[test]
+    fn issues_are_in_document_order() {
+        let mut document = document();
+        document["exporter"] = json!("localhost");
+        document["accounts"][0]["collections"][0]["subCollections"][0]["id"] = json!("");
+        document["accounts"][0]["items"][0]["title"] = json!("");
+        document["accounts"][0]["items"][0]["credentials"][2]["digits"] = json!(5);
+
+        assert_eq!(
+            validate(document),
+            [
+                issue("/exporter", ValidationIssueKind::RpId),
+                issue(
+                    "/accounts/0/collections/0/subCollections/0/id",
+                    ValidationIssueKind::Empty
+                ),
+                issue("/accounts/0/items/0/title", ValidationIssueKind::Empty),
+                issue(
+                    "/accounts/0/items/0/credentials/2/digits",
+                    ValidationIssueKind::TotpDigits
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn pkcs8_structure() {
+        assert!(is_pkcs8(&pkcs8()));
+
+        let mut trailing = pkcs8();
+        trailing.push(0);
+        assert!(!is_pkcs8(&trailing));
+
+        let mut version = pkcs8();
+        version[4] = 2;
+        assert!(!is_pkcs8(&version));
+
+        let mut algorithm = pkcs8();
+        algorithm[7] = 0x04;
+        assert!(!is_pkcs8(&algorithm));
+
+        assert!(!is_pkcs8(&[]));
+    }
+
+    #[test]
+    fn der_lengths() {
+        assert_eq!(
+            der_element(&[0x04, 0x01, 1, 2]),
+            Some((0x04, &[1][..], &[2][..]))
+        );
+        assert_eq!(
+            der_element(&[0x04, 0x81, 0x02, 1, 2, 3]),
+            Some((0x04, &[1, 2][..], &[3][..]))
+        );
+
+        // Truncated tag, length, length of the length and content.
+        assert_eq!(der_element(&[]), None);
+        assert_eq!(der_element(&[0x04]), None);
+        assert_eq!(der_element(&[0x04, 0x82, 0x01]), None);
+        assert_eq!(der_element(&[0x04, 0x03, 1, 2]), None);
+        assert_eq!(der_element(&[0x04, 0x81, 0x80, 1]), None);
+
+        // Indefinite length, and lengths which don't fit in or overflow a usize.
+        assert_eq!(der_element(&[0x04, 0x80, 0x00, 0x00]), None);
+        assert_eq!(
+            der_element(&[0x04, 0x89, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0]),
+            None
+        );
+        assert_eq!(
+            der_element(&[0x04, 0x88, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            None
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Conversion of internationalized domain names between their Unicode form, e.g. `bücher.de`, and
+//! their ASCII form, e.g. `xn--bcher-kva.de`, using the
+//! [Punycode](https://www.rfc-editor.org/rfc/rfc3492) encoding.
+//!
+//! Labels are only lowercased before being encoded, the full mapping and validation of
+//! [IDNA2008](https://www.rfc-editor.org/rfc/rfc5891) is not performed. This is enough to compare
+//! the domains written by different exporters, but not to validate them.
+//!
+//! Domains are only displayed in their Unicode form when it can't be mistaken for another domain,
+//! following a simplified version of the
+//! [restriction levels](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection) of
+//! Unicode Technical Standard #39: labels mixing scripts, e.g. a Cyrillic `а` among Latin
+//! letters, and labels made only of Cyrillic or Greek letters which look like Latin ones, e.g.
+//! `аррӏе`, are kept in their ASCII form.
+
+const ACE_PREFIX: &str = "xn--";
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+/// Convert every label of `domain` to its lowercase ASCII form. Returns [None] if a label cannot
+/// be encoded.
+pub(crate) fn domain_to_ascii(domain: &str) -> Option<String> {
+    let labels = domain
+        .split('.')
+        .map(|label| {
+            let label = label.to_lowercase();
+            if label.is_ascii() {
+                Some(label)
+            } else {
+                encode(&label).map(|encoded| format!("{ACE_PREFIX}{encoded}"))
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(labels.join("."))
+}
+
+/// Convert every ASCII encoded label of `domain` to its Unicode form, for display. Labels which
+/// are not valid Punycode are kept as they are.
+///
+/// If a label could be mistaken for another one, as described in the [module][self]
+/// documentation, the whole domain is returned in its ASCII form instead.
+pub(crate) fn domain_to_unicode(domain: &str) -> String {
+    let labels: Vec<String> = domain
+        .split('.')
+        .map(|label| {
+            label
+                .get(..ACE_PREFIX.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(ACE_PREFIX))
+                .and_then(|_| decode(&label[ACE_PREFIX.len()..]))
+                .unwrap_or_else(|| label.to_owned())
+        })
+        .collect();
+    if labels.iter().all(|label| is_unambiguous(label)) {
+        labels.join(".")
+    } else {
+        domain_to_ascii(domain).unwrap_or_else(|| domain.to_owned())
+    }
+}
+
+/// The scripts distinguished when checking that a label isn't confusable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Script {
+    /// Digits, hyphens and combining marks, which are used with any script.
+    Common,
+    Latin,
+    Greek,
+    Cyrillic,
+    Han,
+    Hiragana,
+    Katakana,
+    Hangul,
+    Bopomofo,
+    /// Any other script. Characters of different unclassified scripts aren't told apart, so
+    /// mixing them with a classified script is always rejected.
+    Other,
+}
+
+impl Script {
+    fn of(c: char) -> Self {
+        match u32::from(c) {
+            0x30..=0x39 | 0x2d | 0x300..=0x36f => Script::Common,
+            0xd7 | 0xf7 => Script::Other,
+            0x41..=0x5a | 0x61..=0x7a | 0xc0..=0x24f | 0x1e00..=0x1eff => Script::Latin,
+            0x370..=0x3ff | 0x1f00..=0x1fff => Script::Greek,
+            0x400..=0x52f | 0x1c80..=0x1c8f | 0x2de0..=0x2dff | 0xa640..=0xa69f => Script::Cyrillic,
+            0x2e80..=0x2fdf | 0x3005 | 0x3007 | 0x3400..=0x4dbf | 0x4e00..=0x9fff => Script::Han,
+            0xf900..=0xfaff | 0x20000..=0x3134f => Script::Han,
+            0x3041..=0x309f => Script::Hiragana,
+            0x30a0..=0x30ff | 0x31f0..=0x31ff | 0xff66..=0xff9f => Script::Katakana,
+            0x1100..=0x11ff | 0x3130..=0x318f | 0xac00..=0xd7af => Script::Hangul,
+            0x3100..=0x312f | 0x31a0..=0x31bf => Script::Bopomofo,
+            _ => Script::Other,
+        }
+    }
+}
+
+/// The combinations of scripts which are commonly written together, and allowed within a label.
+const SCRIPT_COMBINATIONS: [&[Script]; 3] = [
+    &[
+        Script::Latin,
+        Script::Han,
+        Script::Hiragana,
+        Script::Katakana,
+    ],
+    &[Script::Latin, Script::Han, Script::Bopomofo],
+    &[Script::Latin, Script::Han, Script::Hangul],
+];
+
+/// Cyrillic and Greek letters which look like Latin ones.
+const LATIN_LOOKALIKES: &str = "аеһіјӏорԛѕԝхуъьԁгпκνορτυχαι";
+
+/// Whether `label` can't be mistaken for another label: its letters belong to a single script or
+/// to an allowed combination of scripts, and it isn't only made of Latin lookalikes.
+fn is_unambiguous(label: &str) -> bool {
+    if label.is_ascii() {
+        return true;
+    }
+    let mut scripts: Vec<Script> = Vec::new();
+    for c in label.chars() {
+        let script = Script::of(c);
+        if script != Script::Common && !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+    let single_script = match scripts.as_slice() {
+        [] => return true,
+        [script] => *script,
+        _ => {
+            return !scripts.contains(&Script::Other)
+                && SCRIPT_COMBINATIONS
+                    .iter()
+                    .any(|allowed| scripts.iter().all(|script| allowed.contains(script)));
+        }
+    };
+    !matches!(single_script, Script::Cyrillic | Script::Greek)
+        || !label
+            .chars()
+            .all(|c| Script::of(c) == Script::Common || LATIN_LOOKALIKES.contains(c))
+}
+
+fn adapt(mut delta: u32, points: u32, first: bool) -> u32 {
+    delta /= if first { DAMP } else { 2 };
+    delta += delta / points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+fn encode_digit(digit: u32) -> char {
+    match digit {
+        0..=25 => char::from(b'a' + digit as u8),
+        _ => char::from(b'0' + (digit - 26) as u8),
+    }
+}
+
+fn decode_digit(byte: u8) -> Option<u32> {
+    match byte {
+        b'a'..=b'z' => Some(u32::from(byte - b'a')),
+        b'A'..=b'Z' => Some(u32::from(byte - b'A')),
+        b'0'..=b'9' => Some(u32::from(byte - b'0') + 26),
+        _ => None,
+    }
+}
+
+/// Encode a label with [Punycode](https://www.rfc-editor.org/rfc/rfc3492#section-6.3).
+fn encode(label: &str) -> Option<String> {
+    let input: Vec<u32> = label.chars().map(u32::from).collect();
+    let mut output: String = label.chars().filter(char::is_ascii).collect();
+    let basic = output.len() as u32;
+    if basic > 0 {
+        output.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic;
+    while (handled as usize) < input.len() {
+        let m = input.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &input {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n = n.checked_add(1)?;
+    }
+    Some(output)
+}
+
+/// Decode a label encoded with [Punycode](https://www.rfc-editor.org/rfc/rfc3492#section-6.2).
+fn decode(encoded: &str) -> Option<String> {
+    let (basic, extended) = match encoded.rfind('-') {
+        Some(index) => (&encoded[..index], &encoded[index + 1..]),
+        None => ("", encoded),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut bytes = extended.bytes().peekable();
+    while bytes.peek().is_some() {
+        let previous = i;
+        let mut weight: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(bytes.next()?)?;
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            weight = weight.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - previous, length, previous == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples of [RFC 3492](https://www.rfc-editor.org/rfc/rfc3492#section-7.1).
+    const SAMPLES: [(&str, &str); 3] = [
+        ("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+        ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+        ("bücher", "bcher-kva"),
+    ];
+
+    #[test]
+    fn punycode_samples() {
+        for (label, encoded) in SAMPLES {
+            assert_eq!(encode(label).as_deref(), Some(encoded));
+            assert_eq!(decode(encoded).as_deref(), Some(label));
+        }
+    }
+
+    #[test]
+    fn invalid_punycode_is_rejected() {
+        assert_eq!(decode("ä-abc"), None);
+        assert_eq!(decode("bcher-kv!"), None);
+        assert_eq!(domain_to_unicode("xn--bcher-kv!.de"), "xn--bcher-kv!.de");
+    }
+
+    #[test]
+    fn domains_round_trip() {
+        assert_eq!(
+            domain_to_ascii("Bücher.de").as_deref(),
+            Some("xn--bcher-kva.de")
+        );
+        assert_eq!(domain_to_unicode("xn--bcher-kva.de"), "bücher.de");
+        assert_eq!(domain_to_unicode("XN--bcher-kva.de"), "bücher.de");
+    }
+
+    #[test]
+    fn confusable_domains_are_displayed_in_ascii() {
+        // A Cyrillic "а" among Latin letters.
+        let mixed = domain_to_ascii("аpple.com").expect("the domain can be encoded");
+        assert_eq!(domain_to_unicode(&mixed), mixed);
+        // Only Cyrillic letters which look like "apple".
+        let lookalike = domain_to_ascii("аррӏе.com").expect("the domain can be encoded");
+        assert_eq!(domain_to_unicode(&lookalike), lookalike);
+    }
+
+    #[test]
+    fn single_and_combined_scripts_are_displayed_in_unicode() {
+        for domain in [
+            "пример.рф",
+            "ελλάδα.gr",
+            "3年b組金八先生.jp",
+            "한국어abc.kr",
+        ] {
+            let ascii = domain_to_ascii(domain).expect("the domain can be encoded");
+            assert_eq!(domain_to_unicode(&ascii), domain);
+        }
+    }
+}
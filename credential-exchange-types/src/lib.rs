@@ -1,7 +1,21 @@
+#[cfg(feature = "arbitrary")]
+mod arbitrary_values;
 mod b64url;
-pub use b64url::B64Url;
+pub use b64url::{B64Url, InvalidEncoding, B32};
+mod email;
+pub use email::{Email, InvalidEmail};
 
 pub mod format;
+mod idna;
+#[cfg(feature = "protocol")]
 pub mod protocol;
-
-type Uri = String;
+mod rp_id;
+pub use rp_id::{InvalidRpId, RpId};
+pub mod samples;
+pub mod serialization;
+#[cfg(feature = "testdata")]
+pub mod testdata;
+pub mod timestamp;
+mod trace;
+mod uri;
+pub use uri::{InvalidUri, Uri};
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::b64url::B64Url;
 
+#[cfg(feature = "crypto")]
+pub mod crypto;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExportRequest {
@@ -41,7 +44,7 @@ impl From<Version> for u8 {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum CredentialType {
     BasicAuth,
@@ -51,11 +54,21 @@ pub enum CredentialType {
     File,
     Address,
     CreditCard,
-    DriverLicense,
+    #[serde(alias = "driver-license")]
+    DriversLicense,
     ItemReference,
     IdentityDocument,
     Passport,
     PersonName,
+    #[cfg(feature = "non-standard-credentials")]
+    BirthCertificate,
+    #[cfg(feature = "non-standard-credentials")]
+    InsurancePolicy,
+    ApiKey,
+    SshKey,
+    Wifi,
+    GeneratedPassword,
+    CustomFields,
     #[serde(untagged)]
     Unknown(String),
 }
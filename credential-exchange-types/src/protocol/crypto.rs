@@ -0,0 +1,395 @@
+//! Encrypts and decrypts the [payload][super::ExportResponse::payload] of an export with HPKE, as
+//! negotiated by the [HpkeParameters] of the [ExportRequest][super::ExportRequest].
+//!
+//! The payload is the concatenation of the encapsulated key of the exporter and of the ciphertext
+//! of the serialized [Header][crate::format::Header], encoded in base64url without padding:
+//!
+//! - the encapsulated key is the serialization defined by [RFC 9180](https://www.rfc-editor.org/rfc/rfc9180#section-7.1.1)
+//!   for the key encapsulation, i.e. an uncompressed SEC1 point of 65 bytes for P-256 and 32 bytes
+//!   for X25519;
+//! - the ciphertext is the output of the AEAD, i.e. the encrypted header followed by the 16 bytes
+//!   of its authentication tag;
+//! - the HPKE `info` and associated data are empty.
+//!
+//! Only the [base mode][HpkeMode::Base] is supported,
+//! with the [P-256][HpkeKem::DhP256] and [X25519][HpkeKem::DhX25519] key encapsulations, any of
+//! the HKDF key derivations and any of the AEADs except [export only][HpkeAead::ExportOnly].
+//!
+//! Importers create the key pair of an exchange with [HpkeParameters::generate_key_pair].
+
+use data_encoding::BASE64URL_NOPAD;
+use hpke::{
+    aead::{Aead, AesGcm128, AesGcm256, ChaCha20Poly1305},
+    kdf::{HkdfSha256, HkdfSha384, HkdfSha512, Kdf},
+    kem::{DhP256HkdfSha256, X25519HkdfSha256},
+    Deserializable, Kem, OpModeR, OpModeS, Serializable,
+};
+use rand::rngs::OsRng;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{HpkeAead, HpkeKdf, HpkeKem, HpkeMode, HpkeParameters};
+use crate::B64Url;
+
+/// The HPKE `info` and associated data, which the protocol leaves empty.
+const INFO: &[u8] = b"";
+const AAD: &[u8] = b"";
+
+/// The reasons why a payload cannot be sealed or opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadError {
+    /// The mode, key encapsulation, key derivation or AEAD of the [HpkeParameters] is not
+    /// supported.
+    UnsupportedParameters,
+    /// The [HpkeParameters] don't hold the [key][HpkeParameters::key] of the importer.
+    MissingKey,
+    /// The key isn't a valid JWK for the key encapsulation of the [HpkeParameters].
+    InvalidKey,
+    /// The payload couldn't be encrypted or decrypted, e.g. because it was modified or was not
+    /// sealed for this key.
+    Encryption,
+}
+
+impl std::fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PayloadError::UnsupportedParameters => "HPKE parameters aren't supported",
+            PayloadError::MissingKey => "HPKE parameters don't hold the importer key",
+            PayloadError::InvalidKey => "Key isn't valid for the HPKE parameters",
+            PayloadError::Encryption => "Payload couldn't be encrypted or decrypted",
+        })
+    }
+}
+
+/// The private key of an importer, as returned by [HpkeParameters::generate_key_pair]. It is
+/// never printed by its [Debug] implementation nor cloned, and its private `d` member is zeroized
+/// when it is dropped, as are the copies made by this module while using it.
+pub struct ImporterPrivateKey(jose_jwk::Jwk);
+
+impl ImporterPrivateKey {
+    /// The key as a JWK holding its private `d` member, e.g. to store it until the export
+    /// response arrives, or to pass it to [open_payload].
+    pub fn as_jwk(&self) -> &jose_jwk::Jwk {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for ImporterPrivateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ImporterPrivateKey(***)")
+    }
+}
+
+impl HpkeParameters {
+    /// Generate an ephemeral key pair for the key encapsulation of these parameters. The public
+    /// key is meant for the [key][HpkeParameters::key] of the parameters sent in the
+    /// [ExportRequest][super::ExportRequest], and the private key to [open][open_payload] the
+    /// payload of the response.
+    pub fn generate_key_pair(&self) -> Result<(jose_jwk::Jwk, ImporterPrivateKey), PayloadError> {
+        let (public, private) = dispatch(self, GenerateKeyPair)?;
+        let jwk = |value| jose_jwk::Jwk::deserialize(value).map_err(|_| PayloadError::InvalidKey);
+        Ok((jwk(&public)?, ImporterPrivateKey(jwk(&private.0)?)))
+    }
+}
+
+/// Encrypt `plaintext`, usually the serialized [Header][crate::format::Header], for the importer
+/// [key][HpkeParameters::key] of the negotiated `parameters`.
+pub fn seal_payload(parameters: &HpkeParameters, plaintext: &[u8]) -> Result<B64Url, PayloadError> {
+    let key = parameters.key.as_ref().ok_or(PayloadError::MissingKey)?;
+    let key = serde_json::to_value(key).map_err(|_| PayloadError::InvalidKey)?;
+    dispatch(parameters, Seal { key, plaintext })
+}
+
+/// Decrypt a `payload` produced by [seal_payload] for the same `parameters`, with the private key
+/// of the importer, a JWK holding its private `d` member.
+pub fn open_payload(
+    parameters: &HpkeParameters,
+    private_key: &jose_jwk::Jwk,
+    payload: &B64Url,
+) -> Result<Vec<u8>, PayloadError> {
+    let key = serde_json::to_value(private_key).map_err(|_| PayloadError::InvalidKey)?;
+    dispatch(
+        parameters,
+        Open {
+            key: PrivateJwk(key),
+            payload: payload.as_ref(),
+        },
+    )
+}
+
+/// A key encapsulation mechanism whose keys are represented as JWKs.
+trait JwkKem: Kem {
+    /// The length in bytes of the encapsulated key.
+    const ENCAPPED_KEY_LENGTH: usize;
+
+    fn public_key(jwk: &Value) -> Result<Self::PublicKey, PayloadError>;
+
+    fn private_key(jwk: &Value) -> Result<Self::PrivateKey, PayloadError>;
+
+    /// The public and private JWKs of a key pair.
+    fn jwks(private: &Self::PrivateKey, public: &Self::PublicKey) -> (Value, PrivateJwk);
+}
+
+/// A JWK holding a private `d` member, which is zeroized when dropped.
+struct PrivateJwk(Value);
+
+impl PrivateJwk {
+    /// The JWK `public` with the private key `d` added, as a base64url encoded member.
+    fn new(public: &Value, d: &[u8]) -> Self {
+        let mut jwk = PrivateJwk(public.clone());
+        jwk.0["d"] = BASE64URL_NOPAD.encode(d).into();
+        jwk
+    }
+}
+
+impl Drop for PrivateJwk {
+    fn drop(&mut self) {
+        if let Some(Value::String(d)) = self.0.get_mut("d") {
+            d.zeroize();
+        }
+    }
+}
+
+impl JwkKem for DhP256HkdfSha256 {
+    const ENCAPPED_KEY_LENGTH: usize = 65;
+
+    fn public_key(jwk: &Value) -> Result<Self::PublicKey, PayloadError> {
+        check_curve(jwk, "EC", "P-256")?;
+        // An uncompressed SEC1 point.
+        let mut point = vec![0x04];
+        point.extend_from_slice(&member(jwk, "x", 32)?);
+        point.extend_from_slice(&member(jwk, "y", 32)?);
+        Self::PublicKey::from_bytes(&point).map_err(|_| PayloadError::InvalidKey)
+    }
+
+    fn private_key(jwk: &Value) -> Result<Self::PrivateKey, PayloadError> {
+        check_curve(jwk, "EC", "P-256")?;
+        Self::PrivateKey::from_bytes(&member(jwk, "d", 32)?).map_err(|_| PayloadError::InvalidKey)
+    }
+
+    fn jwks(private: &Self::PrivateKey, public: &Self::PublicKey) -> (Value, PrivateJwk) {
+        let point = public.to_bytes();
+        let (x, y) = point[1..].split_at(32);
+        let public = json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": BASE64URL_NOPAD.encode(x),
+            "y": BASE64URL_NOPAD.encode(y),
+        });
+        let private = PrivateJwk::new(&public, &Zeroizing::new(private.to_bytes().to_vec()));
+        (public, private)
+    }
+}
+
+impl JwkKem for X25519HkdfSha256 {
+    const ENCAPPED_KEY_LENGTH: usize = 32;
+
+    fn public_key(jwk: &Value) -> Result<Self::PublicKey, PayloadError> {
+        check_curve(jwk, "OKP", "X25519")?;
+        Self::PublicKey::from_bytes(&member(jwk, "x", 32)?).map_err(|_| PayloadError::InvalidKey)
+    }
+
+    fn private_key(jwk: &Value) -> Result<Self::PrivateKey, PayloadError> {
+        check_curve(jwk, "OKP", "X25519")?;
+        Self::PrivateKey::from_bytes(&member(jwk, "d", 32)?).map_err(|_| PayloadError::InvalidKey)
+    }
+
+    fn jwks(private: &Self::PrivateKey, public: &Self::PublicKey) -> (Value, PrivateJwk) {
+        let public = json!({
+            "kty": "OKP",
+            "crv": "X25519",
+            "x": BASE64URL_NOPAD.encode(&public.to_bytes()),
+        });
+        let private = PrivateJwk::new(&public, &Zeroizing::new(private.to_bytes().to_vec()));
+        (public, private)
+    }
+}
+
+fn check_curve(jwk: &Value, kty: &str, crv: &str) -> Result<(), PayloadError> {
+    if jwk.get("kty").and_then(Value::as_str) != Some(kty)
+        || jwk.get("crv").and_then(Value::as_str) != Some(crv)
+    {
+        return Err(PayloadError::InvalidKey);
+    }
+    Ok(())
+}
+
+/// Decode the base64url member `name` of `jwk`, which must be `len` bytes long. The decoded bytes
+/// are zeroized when dropped, as they may be a private key.
+fn member(jwk: &Value, name: &str, len: usize) -> Result<Zeroizing<Vec<u8>>, PayloadError> {
+    jwk.get(name)
+        .and_then(Value::as_str)
+        .and_then(|value| BASE64URL_NOPAD.decode(value.as_bytes()).ok())
+        .map(Zeroizing::new)
+        .filter(|bytes| bytes.len() == len)
+        .ok_or(PayloadError::InvalidKey)
+}
+
+/// An operation to run with the algorithms selected by the [HpkeParameters].
+trait Operation {
+    type Output;
+
+    fn run<A: Aead, D: Kdf, K: JwkKem>(self) -> Result<Self::Output, PayloadError>;
+}
+
+fn dispatch<O: Operation>(
+    parameters: &HpkeParameters,
+    operation: O,
+) -> Result<O::Output, PayloadError> {
+    if parameters.mode != HpkeMode::Base {
+        return Err(PayloadError::UnsupportedParameters);
+    }
+    match parameters.kem {
+        HpkeKem::DhP256 => dispatch_kdf::<DhP256HkdfSha256, O>(parameters, operation),
+        HpkeKem::DhX25519 => dispatch_kdf::<X25519HkdfSha256, O>(parameters, operation),
+        _ => Err(PayloadError::UnsupportedParameters),
+    }
+}
+
+fn dispatch_kdf<K: JwkKem, O: Operation>(
+    parameters: &HpkeParameters,
+    operation: O,
+) -> Result<O::Output, PayloadError> {
+    match parameters.kdf {
+        HpkeKdf::HkdfSha256 => dispatch_aead::<K, HkdfSha256, O>(parameters, operation),
+        HpkeKdf::HkdfSha384 => dispatch_aead::<K, HkdfSha384, O>(parameters, operation),
+        HpkeKdf::HkdfSha512 => dispatch_aead::<K, HkdfSha512, O>(parameters, operation),
+        _ => Err(PayloadError::UnsupportedParameters),
+    }
+}
+
+fn dispatch_aead<K: JwkKem, D: Kdf, O: Operation>(
+    parameters: &HpkeParameters,
+    operation: O,
+) -> Result<O::Output, PayloadError> {
+    match parameters.aead {
+        HpkeAead::Aes128Gcm => operation.run::<AesGcm128, D, K>(),
+        HpkeAead::Aes256Gcm => operation.run::<AesGcm256, D, K>(),
+        HpkeAead::ChaCha20Poly1305 => operation.run::<ChaCha20Poly1305, D, K>(),
+        _ => Err(PayloadError::UnsupportedParameters),
+    }
+}
+
+struct Seal<'a> {
+    key: Value,
+    plaintext: &'a [u8],
+}
+
+impl Operation for Seal<'_> {
+    type Output = B64Url;
+
+    fn run<A: Aead, D: Kdf, K: JwkKem>(self) -> Result<B64Url, PayloadError> {
+        let key = K::public_key(&self.key)?;
+        let (encapped_key, ciphertext) = hpke::single_shot_seal::<A, D, K, _>(
+            &OpModeS::Base,
+            &key,
+            INFO,
+            self.plaintext,
+            AAD,
+            &mut OsRng,
+        )
+        .map_err(|_| PayloadError::Encryption)?;
+
+        let mut payload = encapped_key.to_bytes().to_vec();
+        payload.extend(ciphertext);
+        Ok(B64Url::from(payload))
+    }
+}
+
+struct Open<'a> {
+    key: PrivateJwk,
+    payload: &'a [u8],
+}
+
+impl Operation for Open<'_> {
+    type Output = Vec<u8>;
+
+    fn run<A: Aead, D: Kdf, K: JwkKem>(self) -> Result<Vec<u8>, PayloadError> {
+        let key = K::private_key(&self.key.0)?;
+        if self.payload.len() < K::ENCAPPED_KEY_LENGTH {
+            return Err(PayloadError::Encryption);
+        }
+        let (encapped_key, ciphertext) = self.payload.split_at(K::ENCAPPED_KEY_LENGTH);
+        let encapped_key =
+            K::EncappedKey::from_bytes(encapped_key).map_err(|_| PayloadError::Encryption)?;
+        hpke::single_shot_open::<A, D, K>(
+            &OpModeR::Base,
+            &key,
+            &encapped_key,
+            INFO,
+            ciphertext,
+            AAD,
+        )
+        .map_err(|_| PayloadError::Encryption)
+    }
+}
+
+struct GenerateKeyPair;
+
+impl Operation for GenerateKeyPair {
+    type Output = (Value, PrivateJwk);
+
+    fn run<A: Aead, D: Kdf, K: JwkKem>(self) -> Result<(Value, PrivateJwk), PayloadError> {
+        let (private, public) = K::gen_keypair(&mut OsRng);
+        Ok(K::jwks(&private, &public))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters(kem: HpkeKem, kdf: HpkeKdf, aead: HpkeAead) -> HpkeParameters {
+        HpkeParameters {
+            mode: HpkeMode::Base,
+            kem,
+            kdf,
+            aead,
+            key: None,
+        }
+    }
+
+    #[test]
+    fn seal_open_round_trip() {
+        let plaintext = br#"{"version":{"major":1,"minor":0}}"#;
+        for (kem, encapped_key_length) in [(HpkeKem::DhP256, 65), (HpkeKem::DhX25519, 32)] {
+            for kdf in [
+                HpkeKdf::HkdfSha256,
+                HpkeKdf::HkdfSha384,
+                HpkeKdf::HkdfSha512,
+            ] {
+                for aead in [
+                    HpkeAead::Aes128Gcm,
+                    HpkeAead::Aes256Gcm,
+                    HpkeAead::ChaCha20Poly1305,
+                ] {
+                    let mut parameters = parameters(kem, kdf, aead);
+                    let (public, private) = parameters.generate_key_pair().unwrap();
+                    parameters.key = Some(public);
+
+                    let payload = seal_payload(&parameters, plaintext).unwrap();
+                    let length = payload.as_ref().len();
+                    assert_eq!(length, encapped_key_length + plaintext.len() + 16);
+                    assert_eq!(
+                        open_payload(&parameters, private.as_jwk(), &payload).unwrap(),
+                        plaintext
+                    );
+
+                    let mut tampered = payload.as_ref().to_vec();
+                    tampered[length - 1] ^= 1;
+                    assert_eq!(
+                        open_payload(&parameters, private.as_jwk(), &tampered.into()),
+                        Err(PayloadError::Encryption)
+                    );
+
+                    let (_, other) = parameters.generate_key_pair().unwrap();
+                    assert_eq!(
+                        open_payload(&parameters, other.as_jwk(), &payload),
+                        Err(PayloadError::Encryption)
+                    );
+                }
+            }
+        }
+    }
+}
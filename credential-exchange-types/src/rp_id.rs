@@ -0,0 +1,210 @@
+//! The [RpId] type of the relying party identifiers stored in passkeys and headers.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// The longest domain name allowed by [RFC1035](https://www.rfc-editor.org/rfc/rfc1035).
+const MAX_LENGTH: usize = 253;
+/// The longest label of a domain name allowed by [RFC1035](https://www.rfc-editor.org/rfc/rfc1035).
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// Second level labels which are commonly registered under a country code top level domain, e.g.
+/// `co.uk`, and are therefore public suffixes themselves.
+const SECOND_LEVEL_SUFFIXES: &[&str] = &["ac", "co", "com", "edu", "gov", "net", "org"];
+
+/// A [WebAuthn](https://www.w3.org/TR/webauthn-3)
+/// [Relying Party Identifier](https://www.w3.org/TR/webauthn-3/#relying-party-identifier), which
+/// is a domain name such as `login.example.com`.
+///
+/// The identifier is lowercased and its trailing dot removed when [parsed][RpId::parse].
+/// Identifiers are compared in this [normalized][RpId::normalized] form, so that identifiers
+/// written differently by exporters compare equal.
+///
+/// Deserialization accepts any string and keeps it unchanged, so that a passkey or a header with
+/// a malformed identifier is still imported, and every identifier is exported again as it was
+/// written. Use [is_valid][RpId::is_valid] to check them.
+#[derive(Clone, Debug, Serialize)]
+#[serde(into = "String")]
+pub struct RpId(String);
+
+impl RpId {
+    /// Parse a relying party identifier, validating that it is an ASCII domain name of at least
+    /// two labels which isn't a public suffix such as `com` or `co.uk`. Internationalized domain
+    /// names must be given in their punycode form.
+    pub fn parse(value: &str) -> Result<Self, InvalidRpId> {
+        let value = value
+            .strip_suffix('.')
+            .unwrap_or(value)
+            .to_ascii_lowercase();
+        if is_rp_id(&value) {
+            Ok(Self(value))
+        } else {
+            Err(InvalidRpId)
+        }
+    }
+
+    /// Create an [RpId] without validating nor normalizing it, for values which must be kept even
+    /// though they are malformed.
+    pub fn lenient(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+
+    /// Whether the identifier would be accepted by [parse][RpId::parse], which is always the case
+    /// for parsed values but not for [deserialized][Deserialize] or [lenient][RpId::lenient] ones.
+    /// The case and the trailing dot of the identifier don't matter.
+    ///
+    /// Public suffixes are recognized without the
+    /// [public suffix list](https://publicsuffix.org): top level domains and the common second
+    /// level suffixes of country codes such as `co.uk` are rejected, but not suffixes such as
+    /// `github.io`.
+    pub fn is_valid(&self) -> bool {
+        is_rp_id(&self.normalized())
+    }
+
+    /// The identifier as a string, as it was written.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The identifier lowercased and without its trailing dot, which is the form compared by the
+    /// [PartialEq], [Hash] and [Ord] implementations and used by WebAuthn ceremonies.
+    pub fn normalized(&self) -> String {
+        self.trimmed().to_ascii_lowercase()
+    }
+
+    /// The identifier without its trailing dot.
+    fn trimmed(&self) -> &str {
+        self.0.strip_suffix('.').unwrap_or(&self.0)
+    }
+
+    /// The bytes of the [normalized][RpId::normalized] identifier.
+    fn normalized_bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.trimmed().bytes().map(|byte| byte.to_ascii_lowercase())
+    }
+
+    /// Whether the identifier is usable by the website at `host`, i.e. whether it is equal to the
+    /// host or one of its parent domains, as required during a WebAuthn ceremony. Always false
+    /// for [invalid][RpId::is_valid] identifiers.
+    pub fn is_valid_for(&self, host: &str) -> bool {
+        let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+        self.is_valid()
+            && host
+                .strip_suffix(self.normalized().as_str())
+                .is_some_and(|subdomain| subdomain.is_empty() || subdomain.ends_with('.'))
+    }
+}
+
+impl PartialEq for RpId {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed().eq_ignore_ascii_case(other.trimmed())
+    }
+}
+
+impl Eq for RpId {}
+
+impl std::hash::Hash for RpId {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.normalized_bytes()
+            .for_each(|byte| state.write_u8(byte));
+        // Terminated like the Hash implementation of str.
+        state.write_u8(0xff);
+    }
+}
+
+impl PartialOrd for RpId {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RpId {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.normalized_bytes().cmp(other.normalized_bytes())
+    }
+}
+
+impl std::fmt::Display for RpId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for RpId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<RpId> for String {
+    fn from(src: RpId) -> Self {
+        src.0
+    }
+}
+
+impl TryFrom<&str> for RpId {
+    type Error = InvalidRpId;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl TryFrom<String> for RpId {
+    type Error = InvalidRpId;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl std::str::FromStr for RpId {
+    type Err = InvalidRpId;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for RpId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for RpId {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        crate::arbitrary_values::domain(u).map(Self)
+    }
+}
+
+/// An error returned when a string is not a valid relying party identifier.
+#[derive(Debug)]
+pub struct InvalidRpId;
+
+impl std::fmt::Display for InvalidRpId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Data isn't a valid relying party identifier")
+    }
+}
+
+/// Whether the lowercase `value` is a domain name of at least two labels which isn't a public
+/// suffix.
+fn is_rp_id(value: &str) -> bool {
+    let valid_label = |label: &str| {
+        (1..=MAX_LABEL_LENGTH).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+    };
+    if value.len() > MAX_LENGTH || !value.split('.').all(valid_label) {
+        return false;
+    }
+    let labels: Vec<&str> = value.rsplit('.').collect();
+    match labels.as_slice() {
+        [] | [_] => false,
+        [tld, second] => !(tld.len() == 2 && SECOND_LEVEL_SUFFIXES.contains(second)),
+        _ => true,
+    }
+}
@@ -0,0 +1,36 @@
+//! Example documents of the [Credential Exchange Format][crate::format], for the tests and demos
+//! of importing and exporting providers.
+//!
+//! The documents are synthetic: every identifier, secret and key they contain is a dummy value.
+//!
+//! The example documents of the specification aren't bundled: they are published by the FIDO
+//! Alliance under its own terms rather than the MIT license of this crate, and change with every
+//! draft of the specification. Neither are exports of real providers, as none are available which
+//! could be redistributed, and documents written by hand in their name would misrepresent what
+//! those providers emit. The samples are written after the specification instead, and further ones
+//! can be added as functions of this module once such documents can be shipped.
+//!
+//! ```
+//! use credential_exchange_types::{format::Header, samples};
+//!
+//! let header: Header = serde_json::from_str(samples::basic()).unwrap();
+//! assert_eq!(header.accounts.len(), 1);
+//! ```
+
+/// A single account holding a login with a password, a passkey and a TOTP, organized in a
+/// collection.
+pub fn basic() -> &'static str {
+    include_str!("../samples/basic.json")
+}
+
+/// A single account holding an item for every [Credential][crate::format::Credential] type known
+/// to this crate.
+pub fn all_credentials() -> &'static str {
+    include_str!("../samples/all_credentials.json")
+}
+
+/// Iterate over every sample as `(name, document)` pairs, where the name is the one of the
+/// function returning the document.
+pub fn all() -> impl Iterator<Item = (&'static str, &'static str)> {
+    [("basic", basic()), ("all_credentials", all_credentials())].into_iter()
+}
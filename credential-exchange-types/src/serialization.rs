@@ -0,0 +1,74 @@
+//! Configuration of how documents are serialized.
+//!
+//! By default, optional members without a value are omitted from the output, as recommended by
+//! the specification. Some importers however require them to be present as `null`, which can be
+//! requested by serializing a value wrapped with [with_optional_members]:
+//!
+//! ```
+//! use credential_exchange_types::{
+//!     format::Header,
+//!     serialization::{with_optional_members, OptionalMembers},
+//! };
+//!
+//! fn serialize(header: &Header) -> serde_json::Result<String> {
+//!     serde_json::to_string(&with_optional_members(header, OptionalMembers::Null))
+//! }
+//! ```
+
+use std::cell::Cell;
+
+use serde::{Serialize, Serializer};
+
+/// How optional members without a value are serialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OptionalMembers {
+    /// The member is left out of the serialized object.
+    #[default]
+    Omit,
+    /// The member is serialized with an explicit `null` value.
+    Null,
+}
+
+/// A value serialized with its optional members according to a given [OptionalMembers] mode,
+/// created by [with_optional_members].
+#[derive(Clone, Copy, Debug)]
+pub struct WithOptionalMembers<'a, T: ?Sized> {
+    value: &'a T,
+    mode: OptionalMembers,
+}
+
+/// Wrap `value` so that its optional members without a value are serialized according to `mode`,
+/// at any depth. Serializing `value` directly always [omits][OptionalMembers::Omit] them.
+pub fn with_optional_members<T: ?Sized>(
+    value: &T,
+    mode: OptionalMembers,
+) -> WithOptionalMembers<'_, T> {
+    WithOptionalMembers { value, mode }
+}
+
+impl<T: Serialize + ?Sized> Serialize for WithOptionalMembers<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // The `skip_serializing_if` predicates of the types don't have access to the serializer,
+        // so the mode is made available to them for the duration of this call only, and the
+        // previous one is restored afterwards, even on panics.
+        struct Restore(OptionalMembers);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                OPTIONAL_MEMBERS.with(|current| current.set(self.0));
+            }
+        }
+
+        let _restore = Restore(OPTIONAL_MEMBERS.with(|current| current.replace(self.mode)));
+        self.value.serialize(serializer)
+    }
+}
+
+thread_local! {
+    static OPTIONAL_MEMBERS: Cell<OptionalMembers> = const { Cell::new(OptionalMembers::Omit) };
+}
+
+/// Whether an optional member should be skipped, for use with `skip_serializing_if`.
+pub(crate) fn is_omitted<T>(value: &Option<T>) -> bool {
+    value.is_none() && OPTIONAL_MEMBERS.with(Cell::get) == OptionalMembers::Omit
+}
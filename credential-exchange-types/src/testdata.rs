@@ -0,0 +1,631 @@
+//! Generator of realistic fake documents of the [Credential Exchange Format][crate::format], for
+//! demos, benchmarks and the development of importer interfaces. Unlike the fixed
+//! [samples][crate::samples], the size and content of the documents are configurable.
+//!
+//! Every value is fake but structurally valid: private keys are PKCS#8 encoded, card numbers pass
+//! the Luhn check and relying party identifiers are plausible domains. The generation is
+//! deterministic for a given [seed][TestData::seed].
+//!
+//! ```
+//! use credential_exchange_types::{format::CredentialType, testdata::TestData};
+//!
+//! let document = TestData::empty()
+//!     .with(CredentialType::Passkey, 20)
+//!     .with(CredentialType::CreditCard, 5)
+//!     .generate();
+//! assert_eq!(document.header.accounts[0].items.len(), 25);
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, RngCore, SeedableRng};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    b64url::B32,
+    format::{
+        Account, Collection, Credential, CredentialType, Documents, FileCredential, Header, Item,
+        ItemType, LinkedItem,
+    },
+    B64Url, Email, RpId,
+};
+
+/// The PKCS#8 prefix of a P-256 private key without its public key, followed by the 32 bytes of
+/// the private scalar.
+const P256_PKCS8_PREFIX: [u8; 35] = [
+    0x30, 0x41, 0x02, 0x01, 0x00, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01,
+    0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x04, 0x27, 0x30, 0x25, 0x02, 0x01,
+    0x01, 0x04, 0x20,
+];
+/// The PKCS#8 prefix of an Ed25519 private key, followed by the 32 bytes of the seed.
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// The timestamp of the generated documents, 2024-01-01T00:00:00Z. Items are created and modified
+/// during the preceding years.
+const TIMESTAMP: u64 = 1_704_067_200;
+const FIVE_YEARS: u64 = 5 * 365 * 24 * 60 * 60;
+
+const GIVEN_NAMES: &[&str] = &[
+    "Alex", "Amara", "Chen", "Diego", "Elena", "Farah", "Hiro", "Ingrid", "Jonas", "Kemal", "Lena",
+    "Mateo", "Nadia", "Omar", "Priya", "Sofia",
+];
+const SURNAMES: &[&str] = &[
+    "Andersen", "Baptiste", "Costa", "Dubois", "Fischer", "Garcia", "Kowalski", "Li", "Moreau",
+    "Nakamura", "Okafor", "Rossi", "Silva", "Smith", "Tanaka", "Weber",
+];
+const WORDS: &[&str] = &[
+    "acme", "blue", "cloud", "corner", "daily", "falcon", "green", "harbor", "lumen", "maple",
+    "north", "nova", "orbit", "pixel", "river", "summit", "swift", "terra", "urban", "zen",
+];
+const SERVICES: &[&str] = &[
+    "bank", "books", "cloud", "games", "health", "mail", "market", "news", "pay", "photos", "shop",
+    "social", "stream", "travel",
+];
+const TLDS: &[&str] = &["com", "net", "org", "io", "app", "co.uk", "de", "fr"];
+const CITIES: &[&str] = &[
+    "Springfield",
+    "Riverside",
+    "Fairview",
+    "Greenville",
+    "Madison",
+];
+const FILE_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "txt", "docx"];
+
+/// The credential types which are generated, in order of generation.
+const GENERATED_TYPES: &[CredentialType] = &[
+    CredentialType::BasicAuth,
+    CredentialType::Passkey,
+    CredentialType::Totp,
+    CredentialType::SshKey,
+    CredentialType::CreditCard,
+    CredentialType::Note,
+    CredentialType::PersonName,
+    CredentialType::Address,
+    CredentialType::DriversLicense,
+    CredentialType::IdentityDocument,
+    CredentialType::Passport,
+    #[cfg(feature = "non-standard-credentials")]
+    CredentialType::BirthCertificate,
+    #[cfg(feature = "non-standard-credentials")]
+    CredentialType::InsurancePolicy,
+];
+
+/// The number of items generated for each credential type by [TestData::default].
+const DEFAULT_CREDENTIALS: &[(CredentialType, usize)] = &[
+    (CredentialType::BasicAuth, 10),
+    (CredentialType::Passkey, 5),
+    (CredentialType::Totp, 5),
+    (CredentialType::CreditCard, 2),
+    (CredentialType::Note, 3),
+    (CredentialType::SshKey, 2),
+    (CredentialType::PersonName, 1),
+    (CredentialType::Address, 1),
+    (CredentialType::DriversLicense, 1),
+    (CredentialType::IdentityDocument, 1),
+    (CredentialType::Passport, 1),
+    #[cfg(feature = "non-standard-credentials")]
+    (CredentialType::BirthCertificate, 1),
+    #[cfg(feature = "non-standard-credentials")]
+    (CredentialType::InsurancePolicy, 1),
+];
+
+/// The options of the generator. [TestData::default] generates a small document with a few
+/// credentials of every type, [TestData::empty] generates a document without any item.
+#[derive(Clone, Debug)]
+pub struct TestData {
+    /// The seed of the generator. The same options always generate the same document.
+    pub seed: u64,
+    /// The number of [Items][Item] to generate for each type of credential. Every item holds a
+    /// single credential, except for the files. [File][CredentialType::File],
+    /// [item reference][CredentialType::ItemReference] and unknown types are ignored.
+    pub credentials: HashMap<CredentialType, usize>,
+    /// The number of [FileCredentials][FileCredential] to attach to random items.
+    pub attachments: usize,
+    /// The number of [Collections][Collection] at every level of the tree.
+    pub collections: usize,
+    /// The number of levels of the tree of collections.
+    pub collection_depth: usize,
+}
+
+impl Default for TestData {
+    fn default() -> Self {
+        Self {
+            credentials: DEFAULT_CREDENTIALS.iter().cloned().collect(),
+            ..Self::empty().attachments(2).collections(3, 2)
+        }
+    }
+}
+
+impl TestData {
+    /// Options generating a single account without any item or collection.
+    pub fn empty() -> Self {
+        Self {
+            seed: 0,
+            credentials: HashMap::new(),
+            attachments: 0,
+            collections: 0,
+            collection_depth: 0,
+        }
+    }
+
+    /// Set the seed of the generator.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the number of items to generate for the credential type `ty`.
+    pub fn with(mut self, ty: CredentialType, count: usize) -> Self {
+        self.credentials.insert(ty, count);
+        self
+    }
+
+    /// Set the number of files to attach to random items.
+    pub fn attachments(mut self, count: usize) -> Self {
+        self.attachments = count;
+        self
+    }
+
+    /// Set the number of collections at every level, and the number of levels.
+    pub fn collections(mut self, count: usize, depth: usize) -> Self {
+        self.collections = count;
+        self.collection_depth = depth;
+        self
+    }
+
+    /// Generate a document with these options.
+    pub fn generate(&self) -> TestDocument {
+        Generator {
+            rng: StdRng::seed_from_u64(self.seed),
+        }
+        .document(self)
+    }
+}
+
+/// A document generated by [TestData::generate].
+#[derive(Clone, Debug)]
+pub struct TestDocument {
+    /// The generated exchange, holding a single [Account].
+    pub header: Header,
+    /// The content of the attached files, by [file id][FileCredential::id].
+    pub files: Documents,
+}
+
+struct Generator {
+    rng: StdRng,
+}
+
+/// The owner of the generated account.
+struct Person {
+    given: &'static str,
+    surname: &'static str,
+    email: String,
+}
+
+impl Person {
+    fn full_name(&self) -> String {
+        format!("{} {}", self.given, self.surname)
+    }
+}
+
+impl Generator {
+    fn document(&mut self, options: &TestData) -> TestDocument {
+        let given = self.pick(GIVEN_NAMES);
+        let surname = self.pick(SURNAMES);
+        let email = format!(
+            "{}.{}@{}",
+            given.to_lowercase(),
+            surname.to_lowercase(),
+            self.domain()
+        );
+        let person = Person {
+            given,
+            surname,
+            email,
+        };
+
+        let mut items = Vec::new();
+        for ty in GENERATED_TYPES {
+            for _ in 0..options.credentials.get(ty).copied().unwrap_or_default() {
+                items.push(self.item(ty, &person));
+            }
+        }
+
+        let mut files = Documents::new();
+        for _ in 0..options.attachments {
+            if items.is_empty() {
+                items.push(self.item(&CredentialType::Note, &person));
+            }
+            let (file, content) = self.file();
+            files.insert(file.id.clone(), content);
+            let index = self.rng.gen_range(0..items.len());
+            items[index]
+                .credentials
+                .push(Credential::File(Box::new(file)));
+        }
+
+        let collections = self.collections(options, &items);
+        let account = Account {
+            id: self.id(),
+            user_name: person.email.clone(),
+            email: Email::parse(&person.email).expect("generated addresses are valid"),
+            full_name: Some(person.full_name()),
+            icon: None,
+            collections,
+            items,
+            extensions: None,
+            extra: Default::default(),
+        };
+        let header = Header {
+            version: 0,
+            exporter: RpId::parse("exporter.example.com").expect("the exporter is a valid RP ID"),
+            timestamp: DateTime::from_timestamp(TIMESTAMP as i64, 0).unwrap_or_default(),
+            accounts: vec![account],
+            extensions: None,
+            extra: Default::default(),
+        };
+        TestDocument { header, files }
+    }
+
+    fn item(&mut self, ty: &CredentialType, person: &Person) -> Item {
+        let domain = self.domain();
+        let site = site_name(&domain);
+        let full_name = person.full_name();
+        let (item_type, title, credential) = match ty {
+            CredentialType::BasicAuth => {
+                let password = self.password();
+                let credential = json!({
+                    "type": "basic-auth",
+                    "urls": [format!("https://www.{domain}/login")],
+                    "username": self.field("string", &person.email),
+                    "password": self.field("concealed-string", &password),
+                });
+                (ItemType::Login, site, credential)
+            }
+            CredentialType::Passkey => {
+                let credential = json!({
+                    "type": "passkey",
+                    "credentialId": self.bytes(16),
+                    "rpId": domain,
+                    "userName": person.email,
+                    "userDisplayName": full_name,
+                    "userHandle": self.bytes(16),
+                    "key": self.private_key(&P256_PKCS8_PREFIX),
+                });
+                (ItemType::Login, site, credential)
+            }
+            CredentialType::Totp => {
+                let secret: String = B32::from(self.random_bytes(20)).into();
+                let credential = json!({
+                    "type": "totp",
+                    "secret": secret,
+                    "period": 30,
+                    "digits": 6,
+                    "username": person.email,
+                    "algorithm": "sha1",
+                    "issuer": site,
+                });
+                (ItemType::Login, site, credential)
+            }
+            CredentialType::SshKey => {
+                let credential = json!({
+                    "type": "ssh-key",
+                    "keyType": "ssh-ed25519",
+                    "privateKey": self.private_key(&ED25519_PKCS8_PREFIX),
+                    "keyComment": format!("{}@{domain}", person.given.to_lowercase()),
+                });
+                (ItemType::Login, format!("{site} server"), credential)
+            }
+            CredentialType::CreditCard => {
+                let expiry_year: u32 = self.rng.gen_range(2025..2035);
+                let expiry_month: u32 = self.rng.gen_range(1..13);
+                let credential = json!({
+                    "type": "credit-card",
+                    "number": self.card_number(),
+                    "fullName": full_name,
+                    "cardType": "Visa",
+                    "verificationNumber": self.digits(3),
+                    "expiryDate": format!("{expiry_year}-{expiry_month:02}"),
+                });
+                (ItemType::Identity, format!("{site} card"), credential)
+            }
+            CredentialType::Note => {
+                let credential = json!({ "type": "note", "content": self.sentence() });
+                (ItemType::Document, format!("{site} notes"), credential)
+            }
+            CredentialType::PersonName => {
+                let credential = json!({
+                    "type": "person-name",
+                    "given": self.field("string", person.given),
+                    "surname": self.field("string", person.surname),
+                });
+                (ItemType::Identity, full_name, credential)
+            }
+            CredentialType::Address => {
+                let number: u32 = self.rng.gen_range(1..200);
+                let street = format!("{number} {} Street", capitalize(self.pick(WORDS)));
+                let postal_code = self.digits(5);
+                let city = self.pick(CITIES);
+                let credential = json!({
+                    "type": "address",
+                    "streetAddress": self.field("string", &street),
+                    "postalCode": self.field("string", &postal_code),
+                    "city": self.field("string", city),
+                    "country": self.field("string", "US"),
+                });
+                (ItemType::Identity, "Home".to_owned(), credential)
+            }
+            CredentialType::DriversLicense => {
+                let birth_date = self.date(1950..2005);
+                let number = format!("D{}", self.digits(7));
+                let credential = json!({
+                    "type": "drivers-license",
+                    "fullName": self.field("string", &full_name),
+                    "birthDate": self.field("date", &birth_date),
+                    "licenseNumber": self.field("string", &number),
+                    "country": self.field("string", "US"),
+                });
+                (
+                    ItemType::Identity,
+                    "Driver's license".to_owned(),
+                    credential,
+                )
+            }
+            CredentialType::IdentityDocument => {
+                let number = format!("ID{}", self.digits(6));
+                let expiry_date = self.date(2025..2035);
+                let credential = json!({
+                    "type": "identity-document",
+                    "documentNumber": self.field("string", &number),
+                    "fullName": self.field("string", &full_name),
+                    "expiryDate": self.field("date", &expiry_date),
+                });
+                (ItemType::Identity, "Identity card".to_owned(), credential)
+            }
+            CredentialType::Passport => {
+                let number = format!("P{}", self.digits(7));
+                let credential = json!({
+                    "type": "passport",
+                    "passportNumber": self.field("string", &number),
+                    "issuingCountry": self.field("string", "US"),
+                    "fullName": self.field("string", &full_name),
+                });
+                (ItemType::Identity, "Passport".to_owned(), credential)
+            }
+            #[cfg(feature = "non-standard-credentials")]
+            CredentialType::BirthCertificate => {
+                let number = format!("B{}", self.digits(8));
+                let birth_date = self.date(1950..2005);
+                let city = self.pick(CITIES);
+                let credential = json!({
+                    "type": "birth-certificate",
+                    "registrationNumber": self.field("string", &number),
+                    "fullName": self.field("string", &full_name),
+                    "birthDate": self.field("date", &birth_date),
+                    "birthPlace": self.field("string", city),
+                });
+                (
+                    ItemType::Identity,
+                    "Birth certificate".to_owned(),
+                    credential,
+                )
+            }
+            #[cfg(feature = "non-standard-credentials")]
+            CredentialType::InsurancePolicy => {
+                let number = format!("HP-{}", self.digits(9));
+                let start_date = self.date(2018..2024);
+                let expiry_date = self.date(2025..2030);
+                let credential = json!({
+                    "type": "insurance-policy",
+                    "insurer": self.field("string", "Example Health"),
+                    "policyNumber": self.field("string", &number),
+                    "policyType": self.field("string", "health"),
+                    "startDate": self.field("date", &start_date),
+                    "expiryDate": self.field("date", &expiry_date),
+                });
+                (
+                    ItemType::Identity,
+                    "Health insurance".to_owned(),
+                    credential,
+                )
+            }
+            CredentialType::ItemReference | CredentialType::File | CredentialType::Unknown(_) => {
+                unreachable!("only the generated types are passed")
+            }
+        };
+        let credential: Credential =
+            serde_json::from_value(credential).expect("generated credentials are valid");
+
+        let created = TIMESTAMP - self.rng.gen_range(0..FIVE_YEARS);
+        let modified = created + self.rng.gen_range(0..TIMESTAMP - created + 1);
+        Item {
+            id: self.id(),
+            creation_at: DateTime::from_timestamp(created as i64, 0),
+            modified_at: DateTime::from_timestamp(modified as i64, 0),
+            ty: item_type,
+            title,
+            subtitle: None,
+            favorite: self.rng.gen_bool(0.1).then_some(true),
+            credentials: vec![credential],
+            tags: None,
+            extensions: None,
+            extra: Default::default(),
+        }
+    }
+
+    /// A tree of collections, each linking some of the items.
+    fn collections(&mut self, options: &TestData, items: &[Item]) -> Vec<Collection> {
+        // The collections are generated breadth first, so that children always come after their
+        // parent, and then assembled from the leaves up.
+        let mut nodes: Vec<(Option<usize>, Collection)> = Vec::new();
+        let mut level: Vec<Option<usize>> = vec![None];
+        for _ in 0..options.collection_depth {
+            let mut next = Vec::new();
+            for parent in level {
+                for _ in 0..options.collections {
+                    next.push(Some(nodes.len()));
+                    nodes.push((parent, self.collection()));
+                }
+            }
+            level = next;
+        }
+        if nodes.is_empty() {
+            return Vec::new();
+        }
+
+        for item in items {
+            if self.rng.gen_bool(0.5) {
+                let index = self.rng.gen_range(0..nodes.len());
+                nodes[index].1.items.push(LinkedItem {
+                    item: item.id.clone(),
+                    account: None,
+                    extra: Default::default(),
+                });
+            }
+        }
+
+        let mut roots = Vec::new();
+        while let Some((parent, collection)) = nodes.pop() {
+            match parent {
+                Some(parent) => nodes[parent]
+                    .1
+                    .sub_collections
+                    .get_or_insert_with(Vec::new)
+                    .insert(0, collection),
+                None => roots.insert(0, collection),
+            }
+        }
+        roots
+    }
+
+    fn collection(&mut self) -> Collection {
+        Collection {
+            id: self.id(),
+            title: capitalize(self.pick(WORDS)),
+            subtitle: None,
+            icon: None,
+            items: Vec::new(),
+            sub_collections: None,
+            extensions: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn file(&mut self) -> (FileCredential, Vec<u8>) {
+        let length = self.rng.gen_range(256..4096);
+        let content = self.random_bytes(length);
+        let file = FileCredential {
+            id: self.id(),
+            name: format!("{}.{}", self.pick(WORDS), self.pick(FILE_EXTENSIONS)),
+            decrypted_size: content.len() as u64,
+            integration_hash: B64Url::from(Sha256::digest(&content).to_vec()),
+            extra: Default::default(),
+        };
+        (file, content)
+    }
+
+    fn field(&mut self, field_type: &str, value: &str) -> Value {
+        json!({ "id": self.bytes(16), "fieldType": field_type, "value": value })
+    }
+
+    fn id(&mut self) -> B64Url {
+        B64Url::from(self.random_bytes(B64Url::DEFAULT_ID_LENGTH))
+    }
+
+    fn random_bytes(&mut self, length: usize) -> Vec<u8> {
+        let mut bytes = vec![0; length];
+        self.rng.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    fn bytes(&mut self, length: usize) -> String {
+        B64Url::from(self.random_bytes(length)).into()
+    }
+
+    fn private_key(&mut self, prefix: &[u8]) -> String {
+        let mut key = prefix.to_vec();
+        key.extend(self.random_bytes(32));
+        B64Url::from(key).into()
+    }
+
+    fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+        *values.choose(&mut self.rng).expect("values are not empty")
+    }
+
+    /// A plausible domain, e.g. `northbank.com`.
+    fn domain(&mut self) -> String {
+        format!(
+            "{}{}.{}",
+            self.pick(WORDS),
+            self.pick(SERVICES),
+            self.pick(TLDS)
+        )
+    }
+
+    fn password(&mut self) -> String {
+        const CHARACTERS: &[u8] =
+            b"abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789!#$%&*+-=?@";
+        (0..self.rng.gen_range(12..24))
+            .map(|_| char::from(self.pick(CHARACTERS)))
+            .collect()
+    }
+
+    fn digits(&mut self, length: usize) -> String {
+        (0..length)
+            .map(|_| char::from(b'0' + self.rng.gen_range(0..10)))
+            .collect()
+    }
+
+    /// A 16 digit Visa number with a valid Luhn check digit.
+    fn card_number(&mut self) -> String {
+        let mut number = format!("4{}", self.digits(14));
+        let sum: u32 = number
+            .bytes()
+            .rev()
+            .enumerate()
+            .map(|(position, byte)| {
+                let digit = u32::from(byte - b'0');
+                match position % 2 {
+                    0 if digit * 2 > 9 => digit * 2 - 9,
+                    0 => digit * 2,
+                    _ => digit,
+                }
+            })
+            .sum();
+        number.push(char::from(b'0' + ((10 - sum % 10) % 10) as u8));
+        number
+    }
+
+    fn date(&mut self, years: std::ops::Range<u32>) -> String {
+        format!(
+            "{}-{:02}-{:02}",
+            self.rng.gen_range(years),
+            self.rng.gen_range(1..13),
+            self.rng.gen_range(1..29)
+        )
+    }
+
+    fn sentence(&mut self) -> String {
+        let words: Vec<&str> = (0..self.rng.gen_range(5..15))
+            .map(|_| self.pick(WORDS))
+            .collect();
+        format!("{}.", capitalize(&words.join(" ")))
+    }
+}
+
+/// The display name of the site at `domain`, e.g. `Northbank` for `northbank.com`.
+fn site_name(domain: &str) -> String {
+    capitalize(domain.split('.').next().unwrap_or(domain))
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
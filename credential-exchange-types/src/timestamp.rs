@@ -0,0 +1,176 @@
+//! Serde helpers for the timestamps of the format, to be used with `#[serde(with = "timestamp")]`
+//! on [`DateTime<Utc>`] members, or `#[serde(with = "timestamp::option")]` on optional members.
+//!
+//! Timestamps are always serialized as an integer number of seconds since the UNIX epoch, as
+//! mandated by the specification. Deserialization is flexible in order to accept the variants
+//! produced by existing exporters:
+//!
+//! - integer seconds, e.g. `1700000000`,
+//! - integer milliseconds, e.g. `1700000000000`. Any value of at least [MILLISECONDS_THRESHOLD] is
+//!   considered to be in milliseconds,
+//! - fractional seconds or milliseconds, e.g. `1700000000.123`, of which the fraction is dropped,
+//! - [RFC3339](https://www.rfc-editor.org/rfc/rfc3339) strings, e.g. `"2023-11-14T22:13:20Z"`, of
+//!   which the fraction of seconds is dropped as well, and strings holding any of the numeric
+//!   variants above.
+//!
+//! Timestamps of at least [MILLISECONDS_THRESHOLD] seconds, in absolute value, are rejected as
+//! they could not be serialized back without being mistaken for milliseconds.
+
+use chrono::{DateTime, Utc};
+use serde::{
+    de::{self, Visitor},
+    Deserializer, Serializer,
+};
+
+/// The smallest numeric timestamp, in absolute value, which is interpreted as milliseconds rather
+/// than seconds. In seconds, it lies more than three thousand years in the future, while in
+/// milliseconds it lies in 1973, before any export could have been made.
+pub const MILLISECONDS_THRESHOLD: u64 = 100_000_000_000;
+
+/// Serialize a timestamp as an integer number of seconds.
+pub fn serialize<S: Serializer>(
+    timestamp: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(timestamp.timestamp())
+}
+
+/// Deserialize a timestamp from any of the supported representations.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    deserializer.deserialize_any(TimestampVisitor)
+}
+
+struct TimestampVisitor;
+
+impl Visitor<'_> for TimestampVisitor {
+    type Value = DateTime<Utc>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a UNIX timestamp or an RFC3339 date-time")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        let seconds = if value.unsigned_abs() >= MILLISECONDS_THRESHOLD {
+            value / 1000
+        } else {
+            value
+        };
+        from_seconds(seconds).ok_or_else(|| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        let value = i64::try_from(value)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))?;
+        self.visit_i64(value)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        if !value.is_finite() || value.abs() >= i64::MAX as f64 {
+            return Err(E::invalid_value(de::Unexpected::Float(value), &self));
+        }
+        self.visit_i64(value.trunc() as i64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return from_seconds(datetime.timestamp())
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self));
+        }
+        if let Ok(number) = value.parse::<i64>() {
+            return self.visit_i64(number);
+        }
+        match value.parse::<f64>() {
+            Ok(number) => self.visit_f64(number),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+        }
+    }
+}
+
+/// Create a timestamp from a number of seconds, if it can be serialized back unambiguously.
+fn from_seconds(seconds: i64) -> Option<DateTime<Utc>> {
+    if seconds.unsigned_abs() >= MILLISECONDS_THRESHOLD {
+        return None;
+    }
+    DateTime::from_timestamp(seconds, 0)
+}
+
+/// The equivalent of the parent module for `Option<DateTime<Utc>>` members, where `null` is
+/// deserialized as [None].
+pub mod option {
+    use chrono::{DateTime, Utc};
+    use serde::{
+        de::{self, Visitor},
+        Deserializer, Serializer,
+    };
+
+    /// Serialize an optional timestamp as an integer number of seconds, or `null`.
+    pub fn serialize<S: Serializer>(
+        timestamp: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(timestamp) => super::serialize(timestamp, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserialize an optional timestamp from `null` or any of the supported representations.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        deserializer.deserialize_option(OptionVisitor)
+    }
+
+    struct OptionVisitor;
+
+    impl<'de> Visitor<'de> for OptionVisitor {
+        type Value = Option<DateTime<Utc>>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an optional UNIX timestamp or RFC3339 date-time")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            super::deserialize(deserializer).map(Some)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Timestamp(#[serde(with = "super")] chrono::DateTime<chrono::Utc>);
+
+    fn parse(value: serde_json::Value) -> Timestamp {
+        serde_json::from_value(value).expect("the timestamp is valid")
+    }
+
+    #[test]
+    fn fractions_of_seconds_are_dropped() {
+        let expected = parse(1700000000.into());
+        assert_eq!(parse(1700000000.5.into()), expected);
+        assert_eq!(parse("2023-11-14T22:13:20.5Z".into()), expected);
+        assert_eq!(parse("2023-11-14T23:13:20.999+01:00".into()), expected);
+    }
+
+    #[test]
+    fn rfc3339_round_trip() {
+        let parsed = parse("2023-11-14T22:13:20.5Z".into());
+        let serialized = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(serialized, 1700000000);
+        assert_eq!(parse(serialized), parsed);
+    }
+}
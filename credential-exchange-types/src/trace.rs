@@ -0,0 +1,23 @@
+//! Instrumentation emitted through [tracing](https://docs.rs/tracing) when the `tracing` feature
+//! is enabled. Only counts and sizes are recorded, never the content of a document, and the
+//! duration of each operation is given by its span. Without the feature, these macros expand to
+//! nothing.
+
+/// Enter a span at the debug level until the end of the enclosing block.
+macro_rules! debug_span {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!($($arg)+).entered();
+    };
+}
+
+/// Emit an event at the debug level.
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)+);
+    };
+}
+
+pub(crate) use debug;
+pub(crate) use debug_span;
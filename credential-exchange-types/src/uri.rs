@@ -0,0 +1,356 @@
+//! The [Uri] type of the URLs stored in credentials, and helpers to normalize them so that they
+//! can be compared.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// A [RFC3986](https://www.rfc-editor.org/rfc/rfc3986) URI reference, such as
+/// `https://example.com/login`.
+///
+/// Relative references are accepted, as exporters frequently omit the scheme of websites, e.g.
+/// `example.com`, and so are non-ASCII characters as in
+/// [RFC3987](https://www.rfc-editor.org/rfc/rfc3987) IRIs, e.g. `https://bücher.de`. The trailing
+/// slash of an empty path is ignored when comparing, so that `https://example.com/` and
+/// `https://example.com` are equal [Uri]s, but the value is kept as it is and serialized unchanged.
+///
+/// Deserialization doesn't validate the syntax of the value, so that a credential with a malformed
+/// URL is still imported and exported unchanged. Use [is_valid][Uri::is_valid] to check it, or
+/// [parse][Uri::parse] to reject malformed values.
+#[derive(Clone, Debug, Serialize)]
+#[serde(into = "String")]
+pub struct Uri(String);
+
+impl Uri {
+    /// Parse a URI reference, validating its syntax.
+    pub fn parse(value: &str) -> Result<Self, InvalidUri> {
+        if is_uri_reference(value) {
+            Ok(Self::lenient(value))
+        } else {
+            Err(InvalidUri)
+        }
+    }
+
+    /// Create a [Uri] without validating its syntax, for values which must be kept even though
+    /// they are malformed.
+    pub fn lenient(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+
+    /// Whether the value follows the syntax of a URI reference, which is always the case for
+    /// values created with [parse][Uri::parse] but not for [deserialized][Deserialize] or
+    /// [lenient][Uri::lenient] ones.
+    pub fn is_valid(&self) -> bool {
+        is_uri_reference(&self.0)
+    }
+
+    /// The URI as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The scheme of the URI, e.g. `https`, or [None] for relative references.
+    pub fn scheme(&self) -> Option<&str> {
+        split(&self.0).scheme
+    }
+
+    /// The host of the URI, e.g. `example.com`, without the brackets of IPv6 addresses. For
+    /// relative references without a scheme such as `example.com/login`, the first segment is
+    /// considered to be the host.
+    pub fn host(&self) -> Option<&str> {
+        let authority = split(&self.0).authority?;
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = match authority.strip_prefix('[') {
+            Some(ipv6) => ipv6.split_once(']').map_or(ipv6, |(host, _)| host),
+            None => authority
+                .rsplit_once(':')
+                .map_or(authority, |(host, _)| host),
+        };
+        (!host.is_empty()).then_some(host)
+    }
+
+    /// Convert the internationalized domain name of the host to its lowercase ASCII form, e.g.
+    /// `https://Bücher.de/a` to `https://xn--bcher-kva.de/a`. Returns [None] if the host cannot be
+    /// encoded.
+    ///
+    /// URLs are matched on their ASCII form, e.g. by
+    /// [Account::items_for_url][crate::format::Account::items_for_url], so that both forms of a
+    /// domain refer to the same website.
+    pub fn to_ascii(&self) -> Option<Self> {
+        self.map_host(crate::idna::domain_to_ascii)
+    }
+
+    /// Convert the ASCII encoded labels of the host to their Unicode form for display, e.g.
+    /// `https://xn--bcher-kva.de/a` to `https://bücher.de/a`.
+    pub fn to_unicode(&self) -> Self {
+        self.map_host(|host| Some(crate::idna::domain_to_unicode(host)))
+            .unwrap_or_else(|| self.clone())
+    }
+
+    fn map_host(&self, f: impl FnOnce(&str) -> Option<String>) -> Option<Self> {
+        let Some(host) = self.host().filter(|host| !host.contains(':')) else {
+            return Some(self.clone());
+        };
+        let start = host.as_ptr() as usize - self.0.as_ptr() as usize;
+        let end = start + host.len();
+        Some(Self(format!(
+            "{}{}{}",
+            &self.0[..start],
+            f(host)?,
+            &self.0[end..]
+        )))
+    }
+}
+
+impl Uri {
+    /// The value compared by the [PartialEq], [Hash] and [Ord] implementations, without the
+    /// trailing slash of an empty path.
+    fn normalized(&self) -> &str {
+        match self.0.strip_suffix('/') {
+            Some(stripped) if split(&self.0).path == "/" && !self.0.contains(['?', '#']) => {
+                stripped
+            }
+            _ => &self.0,
+        }
+    }
+}
+
+impl PartialEq for Uri {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for Uri {}
+
+impl std::hash::Hash for Uri {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl PartialOrd for Uri {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Uri {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.normalized().cmp(other.normalized())
+    }
+}
+
+impl std::fmt::Display for Uri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Uri {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Uri> for String {
+    fn from(src: Uri) -> Self {
+        src.0
+    }
+}
+
+impl TryFrom<&str> for Uri {
+    type Error = InvalidUri;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+impl TryFrom<String> for Uri {
+    type Error = InvalidUri;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl std::str::FromStr for Uri {
+    type Err = InvalidUri;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Uri {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Ok(Self::lenient(&value))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Uri {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self(format!(
+            "https://{}",
+            crate::arbitrary_values::domain(u)?
+        )))
+    }
+}
+
+/// An error returned when a string is not a valid URI reference.
+#[derive(Debug)]
+pub struct InvalidUri;
+
+impl std::fmt::Display for InvalidUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Data isn't a valid URI reference")
+    }
+}
+
+/// The components of a URI reference which are not validated.
+struct Components<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+}
+
+/// Split a URI reference into its components. A reference without a scheme nor an authority is
+/// considered to start with its authority, as exporters frequently omit the scheme of websites.
+fn split(value: &str) -> Components<'_> {
+    let end = value.find(['?', '#']).unwrap_or(value.len());
+    let value = &value[..end];
+    let (scheme, rest) = match value.split_once(':') {
+        Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+        _ => (None, value),
+    };
+    let (authority, path) = match (scheme, rest.strip_prefix("//")) {
+        (_, Some(rest)) => {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (Some(&rest[..end]), &rest[end..])
+        }
+        (Some(_), None) => (None, rest),
+        (None, None) => {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (Some(&rest[..end]), &rest[end..])
+        }
+    };
+    Components {
+        scheme,
+        authority,
+        path,
+    }
+}
+
+/// Whether the value follows the syntax of an
+/// [RFC3986 URI reference](https://www.rfc-editor.org/rfc/rfc3986#section-4.1).
+fn is_uri_reference(value: &str) -> bool {
+    if value.is_empty()
+        || value.matches('#').count() > 1
+        || value.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return false;
+    }
+
+    let bytes = value.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let escaped = bytes.get(index + 1..index + 3);
+                if !escaped.is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) {
+                    return false;
+                }
+                index += 3;
+                continue;
+            }
+            byte if byte.is_ascii_alphanumeric() => {}
+            b'-' | b'.' | b'_' | b'~' => {}
+            b':' | b'/' | b'?' | b'#' | b'[' | b']' | b'@' => {}
+            b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => {}
+            byte if !byte.is_ascii() => {}
+            _ => return false,
+        }
+        index += 1;
+    }
+
+    // A colon in the first segment must delimit a valid scheme.
+    let first_segment = value.split(['/', '?', '#']).next().unwrap_or_default();
+    match first_segment.split_once(':') {
+        Some((scheme, _)) => is_scheme(scheme),
+        None => true,
+    }
+}
+
+/// The parts of a URL which are relevant when matching a credential against a website.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Origin {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+}
+
+impl Origin {
+    /// Parse the origin of a URL. Exporters frequently omit the scheme, in which case `https` is
+    /// assumed. The scheme and host are lowercased, internationalized hosts are converted to
+    /// their ASCII form, a trailing dot is removed from the host and default ports are dropped.
+    pub(crate) fn parse(uri: &str) -> Option<Self> {
+        let uri = uri.trim();
+        let (scheme, rest) = match uri.split_once("://") {
+            Some((scheme, rest)) if is_scheme(scheme) => (scheme.to_ascii_lowercase(), rest),
+            _ => ("https".to_owned(), uri),
+        };
+
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+
+        let (host, port) = if let Some(ipv6) = authority.strip_prefix('[') {
+            let (host, rest) = ipv6.split_once(']')?;
+            (host, rest.strip_prefix(':'))
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+        let port = match port {
+            Some("") | None => None,
+            Some(port) => Some(port.parse().ok()?),
+        };
+
+        let host = crate::idna::domain_to_ascii(host.trim_end_matches('.'))?;
+        if host.is_empty() {
+            return None;
+        }
+
+        let port = match (scheme.as_str(), port) {
+            ("http", Some(80)) | ("https", Some(443)) => None,
+            (_, port) => port,
+        };
+
+        Some(Self { scheme, host, port })
+    }
+
+    /// The lowercased ASCII host, without the brackets of IPv6 addresses.
+    pub(crate) fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Whether both origins refer to the same website: the scheme, host and port must all match,
+    /// so that a credential saved for `https` isn't offered to the same host over plain `http`.
+    pub(crate) fn matches(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// Whether the value is a valid [RFC3986](https://www.rfc-editor.org/rfc/rfc3986#section-3.1)
+/// scheme.
+fn is_scheme(value: &str) -> bool {
+    let mut chars = value.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}